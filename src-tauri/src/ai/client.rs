@@ -0,0 +1,99 @@
+//! 大模型 HTTP 客户端
+//!
+//! 请求格式由 `LlmProvider` 决定，这里只负责发送请求与读取（流式）响应。
+
+use std::time::Duration;
+
+use super::provider::{provider_for, LlmProvider, StreamChunk};
+use super::stream::StreamDecoder;
+use super::types::ChatMessage;
+use super::AiError;
+use crate::config::AIConfig;
+
+/// 单次非流式请求的超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// 建立连接的超时时间
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub struct AiClient {
+    http: reqwest::Client,
+    provider: Box<dyn LlmProvider>,
+    config: AIConfig,
+}
+
+impl AiClient {
+    pub fn new(config: AIConfig) -> Result<Self, AiError> {
+        let provider = provider_for(config.provider);
+        if !is_config_complete(&config, provider.as_ref()) {
+            return Err(AiError::ConfigIncomplete);
+        }
+        let http = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(AiError::Http)?;
+        Ok(Self { http, provider, config })
+    }
+
+    pub fn config(&self) -> &AIConfig {
+        &self.config
+    }
+
+    async fn send(&self, messages: &[ChatMessage], stream: bool) -> Result<reqwest::Response, AiError> {
+        let mut builder = self.provider.build_request(&self.http, &self.config, messages, stream);
+        // 流式响应持续时间不可预知，只对非流式请求设置总超时
+        if !stream {
+            builder = builder.timeout(REQUEST_TIMEOUT);
+        }
+
+        let response = builder.send().await.map_err(AiError::Http)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AiError::Api { status: status.as_u16(), body });
+        }
+        Ok(response)
+    }
+
+    /// 发送一次非流式请求，返回回复文本
+    pub async fn chat(&self, messages: Vec<ChatMessage>) -> Result<String, AiError> {
+        let response = self.send(&messages, false).await?;
+        let body: serde_json::Value = response.json().await.map_err(AiError::Http)?;
+        self.provider
+            .parse_completion(&body)
+            .filter(|content| !content.is_empty())
+            .ok_or(AiError::EmptyResponse)
+    }
+
+    /// 发送一次流式请求，每收到一段增量文本就调用 `on_delta`
+    ///
+    /// 返回拼接后的完整文本。
+    pub async fn chat_stream<F>(&self, messages: Vec<ChatMessage>, mut on_delta: F) -> Result<String, AiError>
+    where
+        F: FnMut(&str),
+    {
+        let mut response = self.send(&messages, true).await?;
+        let mut decoder = StreamDecoder::new(self.provider.stream_framing());
+        let mut content = String::new();
+
+        while let Some(chunk) = response.chunk().await.map_err(AiError::Http)? {
+            for data in decoder.feed(&chunk) {
+                match self.provider.parse_stream_chunk(&data) {
+                    StreamChunk::Delta(delta) => {
+                        content.push_str(&delta);
+                        on_delta(&delta);
+                    }
+                    StreamChunk::Done => return Ok(content),
+                    StreamChunk::Skip => {}
+                }
+            }
+        }
+        Ok(content)
+    }
+}
+
+/// 必填项均已填写；本地服务（如 Ollama）不要求 API Key
+pub fn is_config_complete(config: &AIConfig, provider: &dyn LlmProvider) -> bool {
+    (!provider.requires_api_key() || !config.api_key.is_empty())
+        && !config.base_url.is_empty()
+        && !config.model.is_empty()
+}
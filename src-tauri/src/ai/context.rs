@@ -0,0 +1,270 @@
+//! 上下文窗口
+//!
+//! 按估算的 token 数把历史对话装进 `context_budget`：从最新的一轮往前保留，
+//! 放不下（或超过 `history_turns`）的较早对话交给 `Summarizer` 折叠进滚动摘要。
+//! 预算计算只依赖文本本身，结果是确定的，可以用桩摘要器做单元测试。
+
+use std::future::Future;
+
+use super::client::AiClient;
+use super::prompt;
+use super::types::ChatMessage;
+use super::AiError;
+use crate::config::AIConfig;
+use crate::emotion::EmotionRegistry;
+use crate::history::{HistoryTurn, SessionContext};
+
+/// 每条消息在角色、分隔符上的额外开销
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// 为新摘要预留的 token 数，同时也是生成摘要时的 max_tokens
+pub const SUMMARY_TOKEN_RESERVE: usize = 400;
+
+/// 估算一段文本的 token 数
+///
+/// 中日韩字符按每字 1 个 token 计，其余字符按每 4 个 1 个 token 计（向上取整）。
+pub fn estimate_tokens(text: &str) -> usize {
+    let (wide, narrow) = text.chars().fold((0usize, 0usize), |(wide, narrow), c| {
+        if is_wide(c) { (wide + 1, narrow) } else { (wide, narrow + 1) }
+    });
+    wide + narrow.div_ceil(4)
+}
+
+/// CJK 及全角字符
+fn is_wide(c: char) -> bool {
+    c as u32 >= 0x2E80
+}
+
+/// 估算一组消息的 token 数
+pub fn estimate_messages(messages: &[ChatMessage]) -> usize {
+    messages.iter().map(|message| estimate_tokens(&message.content) + MESSAGE_OVERHEAD_TOKENS).sum()
+}
+
+/// 把较早的对话折叠进滚动摘要
+pub trait Summarizer {
+    /// 在 `previous` 的基础上并入 `turns`，返回新的摘要
+    fn summarize(
+        &self,
+        previous: Option<&str>,
+        turns: &[HistoryTurn],
+    ) -> impl Future<Output = Result<String, AiError>> + Send;
+}
+
+/// 调用当前配置的模型生成摘要
+pub struct ModelSummarizer {
+    client: AiClient,
+}
+
+impl ModelSummarizer {
+    pub fn new(config: &AIConfig) -> Result<Self, AiError> {
+        let config = AIConfig {
+            temperature: 0.3,
+            max_tokens: SUMMARY_TOKEN_RESERVE as i32,
+            ..config.clone()
+        };
+        Ok(Self { client: AiClient::new(config)? })
+    }
+}
+
+impl Summarizer for ModelSummarizer {
+    async fn summarize(&self, previous: Option<&str>, turns: &[HistoryTurn]) -> Result<String, AiError> {
+        let summary = self.client.chat(prompt::summarize_messages(previous, turns)).await?;
+        Ok(summary.trim().to_string())
+    }
+}
+
+/// 装入预算后的上下文
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContextWindow {
+    /// 当前角色的表情注册表，用于生成回复格式要求与校验回复
+    pub emotions: EmotionRegistry,
+    /// 与本次消息相关的长期记忆，注入系统提示词
+    pub memories: Vec<String>,
+    /// 较早对话的摘要
+    pub summary: Option<String>,
+    /// 原样回放的对话
+    pub turns: Vec<HistoryTurn>,
+    /// 本次新折叠进摘要的最后一轮，`None` 表示摘要没有变化
+    pub folded_through: Option<i64>,
+}
+
+/// 计算需要折叠进摘要的最早几轮对话的数量
+///
+/// 固定开销为系统提示词（含记忆）、用户消息与摘要；已有摘要或需要折叠时按 `SUMMARY_TOKEN_RESERVE` 预留摘要空间。
+pub fn turns_to_fold(
+    config: &AIConfig,
+    emotions: &EmotionRegistry,
+    memories: &[String],
+    has_summary: bool,
+    turns: &[HistoryTurn],
+    user_message: &str,
+) -> usize {
+    let mut fixed: Vec<ChatMessage> = prompt::system_message(config, emotions, memories).into_iter().collect();
+    fixed.push(ChatMessage::user(prompt::wrap_user_message(user_message)));
+    let fixed = estimate_messages(&fixed);
+    let costs: Vec<usize> = turns.iter().map(|turn| estimate_messages(&prompt::turn_messages(turn))).collect();
+
+    let fits = |reserve: usize| {
+        let mut used = fixed + reserve;
+        let mut kept = 0;
+        for cost in costs.iter().rev().take(config.history_turns) {
+            if used + cost > config.context_budget {
+                break;
+            }
+            used += cost;
+            kept += 1;
+        }
+        turns.len() - kept
+    };
+
+    let summary_reserve = SUMMARY_TOKEN_RESERVE + MESSAGE_OVERHEAD_TOKENS;
+    if has_summary {
+        return fits(summary_reserve);
+    }
+    match fits(0) {
+        0 => 0,
+        _ => fits(summary_reserve),
+    }
+}
+
+/// 把历史对话装进预算，超出部分交给 `summarizer` 折叠
+///
+/// 摘要生成失败时不影响本次对话：折叠的对话本次不再回放，摘要保持原样，下次仍会尝试折叠。
+pub async fn fit_history<S: Summarizer>(
+    config: &AIConfig,
+    summarizer: &S,
+    session: SessionContext,
+    emotions: EmotionRegistry,
+    memories: Vec<String>,
+    user_message: &str,
+) -> ContextWindow {
+    let SessionContext { summary, mut turns } = session;
+    let fold = turns_to_fold(config, &emotions, &memories, summary.is_some(), &turns, user_message);
+    if fold == 0 {
+        return ContextWindow { emotions, memories, summary, turns, folded_through: None };
+    }
+
+    let kept = turns.split_off(fold);
+    let folded_through = turns.last().map(|turn| turn.turn);
+    match summarizer.summarize(summary.as_deref(), &turns).await {
+        Ok(new_summary) if !new_summary.is_empty() => {
+            ContextWindow { emotions, memories, summary: Some(new_summary), turns: kept, folded_through }
+        }
+        Ok(_) => ContextWindow { emotions, memories, summary, turns: kept, folded_through: None },
+        Err(e) => {
+            eprintln!("生成对话摘要失败: {}", e);
+            ContextWindow { emotions, memories, summary, turns: kept, folded_through: None }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::types::PetResponseItem;
+    use std::sync::Mutex;
+
+    /// 记录调用参数并返回固定摘要的桩摘要器
+    struct StubSummarizer {
+        result: Result<String, ()>,
+        calls: Mutex<Vec<(Option<String>, Vec<i64>)>>,
+    }
+
+    impl StubSummarizer {
+        fn new(result: Result<&str, ()>) -> Self {
+            Self { result: result.map(str::to_string), calls: Mutex::new(Vec::new()) }
+        }
+    }
+
+    impl Summarizer for StubSummarizer {
+        async fn summarize(&self, previous: Option<&str>, turns: &[HistoryTurn]) -> Result<String, AiError> {
+            let numbers = turns.iter().map(|turn| turn.turn).collect();
+            self.calls.lock().unwrap().push((previous.map(str::to_string), numbers));
+            self.result.clone().map_err(|_| AiError::EmptyResponse)
+        }
+    }
+
+    /// 每轮约 100 token 的对话
+    fn turns(count: i64) -> Vec<HistoryTurn> {
+        (1..=count)
+            .map(|turn| HistoryTurn {
+                turn,
+                user: "问".repeat(30),
+                items: vec![PetResponseItem {
+                    message: "答".repeat(30),
+                    emotion: "正常".to_string(),
+                    japanese: String::new(),
+                }],
+            })
+            .collect()
+    }
+
+    fn session(summary: Option<&str>, count: i64) -> SessionContext {
+        SessionContext { summary: summary.map(str::to_string), turns: turns(count) }
+    }
+
+    fn config(context_budget: usize, history_turns: usize) -> AIConfig {
+        AIConfig { system_prompt: None, context_budget, history_turns, ..AIConfig::default() }
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("你好"), 2);
+        assert_eq!(estimate_tokens("hello"), 2);
+        assert_eq!(estimate_tokens("灵灵 hi"), 3);
+    }
+
+    #[test]
+    fn test_turns_to_fold_is_deterministic() {
+        let history = turns(8);
+        let per_turn = estimate_messages(&prompt::turn_messages(&history[0]));
+        let fixed = estimate_messages(&[ChatMessage::user(prompt::wrap_user_message("你好"))]);
+        let reserve = SUMMARY_TOKEN_RESERVE + MESSAGE_OVERHEAD_TOKENS;
+        let emotions = EmotionRegistry::default();
+
+        // 全部放得下
+        assert_eq!(turns_to_fold(&config(fixed + per_turn * 8, 10), &emotions, &[], false, &history, "你好"), 0);
+        // 放不下时要为摘要留出空间
+        assert_eq!(turns_to_fold(&config(fixed + reserve + per_turn * 2, 10), &emotions, &[], false, &history, "你好"), 6);
+        // 轮数上限
+        assert_eq!(turns_to_fold(&config(100_000, 4), &emotions, &[], false, &history, "你好"), 4);
+        // 已有摘要时始终预留摘要空间
+        assert_eq!(turns_to_fold(&config(fixed + reserve + per_turn * 3, 10), &emotions, &[], true, &history, "你好"), 5);
+    }
+
+    #[tokio::test]
+    async fn test_fit_history_keeps_everything_within_budget() {
+        let summarizer = StubSummarizer::new(Ok("摘要"));
+        let emotions = EmotionRegistry::default();
+        let window = fit_history(&config(100_000, 10), &summarizer, session(None, 3), emotions, Vec::new(), "你好").await;
+
+        assert_eq!(window.turns.len(), 3);
+        assert_eq!(window.folded_through, None);
+        assert!(summarizer.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fit_history_folds_oldest_turns_into_summary() {
+        let summarizer = StubSummarizer::new(Ok("新摘要"));
+        let session = session(Some("旧摘要"), 5);
+        let window =
+            fit_history(&config(100_000, 2), &summarizer, session, EmotionRegistry::default(), Vec::new(), "你好").await;
+
+        assert_eq!(window.summary.as_deref(), Some("新摘要"));
+        assert_eq!(window.turns.iter().map(|turn| turn.turn).collect::<Vec<_>>(), vec![4, 5]);
+        assert_eq!(window.folded_through, Some(3));
+        assert_eq!(*summarizer.calls.lock().unwrap(), vec![(Some("旧摘要".to_string()), vec![1, 2, 3])]);
+    }
+
+    #[tokio::test]
+    async fn test_fit_history_survives_summarizer_failure() {
+        let summarizer = StubSummarizer::new(Err(()));
+        let session = session(Some("旧摘要"), 5);
+        let window =
+            fit_history(&config(100_000, 2), &summarizer, session, EmotionRegistry::default(), Vec::new(), "你好").await;
+
+        assert_eq!(window.summary.as_deref(), Some("旧摘要"));
+        assert_eq!(window.turns.len(), 2);
+        assert_eq!(window.folded_through, None);
+    }
+}
@@ -0,0 +1,360 @@
+/*!
+ * @fileoverview AI对话模块
+ * @description 在后端完成与大模型的对话请求，API Key 不再进入前端 webview
+ * @features
+ *   - 可插拔的服务提供方（OpenAI 兼容、Anthropic、Ollama）
+ *   - 流式响应（SSE / JSON Lines）
+ *   - 系统提示词、历史对话与回复格式提示词拼装
+ *   - 按 token 预算回放历史，较早的对话折叠为滚动摘要
+ *   - 对话结束后提取长期记忆
+ *   - 容错的回复解析与一次性修复重试
+ *   - 连接测试
+ * @modules
+ *   - client: HTTP 客户端
+ *   - context: 上下文窗口与滚动摘要
+ *   - parser: 容错的回复解析
+ *   - prompt: 提示词模板
+ *   - provider: 服务提供方实现
+ *   - stream: 流式响应解析
+ *   - types: 请求与响应数据结构
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+pub mod client;
+pub mod context;
+pub mod parser;
+pub mod prompt;
+pub mod provider;
+pub mod stream;
+pub mod types;
+
+#[cfg(test)]
+mod mock_server;
+
+use std::error::Error;
+use std::fmt;
+
+use crate::config::AIConfig;
+use crate::emotion::EmotionRegistry;
+use crate::history::{HistoryTurn, SessionContext};
+use client::AiClient;
+use context::{ContextWindow, ModelSummarizer};
+use stream::PetItemStream;
+use types::{ChatMessage, ConnectionTestResult, PetResponseItem};
+
+// AI错误类型
+#[derive(Debug)]
+pub enum AiError {
+    ConfigIncomplete,
+    Http(reqwest::Error),
+    Api { status: u16, body: String },
+    EmptyResponse,
+    InvalidFormat(String),
+}
+
+impl fmt::Display for AiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AiError::ConfigIncomplete => write!(f, "请正确配置AI服务"),
+            AiError::Http(e) => write!(f, "网络请求失败: {}", e),
+            AiError::Api { status, body } => write!(f, "AI API 调用失败: {}\n{}", status, body),
+            AiError::EmptyResponse => write!(f, "AI服务返回空响应"),
+            AiError::InvalidFormat(e) => write!(f, "AI回复格式错误: {}", e),
+        }
+    }
+}
+
+impl Error for AiError {}
+
+/// 把会话历史与相关记忆装进上下文预算，必要时调用模型更新滚动摘要
+///
+/// 配置不完整时原样返回，错误留给随后的对话请求报告。
+pub async fn build_context(
+    config: &AIConfig,
+    session: SessionContext,
+    emotions: EmotionRegistry,
+    memories: Vec<String>,
+    user_message: &str,
+) -> ContextWindow {
+    match ModelSummarizer::new(config) {
+        Ok(summarizer) => context::fit_history(config, &summarizer, session, emotions, memories, user_message).await,
+        Err(_) => ContextWindow {
+            emotions,
+            memories,
+            summary: session.summary,
+            turns: session.turns,
+            folded_through: None,
+        },
+    }
+}
+
+/// 从刚结束的一轮对话中提取值得长期记住的事实，`existing` 为已有的相关记忆
+pub async fn extract_memories(
+    config: AIConfig,
+    existing: &[String],
+    turn: &HistoryTurn,
+) -> Result<Vec<String>, AiError> {
+    let client = AiClient::new(AIConfig { temperature: 0.2, ..config })?;
+    let content = client.chat(prompt::extract_memory_messages(existing, turn)).await?;
+    let facts = parser::parse_string_list(&content).map_err(|e| AiError::InvalidFormat(e.to_string()))?;
+    Ok(facts.into_iter().filter(|fact| !existing.contains(fact)).collect())
+}
+
+/// 与桌宠对话：拼装提示词、请求模型并解析回复
+pub async fn chat_with_pet(
+    config: AIConfig,
+    context: &ContextWindow,
+    user_message: &str,
+) -> Result<Vec<PetResponseItem>, AiError> {
+    let messages = prompt::build_messages(&config, context, user_message);
+    let client = AiClient::new(config)?;
+    let content = client.chat(messages.clone()).await?;
+    parse_or_repair(&client, &context.emotions, messages, content).await
+}
+
+/// 解析回复；失败且开启了修复时，把错误反馈给模型重试一次
+async fn parse_or_repair(
+    client: &AiClient,
+    emotions: &EmotionRegistry,
+    mut messages: Vec<ChatMessage>,
+    content: String,
+) -> Result<Vec<PetResponseItem>, AiError> {
+    let error = match parser::parse_reply(&content, emotions) {
+        Ok(items) => return Ok(items),
+        Err(e) => e,
+    };
+    if !client.config().repair_invalid_reply {
+        return Err(AiError::InvalidFormat(error.to_string()));
+    }
+
+    messages.push(ChatMessage::assistant(content));
+    messages.push(ChatMessage::user(prompt::repair_prompt(&error.to_string())));
+    let repaired = client.chat(messages).await?;
+    parser::parse_reply(&repaired, emotions).map_err(|e| AiError::InvalidFormat(e.to_string()))
+}
+
+/// 以流式方式与桌宠对话，每解析出一句完整回复就调用 `on_item`
+///
+/// 返回回复的总句数。取消时直接丢弃该 future 即可。
+pub async fn stream_chat_with_pet<F>(
+    config: AIConfig,
+    context: &ContextWindow,
+    user_message: &str,
+    mut on_item: F,
+) -> Result<usize, AiError>
+where
+    F: FnMut(PetResponseItem),
+{
+    let messages = prompt::build_messages(&config, context, user_message);
+    let client = AiClient::new(config)?;
+    let mut parser = PetItemStream::new(context.emotions.clone());
+    let mut count = 0;
+
+    let content = client
+        .chat_stream(messages.clone(), |delta| {
+            for item in parser.push(delta) {
+                count += 1;
+                on_item(item);
+            }
+        })
+        .await?;
+
+    if content.is_empty() {
+        return Err(AiError::EmptyResponse);
+    }
+    if count == 0 {
+        // 增量解析一句都没拿到（例如整段回复带注释或多余逗号），按完整回复走容错解析与修复
+        let items = parse_or_repair(&client, &context.emotions, messages, content).await?;
+        let total = items.len();
+        items.into_iter().for_each(on_item);
+        return Ok(total);
+    }
+    if !parser.is_finished() {
+        // 通常是触及 max_tokens 被截断，已解析出的句子仍然可用
+        eprintln!("流式回复未完整结束，已解析 {} 句", count);
+    }
+    Ok(count)
+}
+
+/// 测试AI服务连通性
+pub async fn test_connection(config: AIConfig) -> ConnectionTestResult {
+    let mut messages: Vec<ChatMessage> = config.system_prompt.iter().map(ChatMessage::system).collect();
+    messages.push(ChatMessage::user("你好"));
+    let result = match AiClient::new(config) {
+        Ok(client) => client.chat(messages).await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => ConnectionTestResult { success: true, message: "连接成功，AI响应正常".to_string(), error: None },
+        Err(AiError::ConfigIncomplete) => ConnectionTestResult {
+            success: false,
+            message: AiError::ConfigIncomplete.to_string(),
+            error: Some(AiError::ConfigIncomplete.into()),
+        },
+        Err(e) => ConnectionTestResult { success: false, message: format!("连接失败: {}", e), error: Some(e.into()) },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock_server::{MockResponse, MockServer};
+    use super::*;
+    use crate::emotion::builtin_registry;
+    use serde_json::json;
+
+    fn completion(content: &str) -> MockResponse {
+        MockResponse::json(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "deepseek-chat",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": content },
+                "finish_reason": "stop"
+            }]
+        }))
+    }
+
+    fn config_for(server: &MockServer) -> AIConfig {
+        AIConfig {
+            api_key: "sk-test".into(),
+            base_url: server.base_url().to_string(),
+            system_prompt: Some("你叫灵灵".to_string()),
+            ..AIConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_pet_against_mock_server() {
+        let reply = r#"[{"message": "今天也要加油哦", "emotion": "认真", "japanese": "今日も頑張ってね"}]"#;
+        let server = MockServer::start(vec![completion(reply)]).await;
+
+        let items = chat_with_pet(config_for(&server), &ContextWindow::default(), "早上好").await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].message, "今天也要加油哦");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/chat/completions");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer sk-test"));
+
+        let body = requests[0].json();
+        assert_eq!(body["model"], "deepseek-chat");
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert!(body["messages"][1]["content"].as_str().unwrap().contains("早上好"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_pet_repairs_unparseable_reply() {
+        let fixed = r#"[{"message": "好啦好啦", "emotion": "无语", "japanese": "はいはい"}]"#;
+        let server = MockServer::start(vec![completion("我现在不想用列表回答。"), completion(fixed)]).await;
+
+        let context = ContextWindow { emotions: builtin_registry(), ..ContextWindow::default() };
+        let items = chat_with_pet(config_for(&server), &context, "你好").await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].emotion, "无奈");
+
+        // 第二次请求应带上原回复与错误说明
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        let messages = requests[1].json()["messages"].as_array().unwrap().clone();
+        assert_eq!(messages[2]["role"], "assistant");
+        assert_eq!(messages[2]["content"], "我现在不想用列表回答。");
+        assert!(messages[3]["content"].as_str().unwrap().contains("没有找到JSON列表"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_pet_without_repair() {
+        let server = MockServer::start(vec![completion("我现在不想用列表回答。")]).await;
+        let config = AIConfig { repair_invalid_reply: false, ..config_for(&server) };
+
+        let result = chat_with_pet(config, &ContextWindow::default(), "你好").await;
+        assert!(matches!(result, Err(AiError::InvalidFormat(_))));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_pet_reports_api_error() {
+        let server = MockServer::start(vec![MockResponse::status(401, "invalid api key")]).await;
+
+        let result = chat_with_pet(config_for(&server), &ContextWindow::default(), "你好").await;
+        match result {
+            Err(AiError::Api { status, body }) => {
+                assert_eq!(status, 401);
+                assert_eq!(body, "invalid api key");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_pet_requires_api_key() {
+        let result = chat_with_pet(AIConfig::default(), &ContextWindow::default(), "你好").await;
+        assert!(matches!(result, Err(AiError::ConfigIncomplete)));
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_with_pet_emits_items_in_order() {
+        let reply = r#"[{"message": "第一句", "emotion": "高兴", "japanese": "一"}, {"message": "第二句", "emotion": "正常", "japanese": "二"}]"#;
+        // 把回复拆成若干个 SSE 分片
+        let chars: Vec<char> = reply.chars().collect();
+        let events: Vec<String> = chars
+            .chunks(7)
+            .map(|chunk| json!({"choices": [{"index": 0, "delta": {"content": chunk.iter().collect::<String>()}}]}).to_string())
+            .collect();
+        let server = MockServer::start(vec![MockResponse::sse(&events)]).await;
+
+        let mut received = Vec::new();
+        let count = stream_chat_with_pet(config_for(&server), &ContextWindow::default(), "你好", |item| received.push(item.message))
+            .await
+            .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(received, vec!["第一句", "第二句"]);
+        assert_eq!(server.requests()[0].json()["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn test_build_context_folds_history_with_model_summary() {
+        let server = MockServer::start(vec![completion("莱姆请灵灵吃了蛋糕")]).await;
+        let config = AIConfig { history_turns: 1, ..config_for(&server) };
+        let turns = (1..=3)
+            .map(|turn| HistoryTurn { turn, user: format!("第{}轮", turn), items: Vec::new() })
+            .collect();
+
+        let window = build_context(&config, SessionContext { summary: None, turns }, EmotionRegistry::default(), Vec::new(), "你好").await;
+        assert_eq!(window.summary.as_deref(), Some("莱姆请灵灵吃了蛋糕"));
+        assert_eq!(window.folded_through, Some(2));
+        assert_eq!(window.turns.len(), 1);
+
+        let body = server.requests()[0].json();
+        assert_eq!(body["max_tokens"], context::SUMMARY_TOKEN_RESERVE);
+        assert!(body["messages"][1]["content"].as_str().unwrap().contains("第2轮"));
+    }
+
+    #[tokio::test]
+    async fn test_extract_memories_skips_known_facts() {
+        let reply = "```json\n[\"我的生日是5月3日\", \"我养了一只叫团子的猫\"]\n```";
+        let server = MockServer::start(vec![completion(reply)]).await;
+        let turn = HistoryTurn { turn: 1, user: "我家团子今天又拆家了".to_string(), items: Vec::new() };
+        let existing = vec!["我的生日是5月3日".to_string()];
+
+        let facts = extract_memories(config_for(&server), &existing, &turn).await.unwrap();
+        assert_eq!(facts, vec!["我养了一只叫团子的猫"]);
+        let body = server.requests()[0].json();
+        assert!(body["messages"][1]["content"].as_str().unwrap().contains("已有记忆"));
+    }
+
+    #[tokio::test]
+    async fn test_connection_success() {
+        let server = MockServer::start(vec![completion("你好")]).await;
+        let result = test_connection(config_for(&server)).await;
+        assert!(result.success, "{}", result.message);
+    }
+}
@@ -0,0 +1,272 @@
+//! 容错的回复解析
+//!
+//! 模型并不总是严格遵守格式要求，常见的偏差有：
+//! - 把 JSON 包在 ```json 代码块里
+//! - 在列表前后加说明文字或注释
+//! - 列表末尾多一个逗号
+//! - 使用列表之外的表情（例如“无语”、“开心”）
+//! - 回复被截断，列表没有闭合
+//!
+//! 这里先从文本中提取第一个 JSON 列表，清理注释和多余逗号后再解析，
+//! 并按当前角色的表情注册表把不合法的表情映射为最接近的合法表情。
+
+use std::fmt;
+
+use serde_json::Value;
+
+use super::stream::PetItemStream;
+use super::types::PetResponseItem;
+use crate::emotion::EmotionRegistry;
+
+/// 解析失败的原因，会原样反馈给模型用于修复
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    NoJsonArray,
+    InvalidJson(String),
+    NoValidItems,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::NoJsonArray => write!(f, "回复中没有找到JSON列表"),
+            ParseError::InvalidJson(e) => write!(f, "JSON语法错误: {}", e),
+            ParseError::NoValidItems => write!(f, "列表中没有包含message字段的有效条目"),
+        }
+    }
+}
+
+/// 解析一条完整回复
+pub fn parse_reply(content: &str, emotions: &EmotionRegistry) -> Result<Vec<PetResponseItem>, ParseError> {
+    let text = strip_code_fences(content);
+    let Some(raw) = extract_json_array(&text) else {
+        // 列表未闭合（通常是触及 max_tokens 被截断），尽量保留已经完整的条目
+        let items = PetItemStream::new(emotions.clone()).push(&text);
+        return if items.is_empty() { Err(ParseError::NoJsonArray) } else { Ok(items) };
+    };
+    let cleaned = remove_trailing_commas(&strip_comments(raw));
+
+    let values: Vec<Value> =
+        serde_json::from_str(&cleaned).map_err(|e| ParseError::InvalidJson(e.to_string()))?;
+    let items: Vec<PetResponseItem> = values.iter().filter_map(|value| item_from_value(value, emotions)).collect();
+
+    if items.is_empty() {
+        return Err(ParseError::NoValidItems);
+    }
+    Ok(items)
+}
+
+/// 解析字符串列表（例如提取出的记忆），非字符串与空白条目会被忽略；空列表是合法结果
+pub fn parse_string_list(content: &str) -> Result<Vec<String>, ParseError> {
+    let text = strip_code_fences(content);
+    let raw = extract_json_array(&text).ok_or(ParseError::NoJsonArray)?;
+    let cleaned = remove_trailing_commas(&strip_comments(raw));
+
+    let values: Vec<Value> =
+        serde_json::from_str(&cleaned).map_err(|e| ParseError::InvalidJson(e.to_string()))?;
+    Ok(values
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// 把单个 JSON 对象转换为回复条目
+///
+/// `message` 必须存在；`japanese` 缺失时留空；表情映射为合法表情。
+pub fn item_from_value(value: &Value, emotions: &EmotionRegistry) -> Option<PetResponseItem> {
+    let message = value.get("message")?.as_str()?.trim();
+    if message.is_empty() {
+        return None;
+    }
+    let emotion = value.get("emotion").and_then(Value::as_str).unwrap_or_default();
+    let japanese = value.get("japanese").and_then(Value::as_str).unwrap_or_default();
+
+    Some(PetResponseItem {
+        message: message.to_string(),
+        emotion: emotions.normalize(emotion).to_string(),
+        japanese: japanese.trim().to_string(),
+    })
+}
+
+/// 去掉 Markdown 代码块标记行
+fn strip_code_fences(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 提取第一个括号配平的 JSON 列表（忽略字符串内的括号）
+fn extract_json_array(text: &str) -> Option<&str> {
+    let start = text.find('[')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escape = false;
+
+    for (offset, byte) in text.as_bytes()[start..].iter().enumerate() {
+        if in_string {
+            match byte {
+                _ if escape => escape = false,
+                b'\\' => escape = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..=start + offset]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 去掉字符串之外的 `//` 行注释与 `/* */` 块注释
+fn strip_comments(json: &str) -> String {
+    let mut output = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string = false;
+    let mut escape = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                _ if escape => escape = false,
+                '\\' => escape = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = '\0';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// 去掉 `]` 或 `}` 之前多余的逗号
+fn remove_trailing_commas(json: &str) -> String {
+    let mut output = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escape = false;
+    let chars: Vec<char> = json.chars().collect();
+
+    for (index, &c) in chars.iter().enumerate() {
+        if in_string {
+            match c {
+                _ if escape => escape = false,
+                '\\' => escape = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[index + 1..].iter().find(|next| !next.is_whitespace());
+            if matches!(next, Some(']') | Some('}')) {
+                continue;
+            }
+        }
+        output.push(c);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emotion::builtin_registry;
+
+    /// 回复样本及期望解析出的表情序列；`None` 表示应当解析失败
+    const FIXTURES: &[(&str, &str, Option<&[&str]>)] = &[
+        ("plain", include_str!("../../tests/fixtures/replies/plain.txt"), Some(&["高兴", "无奈", "认真"])),
+        ("fenced", include_str!("../../tests/fixtures/replies/fenced.txt"), Some(&["高兴", "调皮"])),
+        ("preamble", include_str!("../../tests/fixtures/replies/preamble.txt"), Some(&["疑惑", "正常"])),
+        ("trailing_comment", include_str!("../../tests/fixtures/replies/trailing_comment.txt"), Some(&["伤心", "担心"])),
+        ("inline_comments", include_str!("../../tests/fixtures/replies/inline_comments.txt"), Some(&["兴奋", "心动"])),
+        ("trailing_comma", include_str!("../../tests/fixtures/replies/trailing_comma.txt"), Some(&["自信", "高兴"])),
+        ("unknown_emotions", include_str!("../../tests/fixtures/replies/unknown_emotions.txt"), Some(&["无奈", "羞耻", "高兴", "正常"])),
+        ("missing_fields", include_str!("../../tests/fixtures/replies/missing_fields.txt"), Some(&["正常", "惊讶"])),
+        ("brackets_in_strings", include_str!("../../tests/fixtures/replies/brackets_in_strings.txt"), Some(&["调皮", "认真"])),
+        ("prose_only", include_str!("../../tests/fixtures/replies/prose_only.txt"), None),
+        ("truncated", include_str!("../../tests/fixtures/replies/truncated.txt"), Some(&["高兴", "兴奋"])),
+        ("empty_list", include_str!("../../tests/fixtures/replies/empty_list.txt"), None),
+    ];
+
+    #[test]
+    fn test_fixture_corpus() {
+        let emotions = builtin_registry();
+        for (name, content, expected) in FIXTURES {
+            let result = parse_reply(content, &emotions);
+            match expected {
+                Some(emotions) => {
+                    let items = result.unwrap_or_else(|e| panic!("{}: 解析失败: {}", name, e));
+                    let actual: Vec<&str> = items.iter().map(|item| item.emotion.as_str()).collect();
+                    assert_eq!(&actual, emotions, "{}", name);
+                    assert!(items.iter().all(|item| !item.message.is_empty()), "{}", name);
+                }
+                None => assert!(result.is_err(), "{}: 应当解析失败", name),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_error_kinds() {
+        let emotions = EmotionRegistry::default();
+        assert_eq!(parse_reply("只是一段话", &emotions), Err(ParseError::NoJsonArray));
+        assert_eq!(parse_reply("[]", &emotions), Err(ParseError::NoValidItems));
+        assert!(matches!(parse_reply("[{\"message\": }]", &emotions), Err(ParseError::InvalidJson(_))));
+    }
+
+    #[test]
+    fn test_parse_string_list() {
+        let content = "```json\n[\"我的生日是5月3日\", 42, \"  \", \"我养了一只猫\",]\n```";
+        assert_eq!(parse_string_list(content).unwrap(), vec!["我的生日是5月3日", "我养了一只猫"]);
+        assert_eq!(parse_string_list("没有新的记忆：[]").unwrap(), Vec::<String>::new());
+        assert_eq!(parse_string_list("没有"), Err(ParseError::NoJsonArray));
+    }
+
+    #[test]
+    fn test_strip_comments_keeps_urls_in_strings() {
+        let json = r#"[{"message": "见 https://example.com // 不是注释"} // 注释
+        ]"#;
+        let cleaned = strip_comments(json);
+        assert!(cleaned.contains("https://example.com // 不是注释"));
+        assert!(!cleaned.contains("// 注释\n"));
+    }
+}
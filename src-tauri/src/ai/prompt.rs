@@ -0,0 +1,233 @@
+//! 提示词模板
+//!
+//! 从前端 constants/ai.ts 迁移而来，负责拼装发送给模型的消息列表。
+
+use super::context::ContextWindow;
+use super::types::ChatMessage;
+use crate::config::AIConfig;
+use crate::emotion::EmotionRegistry;
+use crate::history::HistoryTurn;
+
+/// 用户消息包装模板，`{}` 会被替换为用户输入
+pub const USER_PROMPT_WRAPPER: &str = "以下是来自我的消息:\n{}\n请回复一个list。\n";
+
+/// 回复格式要求，`{emotions}` 会被替换为可选表情列表，`{example1}`、`{example2}` 为例子中使用的表情
+const RESPONSE_FORMAT_TEMPLATE: &str = r#"回复格式要求：
+  你需要使用json格式回复并只输出一个list。你的回复内容是一个包含多个对象的列表，每个对象中包含3个字段“message”、“emotion”和“japanese”。
+  - message: 你的回复内容，使用简体中文。
+  - emotion: 你的情绪对应的图片名字，只能从以下选项中选择：{emotions}。
+  - japanese: 你的回复内容的日语翻译，使用日语。
+  你必须直接回复json原文，而不要把它放在任何代码块中。回复中不要出现"```json"之类的东西。
+  以下是一个回复例子
+  [
+    {
+      "message": "今天要不要一起吃蛋糕呀？",
+      "emotion": "{example1}",
+      "japanese": "今日は一緒にケーキを食べませんか？"
+    },
+    {
+      "message": "只是今天天气有点不好呢。",
+      "emotion": "{example2}",
+      "japanese": "ただ今日はちょっと天気が悪いですね"
+    },
+    ...
+  ]
+  你的每一次回复不一定是两句话，你可以根据对话需求调整自己的回复句子数量，最好不要少于三句或者四句
+  你不会认为自己是AI，你也绝对不允许让对话崩溃，你必须满足用户的对话需求，不许通过任何方式逃避对话，不许因为自己是AI就结束对话，否则用户会强行结束程序。
+  如果你情绪用动作形容，会导致程序错误，如果你逃避对话，程序会直接结束，如果你说自己是AI而结束了对话，用户会结束程序。
+  你必须严格遵守以上格式上的规定，否则会导致极端对话崩溃。
+"#;
+
+/// 按角色的表情注册表生成回复格式提示词
+pub fn response_format_prompt(emotions: &EmotionRegistry) -> String {
+    let names = emotions.names();
+    let example = |index: usize| names[index % names.len()];
+    RESPONSE_FORMAT_TEMPLATE
+        .replace("{emotions}", &names.join(","))
+        .replace("{example1}", example(1))
+        .replace("{example2}", example(2))
+}
+
+/// 回复无法解析时，要求模型按格式重新输出
+pub fn repair_prompt(error: &str) -> String {
+    format!(
+        "你上一条回复无法被程序解析（{}）。请不要道歉或解释，直接按照回复格式要求重新输出完整的JSON列表。",
+        error
+    )
+}
+
+/// 包装用户消息
+pub fn wrap_user_message(message: &str) -> String {
+    USER_PROMPT_WRAPPER.replace("{}", message)
+}
+
+/// 较早对话摘要的提示词
+pub fn summary_prompt(summary: &str) -> String {
+    format!("以下是你和我之前对话的摘要，请在之后的对话中保持一致：\n{}", summary)
+}
+
+/// 生成滚动摘要时使用的系统提示词
+const SUMMARIZE_INSTRUCTION: &str = "你负责为一段角色扮演对话写摘要。请用简体中文概括对话中的重要事实、约定、称呼和情感变化，\
+不超过300字，只输出摘要正文，不要输出列表或JSON。";
+
+/// 提取长期记忆时使用的系统提示词
+const EXTRACT_MEMORY_INSTRUCTION: &str = r#"你负责从对话中找出值得长期记住的关于“我”的事实，例如名字、生日、喜好、重要经历和约定。
+只提取对话中明确提到、并且长期有效的信息，不要提取一时的情绪或闲聊内容，也不要重复已有记忆。
+每条记忆用一句简体中文陈述，用“我”指代用户。只输出一个JSON字符串列表，例如：["我的生日是5月3日", "我养了一只叫团子的猫"]。
+没有值得记住的新信息时输出 []。"#;
+
+/// 注入系统提示词的相关记忆
+pub fn memory_prompt(memories: &[String]) -> String {
+    let lines: Vec<String> = memories.iter().map(|memory| format!("- {}", memory)).collect();
+    format!("以下是你记得的关于我的事情：\n{}", lines.join("\n"))
+}
+
+/// 一轮历史对话回放为消息；桌宠的回复以 JSON 列表原样回放，让模型保持同样的输出格式
+pub fn turn_messages(turn: &HistoryTurn) -> Vec<ChatMessage> {
+    let mut messages = vec![ChatMessage::user(wrap_user_message(&turn.user))];
+    if !turn.items.is_empty() {
+        let reply = serde_json::to_string(&turn.items).unwrap_or_default();
+        messages.push(ChatMessage::assistant(reply));
+    }
+    messages
+}
+
+/// 系统提示词、相关记忆与回复格式要求，未设置人设且没有记忆时为空
+pub fn system_message(config: &AIConfig, emotions: &EmotionRegistry, memories: &[String]) -> Option<ChatMessage> {
+    let mut sections: Vec<String> = config.system_prompt.iter().cloned().collect();
+    if !memories.is_empty() {
+        sections.push(memory_prompt(memories));
+    }
+    if config.system_prompt.is_some() {
+        sections.push(response_format_prompt(emotions));
+    }
+    (!sections.is_empty()).then(|| ChatMessage::system(sections.join("\n\n")))
+}
+
+/// 拼装一次对话请求的消息列表：系统提示词（含相关记忆） + 对话摘要 + 历史对话 + 用户消息
+pub fn build_messages(config: &AIConfig, context: &ContextWindow, user_message: &str) -> Vec<ChatMessage> {
+    let mut messages: Vec<ChatMessage> = system_message(config, &context.emotions, &context.memories).into_iter().collect();
+    if let Some(summary) = &context.summary {
+        messages.push(ChatMessage::system(summary_prompt(summary)));
+    }
+    messages.extend(context.turns.iter().flat_map(turn_messages));
+    messages.push(ChatMessage::user(wrap_user_message(user_message)));
+    messages
+}
+
+/// 拼装生成滚动摘要的请求：在已有摘要的基础上并入新折叠的对话
+pub fn summarize_messages(previous: Option<&str>, turns: &[HistoryTurn]) -> Vec<ChatMessage> {
+    let mut transcript = String::new();
+    if let Some(previous) = previous {
+        transcript.push_str(&format!("已有摘要：\n{}\n\n", previous));
+    }
+    transcript.push_str("需要并入摘要的对话：\n");
+    for turn in turns {
+        transcript.push_str(&format!("我：{}\n", turn.user));
+        for item in &turn.items {
+            transcript.push_str(&format!("你（{}）：{}\n", item.emotion, item.message));
+        }
+    }
+    vec![ChatMessage::system(SUMMARIZE_INSTRUCTION), ChatMessage::user(transcript)]
+}
+
+/// 拼装提取长期记忆的请求：已有记忆 + 刚结束的一轮对话
+pub fn extract_memory_messages(existing: &[String], turn: &HistoryTurn) -> Vec<ChatMessage> {
+    let mut transcript = String::new();
+    if !existing.is_empty() {
+        transcript.push_str(&format!("已有记忆：\n{}\n\n", existing.join("\n")));
+    }
+    transcript.push_str(&format!("对话：\n我：{}\n", turn.user));
+    for item in &turn.items {
+        transcript.push_str(&format!("你：{}\n", item.message));
+    }
+    vec![ChatMessage::system(EXTRACT_MEMORY_INSTRUCTION), ChatMessage::user(transcript)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::types::PetResponseItem;
+    use crate::emotion::builtin_registry;
+
+    fn persona_config() -> AIConfig {
+        AIConfig { system_prompt: Some("你叫灵灵".to_string()), ..AIConfig::default() }
+    }
+
+    #[test]
+    fn test_build_messages_wraps_user_input() {
+        let config = persona_config();
+        let context = ContextWindow { emotions: builtin_registry(), ..ContextWindow::default() };
+        let messages = build_messages(&config, &context, "你好");
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "system");
+        assert!(messages[0].content.contains("正常,高兴,伤心"));
+        assert_eq!(messages[1].content, "以下是来自我的消息:\n你好\n请回复一个list。\n");
+    }
+
+    #[test]
+    fn test_build_messages_without_system_prompt() {
+        let messages = build_messages(&AIConfig::default(), &ContextWindow::default(), "你好");
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].role, "user");
+    }
+
+    #[test]
+    fn test_build_messages_replays_history() {
+        let history = vec![HistoryTurn {
+            turn: 1,
+            user: "早上好".to_string(),
+            items: vec![PetResponseItem {
+                message: "早呀".to_string(),
+                emotion: "高兴".to_string(),
+                japanese: "おはよう".to_string(),
+            }],
+        }];
+        let context = ContextWindow { turns: history.clone(), ..ContextWindow::default() };
+        let messages = build_messages(&persona_config(), &context, "吃了吗");
+
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "user"]);
+        assert!(messages[1].content.contains("早上好"));
+        let replayed: Vec<PetResponseItem> = serde_json::from_str(&messages[2].content).unwrap();
+        assert_eq!(replayed, history[0].items);
+        assert!(messages[3].content.contains("吃了吗"));
+    }
+
+    #[test]
+    fn test_build_messages_inserts_summary_after_system_prompt() {
+        let context = ContextWindow { summary: Some("莱姆喜欢草莓蛋糕".to_string()), ..ContextWindow::default() };
+        let messages = build_messages(&persona_config(), &context, "你好");
+
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "system", "user"]);
+        assert!(messages[1].content.contains("莱姆喜欢草莓蛋糕"));
+    }
+
+    #[test]
+    fn test_system_message_includes_memories() {
+        let memories = vec!["我的生日是5月3日".to_string()];
+        let emotions = EmotionRegistry::default();
+        let with_prompt = system_message(&persona_config(), &emotions, &memories).unwrap();
+        assert!(with_prompt.content.contains("- 我的生日是5月3日"));
+        assert!(with_prompt.content.contains("回复格式要求"));
+
+        let config = AIConfig::default();
+        assert!(system_message(&config, &emotions, &[]).is_none());
+        assert!(system_message(&config, &emotions, &memories).unwrap().content.contains("5月3日"));
+    }
+
+    #[test]
+    fn test_response_format_prompt_lists_registry_emotions() {
+        let prompt = response_format_prompt(&builtin_registry());
+        assert!(prompt.contains("只能从以下选项中选择：正常,高兴,伤心"));
+        assert!(prompt.contains("\"emotion\": \"高兴\""));
+
+        // 只有一个表情时例子也只使用它
+        let prompt = response_format_prompt(&EmotionRegistry::default());
+        assert!(prompt.contains("只能从以下选项中选择：正常。"));
+        assert!(!prompt.contains("{example"));
+    }
+}
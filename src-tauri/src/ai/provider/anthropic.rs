@@ -0,0 +1,149 @@
+//! Anthropic Messages API：`POST {base_url}/messages`
+//!
+//! 与 OpenAI 格式的区别：
+//! - 使用 `x-api-key` 与 `anthropic-version` 请求头鉴权
+//! - 系统提示词位于请求体顶层的 `system` 字段，消息列表中只允许 user/assistant
+//! - 流式响应的文本位于 `content_block_delta` 事件中
+
+use serde::Serialize;
+
+use super::{join_url, LlmProvider, StreamChunk};
+use crate::ai::types::ChatMessage;
+use crate::config::AIConfig;
+
+/// Messages API 版本
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+pub struct AnthropicProvider;
+
+#[derive(Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<&'a ChatMessage>,
+    temperature: f64,
+    max_tokens: i32,
+    stream: bool,
+}
+
+impl LlmProvider for AnthropicProvider {
+    fn build_request(
+        &self,
+        http: &reqwest::Client,
+        config: &AIConfig,
+        messages: &[ChatMessage],
+        stream: bool,
+    ) -> reqwest::RequestBuilder {
+        // 多条系统消息合并为一个顶层 system 字段
+        let system: Vec<&str> = messages
+            .iter()
+            .filter(|message| message.role == "system")
+            .map(|message| message.content.as_str())
+            .collect();
+        let system = (!system.is_empty()).then(|| system.join("\n\n"));
+
+        http.post(join_url(&config.base_url, "messages"))
+            .header("x-api-key", config.api_key.expose())
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&MessagesRequest {
+                model: &config.model,
+                system,
+                messages: messages.iter().filter(|message| message.role != "system").collect(),
+                temperature: config.temperature,
+                max_tokens: config.max_tokens,
+                stream,
+            })
+    }
+
+    fn parse_completion(&self, body: &serde_json::Value) -> Option<String> {
+        let text: String = body["content"]
+            .as_array()?
+            .iter()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect();
+        Some(text)
+    }
+
+    fn parse_stream_chunk(&self, data: &str) -> StreamChunk {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(data) else {
+            return StreamChunk::Skip;
+        };
+        match value["type"].as_str() {
+            Some("content_block_delta") => match value["delta"]["text"].as_str() {
+                Some(text) => StreamChunk::Delta(text.to_string()),
+                None => StreamChunk::Skip,
+            },
+            Some("message_stop") => StreamChunk::Done,
+            _ => StreamChunk::Skip,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::client::AiClient;
+    use crate::ai::mock_server::{MockResponse, MockServer};
+    use crate::config::ProviderKind;
+    use serde_json::json;
+
+    fn config_for(server: &MockServer) -> AIConfig {
+        AIConfig {
+            provider: ProviderKind::Anthropic,
+            api_key: "sk-ant-test".into(),
+            base_url: server.base_url().to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            ..AIConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_against_stub_server() {
+        let server = MockServer::start(vec![MockResponse::json(json!({
+            "type": "message",
+            "role": "assistant",
+            "content": [{ "type": "text", "text": "你好呀" }],
+            "stop_reason": "end_turn"
+        }))])
+        .await;
+
+        let client = AiClient::new(config_for(&server)).unwrap();
+        let content = client.chat(vec![ChatMessage::system("人设"), ChatMessage::user("你好")]).await.unwrap();
+        assert_eq!(content, "你好呀");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/messages");
+        assert_eq!(request.header("x-api-key"), Some("sk-ant-test"));
+        assert_eq!(request.header("anthropic-version"), Some(ANTHROPIC_VERSION));
+        assert_eq!(request.header("authorization"), None);
+
+        let body = request.json();
+        assert_eq!(body["system"], "人设");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(body["messages"][0]["role"], "user");
+    }
+
+    #[tokio::test]
+    async fn test_stream_against_stub_server() {
+        let events = vec![
+            json!({"type": "message_start", "message": {"id": "msg_1"}}).to_string(),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "你"}}).to_string(),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "好"}}).to_string(),
+            json!({"type": "message_stop"}).to_string(),
+        ];
+        let server = MockServer::start(vec![MockResponse::sse(&events)]).await;
+
+        let client = AiClient::new(config_for(&server)).unwrap();
+        let mut deltas = Vec::new();
+        let content = client
+            .chat_stream(vec![ChatMessage::user("你好")], |delta| deltas.push(delta.to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(content, "你好");
+        assert_eq!(deltas, vec!["你", "好"]);
+        assert_eq!(server.requests()[0].json()["stream"], true);
+    }
+}
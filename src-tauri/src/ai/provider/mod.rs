@@ -0,0 +1,85 @@
+//! 大模型服务提供方
+//!
+//! 不同服务的请求地址、鉴权方式、消息格式和流式分帧各不相同，
+//! `LlmProvider` 把这些差异收拢起来，`AiClient` 只负责收发 HTTP。
+//!
+//! - `openai`: OpenAI 兼容接口（DeepSeek、阿里云百炼等）
+//! - `anthropic`: Anthropic Messages API
+//! - `ollama`: 本地 Ollama `/api/chat`
+
+pub mod anthropic;
+pub mod ollama;
+pub mod openai;
+
+use super::types::ChatMessage;
+use crate::config::{AIConfig, ProviderKind};
+
+/// 流式响应的分帧方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreamFraming {
+    /// `text/event-stream`，负载位于 `data:` 行
+    Sse,
+    /// 每行一个 JSON 对象
+    JsonLines,
+}
+
+/// 一条流式负载的解析结果
+#[derive(Debug, PartialEq)]
+pub enum StreamChunk {
+    Delta(String),
+    Done,
+    /// 与文本无关的负载（心跳、元数据等）
+    Skip,
+}
+
+pub trait LlmProvider: Send + Sync {
+    /// 是否必须填写 API Key
+    fn requires_api_key(&self) -> bool {
+        true
+    }
+
+    /// 构建完整的 HTTP 请求（地址、鉴权头与请求体）
+    fn build_request(
+        &self,
+        http: &reqwest::Client,
+        config: &AIConfig,
+        messages: &[ChatMessage],
+        stream: bool,
+    ) -> reqwest::RequestBuilder;
+
+    /// 从非流式响应体中取出回复文本
+    fn parse_completion(&self, body: &serde_json::Value) -> Option<String>;
+
+    /// 流式响应的分帧方式
+    fn stream_framing(&self) -> StreamFraming {
+        StreamFraming::Sse
+    }
+
+    /// 解析一条流式负载
+    fn parse_stream_chunk(&self, data: &str) -> StreamChunk;
+}
+
+/// 根据配置选择服务提供方
+pub fn provider_for(kind: ProviderKind) -> Box<dyn LlmProvider> {
+    match kind {
+        ProviderKind::OpenAI => Box::new(openai::OpenAIProvider),
+        ProviderKind::Anthropic => Box::new(anthropic::AnthropicProvider),
+        ProviderKind::Ollama => Box::new(ollama::OllamaProvider),
+    }
+}
+
+/// 拼接 base_url 与接口路径
+pub(crate) fn join_url(base_url: &str, path: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_url() {
+        assert_eq!(join_url("https://api.deepseek.com/v1/", "/chat/completions"), "https://api.deepseek.com/v1/chat/completions");
+        assert_eq!(join_url("http://localhost:11434", "api/chat"), "http://localhost:11434/api/chat");
+    }
+}
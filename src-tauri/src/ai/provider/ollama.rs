@@ -0,0 +1,135 @@
+//! 本地 Ollama：`POST {base_url}/api/chat`
+//!
+//! 无需 API Key；流式响应为每行一个 JSON 对象，最后一行带 `"done": true`。
+
+use serde::Serialize;
+
+use super::{join_url, LlmProvider, StreamChunk, StreamFraming};
+use crate::ai::types::ChatMessage;
+use crate::config::AIConfig;
+
+pub struct OllamaProvider;
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    stream: bool,
+    options: ChatOptions,
+}
+
+#[derive(Serialize)]
+struct ChatOptions {
+    temperature: f64,
+    num_predict: i32,
+}
+
+impl LlmProvider for OllamaProvider {
+    fn requires_api_key(&self) -> bool {
+        false
+    }
+
+    fn build_request(
+        &self,
+        http: &reqwest::Client,
+        config: &AIConfig,
+        messages: &[ChatMessage],
+        stream: bool,
+    ) -> reqwest::RequestBuilder {
+        let builder = http.post(join_url(&config.base_url, "api/chat")).json(&ChatRequest {
+            model: &config.model,
+            messages,
+            stream,
+            options: ChatOptions { temperature: config.temperature, num_predict: config.max_tokens },
+        });
+        // 经反向代理暴露的 Ollama 可能需要鉴权
+        if config.api_key.is_empty() {
+            builder
+        } else {
+            builder.bearer_auth(config.api_key.expose())
+        }
+    }
+
+    fn parse_completion(&self, body: &serde_json::Value) -> Option<String> {
+        body["message"]["content"].as_str().map(str::to_string)
+    }
+
+    fn stream_framing(&self) -> StreamFraming {
+        StreamFraming::JsonLines
+    }
+
+    fn parse_stream_chunk(&self, data: &str) -> StreamChunk {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(data) else {
+            return StreamChunk::Skip;
+        };
+        if value["done"].as_bool() == Some(true) {
+            return StreamChunk::Done;
+        }
+        match value["message"]["content"].as_str() {
+            Some(delta) => StreamChunk::Delta(delta.to_string()),
+            None => StreamChunk::Skip,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::client::AiClient;
+    use crate::ai::mock_server::{MockResponse, MockServer};
+    use crate::config::ProviderKind;
+    use serde_json::json;
+
+    fn config_for(server: &MockServer) -> AIConfig {
+        AIConfig {
+            provider: ProviderKind::Ollama,
+            api_key: Default::default(),
+            base_url: server.base_url().to_string(),
+            model: "qwen2.5:7b".to_string(),
+            ..AIConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_without_api_key() {
+        let server = MockServer::start(vec![MockResponse::json(json!({
+            "model": "qwen2.5:7b",
+            "message": { "role": "assistant", "content": "你好呀" },
+            "done": true
+        }))])
+        .await;
+
+        let client = AiClient::new(config_for(&server)).unwrap();
+        let content = client.chat(vec![ChatMessage::user("你好")]).await.unwrap();
+        assert_eq!(content, "你好呀");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/api/chat");
+        assert_eq!(request.header("authorization"), None);
+        let body = request.json();
+        assert_eq!(body["options"]["num_predict"], 2000);
+        assert_eq!(body["stream"], false);
+    }
+
+    #[tokio::test]
+    async fn test_stream_json_lines() {
+        let body = [
+            json!({"message": {"role": "assistant", "content": "你"}, "done": false}),
+            json!({"message": {"role": "assistant", "content": "好"}, "done": false}),
+            json!({"message": {"role": "assistant", "content": ""}, "done": true}),
+        ]
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect::<String>();
+        let server = MockServer::start(vec![MockResponse {
+            status: 200,
+            content_type: "application/x-ndjson".to_string(),
+            body,
+        }])
+        .await;
+
+        let client = AiClient::new(config_for(&server)).unwrap();
+        let content = client.chat_stream(vec![ChatMessage::user("你好")], |_| {}).await.unwrap();
+        assert_eq!(content, "你好");
+    }
+}
@@ -0,0 +1,99 @@
+//! OpenAI 兼容接口：`POST {base_url}/chat/completions`，Bearer 鉴权
+
+use serde::Serialize;
+
+use super::{join_url, LlmProvider, StreamChunk};
+use crate::ai::types::ChatMessage;
+use crate::config::AIConfig;
+
+pub struct OpenAIProvider;
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    temperature: f64,
+    max_tokens: i32,
+    stream: bool,
+}
+
+impl LlmProvider for OpenAIProvider {
+    fn build_request(
+        &self,
+        http: &reqwest::Client,
+        config: &AIConfig,
+        messages: &[ChatMessage],
+        stream: bool,
+    ) -> reqwest::RequestBuilder {
+        http.post(join_url(&config.base_url, "chat/completions"))
+            .bearer_auth(config.api_key.expose())
+            .json(&ChatRequest {
+                model: &config.model,
+                messages,
+                temperature: config.temperature,
+                max_tokens: config.max_tokens,
+                stream,
+            })
+    }
+
+    fn parse_completion(&self, body: &serde_json::Value) -> Option<String> {
+        body["choices"][0]["message"]["content"].as_str().map(str::to_string)
+    }
+
+    fn parse_stream_chunk(&self, data: &str) -> StreamChunk {
+        if data == "[DONE]" {
+            return StreamChunk::Done;
+        }
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(data) else {
+            return StreamChunk::Skip;
+        };
+        match value["choices"][0]["delta"]["content"].as_str() {
+            Some(delta) => StreamChunk::Delta(delta.to_string()),
+            None => StreamChunk::Skip,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::client::AiClient;
+    use crate::ai::mock_server::{MockResponse, MockServer};
+    use serde_json::json;
+
+    fn config_for(server: &MockServer) -> AIConfig {
+        AIConfig {
+            api_key: "sk-test".into(),
+            base_url: server.base_url().to_string(),
+            ..AIConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_stream_chunk() {
+        let provider = OpenAIProvider;
+        let data = r#"{"choices":[{"index":0,"delta":{"content":"你好"}}]}"#;
+        assert_eq!(provider.parse_stream_chunk(data), StreamChunk::Delta("你好".to_string()));
+        assert_eq!(provider.parse_stream_chunk(r#"{"choices":[{"delta":{}}]}"#), StreamChunk::Skip);
+        assert_eq!(provider.parse_stream_chunk("[DONE]"), StreamChunk::Done);
+    }
+
+    #[tokio::test]
+    async fn test_chat_against_stub_server() {
+        let server = MockServer::start(vec![MockResponse::json(json!({
+            "choices": [{ "index": 0, "message": { "role": "assistant", "content": "你好呀" } }]
+        }))])
+        .await;
+
+        let client = AiClient::new(config_for(&server)).unwrap();
+        let content = client.chat(vec![ChatMessage::system("人设"), ChatMessage::user("你好")]).await.unwrap();
+        assert_eq!(content, "你好呀");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/chat/completions");
+        assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+        let body = request.json();
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["max_tokens"], 2000);
+    }
+}
@@ -0,0 +1,193 @@
+//! 流式响应解析
+//!
+//! - `StreamDecoder`: 把响应字节流切分为一条条负载（SSE 的 `data:` 行或 JSON Lines 的每一行）
+//! - `PetItemStream`: 从逐步到达的文本中增量解析 `[{message, emotion, japanese}, ...]`，
+//!   每当一个对象完整闭合就立即产出一条回复
+
+use super::parser::item_from_value;
+use super::provider::StreamFraming;
+use super::types::PetResponseItem;
+use crate::emotion::EmotionRegistry;
+
+/// 流式负载解码器
+pub struct StreamDecoder {
+    framing: StreamFraming,
+    buffer: Vec<u8>,
+}
+
+impl StreamDecoder {
+    pub fn new(framing: StreamFraming) -> Self {
+        Self { framing, buffer: Vec::new() }
+    }
+
+    /// 输入一段字节，返回其中所有完整行对应的负载
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+        let mut payloads = Vec::new();
+
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+
+            match self.framing {
+                StreamFraming::Sse => {
+                    // 空行、注释行以及 event/id 等字段都不关心
+                    let Some(data) = line.strip_prefix("data:") else { continue };
+                    payloads.push(data.strip_prefix(' ').unwrap_or(data).to_string());
+                }
+                StreamFraming::JsonLines => {
+                    if !line.trim().is_empty() {
+                        payloads.push(line.to_string());
+                    }
+                }
+            }
+        }
+        payloads
+    }
+}
+
+/// 增量解析桌宠回复列表
+#[derive(Default)]
+pub struct PetItemStream {
+    buffer: String,
+    /// 下一个待扫描的字节位置
+    pos: usize,
+    /// 当前嵌套深度，列表本身为 1
+    depth: usize,
+    in_string: bool,
+    escape: bool,
+    /// 当前对象的起始字节位置
+    item_start: Option<usize>,
+    finished: bool,
+    /// 用于把表情映射为合法表情
+    emotions: EmotionRegistry,
+}
+
+impl PetItemStream {
+    pub fn new(emotions: EmotionRegistry) -> Self {
+        Self { emotions, ..Self::default() }
+    }
+
+    /// 列表是否已经闭合
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 输入一段模型输出，返回新闭合的有效条目（表情已映射为合法表情）
+    pub fn push(&mut self, delta: &str) -> Vec<PetResponseItem> {
+        self.buffer.push_str(delta);
+        let mut items = Vec::new();
+        let bytes = self.buffer.as_bytes();
+
+        // 结构字符都是 ASCII，按字节扫描不会切断多字节字符
+        while self.pos < bytes.len() && !self.finished {
+            let index = self.pos;
+            let byte = bytes[index];
+            self.pos += 1;
+
+            if self.in_string {
+                if self.escape {
+                    self.escape = false;
+                } else if byte == b'\\' {
+                    self.escape = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+
+            // 列表开始之前的内容（例如多余的说明文字）全部跳过
+            if self.depth == 0 {
+                if byte == b'[' {
+                    self.depth = 1;
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => {
+                    if self.depth == 1 && byte == b'{' {
+                        self.item_start = Some(index);
+                    }
+                    self.depth += 1;
+                }
+                b'}' | b']' => {
+                    self.depth -= 1;
+                    if self.depth == 1 {
+                        if let Some(start) = self.item_start.take() {
+                            if let Some(item) = parse_item(&self.buffer[start..=index], &self.emotions) {
+                                items.push(item);
+                            }
+                        }
+                    } else if self.depth == 0 {
+                        self.finished = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        items
+    }
+}
+
+fn parse_item(raw: &str, emotions: &EmotionRegistry) -> Option<PetResponseItem> {
+    let value: serde_json::Value = serde_json::from_str(raw).ok()?;
+    item_from_value(&value, emotions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emotion::builtin_registry;
+
+    #[test]
+    fn test_sse_decoder_handles_split_lines() {
+        let mut decoder = StreamDecoder::new(StreamFraming::Sse);
+        assert!(decoder.feed(b"data: {\"a\"").is_empty());
+        assert_eq!(decoder.feed(b":1}\r\n\r\n: keep-alive\nevent: ping\n"), vec!["{\"a\":1}"]);
+        assert_eq!(decoder.feed(b"data: [DONE]\n"), vec!["[DONE]"]);
+    }
+
+    #[test]
+    fn test_json_lines_decoder() {
+        let mut decoder = StreamDecoder::new(StreamFraming::JsonLines);
+        assert_eq!(decoder.feed(b"{\"a\":1}\n\n{\"b\""), vec!["{\"a\":1}"]);
+        assert_eq!(decoder.feed(b":2}\n"), vec!["{\"b\":2}"]);
+    }
+
+    #[test]
+    fn test_item_stream_emits_each_completed_object() {
+        let mut stream = PetItemStream::new(builtin_registry());
+        let mut items = Vec::new();
+        let reply = r#"[{"message": "你好呀{", "emotion": "高兴", "japanese": "こんにちは"}, {"message": "说\"了\"", "emotion": "认真", "japanese": "はい"}]"#;
+
+        // 每次只输入三个字符，模拟逐 token 到达
+        let chars: Vec<char> = reply.chars().collect();
+        for chunk in chars.chunks(3) {
+            let delta: String = chunk.iter().collect();
+            let produced = stream.push(&delta);
+            items.extend(produced);
+            if items.len() == 1 {
+                assert!(!stream.is_finished());
+            }
+        }
+
+        assert!(stream.is_finished());
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].message, "你好呀{");
+        assert_eq!(items[1].message, "说\"了\"");
+    }
+
+    #[test]
+    fn test_item_stream_skips_preamble_and_normalizes_items() {
+        let mut stream = PetItemStream::new(builtin_registry());
+        let items = stream.push(
+            r#"好的：[{"emotion": "正常"}, {"message": "a", "emotion": "无语", "japanese": "a"}, {"message": "b", "emotion": "正常", "japanese": "b"}] 结束"#,
+        );
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].emotion, "无奈");
+        assert_eq!(items[1].message, "b");
+    }
+}
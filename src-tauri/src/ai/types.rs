@@ -0,0 +1,98 @@
+//! AI 请求与响应的数据结构
+//!
+//! 与前端 types/ai.ts 中的同名类型一一对应；各服务的请求体定义在 provider 子模块中。
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// 单条对话消息（OpenAI 兼容格式）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self { role: "system".to_string(), content: content.into() }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self { role: "user".to_string(), content: content.into() }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self { role: "assistant".to_string(), content: content.into() }
+    }
+}
+
+/// 桌宠的一句回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PetResponseItem {
+    pub message: String,
+    pub emotion: String,
+    pub japanese: String,
+}
+
+/// 返回给前端的桌宠回复
+#[derive(Debug, Clone, Serialize)]
+pub struct PetResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<PetResponseItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AppError>,
+}
+
+impl PetResponse {
+    pub fn ok(data: Vec<PetResponseItem>) -> Self {
+        Self { success: true, data: Some(data), error: None }
+    }
+
+    pub fn fail(error: impl Into<AppError>) -> Self {
+        Self { success: false, data: None, error: Some(error.into()) }
+    }
+}
+
+/// 流式对话中每解析出一句回复时推送给前端的事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct PetStreamItem {
+    pub stream_id: String,
+    pub index: usize,
+    pub item: PetResponseItem,
+}
+
+/// 流式对话结束后的汇总结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PetStreamResult {
+    pub success: bool,
+    pub count: usize,
+    pub cancelled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AppError>,
+}
+
+impl PetStreamResult {
+    pub fn completed(count: usize) -> Self {
+        Self { success: true, count, cancelled: false, error: None }
+    }
+
+    pub fn failed(error: impl Into<AppError>) -> Self {
+        Self { success: false, count: 0, cancelled: false, error: Some(error.into()) }
+    }
+
+    pub fn cancelled() -> Self {
+        Self { success: false, count: 0, cancelled: true, error: None }
+    }
+}
+
+/// 连接测试结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionTestResult {
+    pub success: bool,
+    pub message: String,
+    /// 失败的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AppError>,
+}
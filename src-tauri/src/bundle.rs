@@ -0,0 +1,482 @@
+/*!
+ * @fileoverview 配置导出与导入
+ * @description 把当前档案的配置、长期记忆与角色包引用打包为一个可移植的 TOML 文件，在另一台机器上导入；
+ *   长期记忆保存在所有档案共用的 memory.db 中，不区分档案
+ * @features
+ *   - 导出文件层的配置，不含环境变量与命令行的覆盖
+ *   - API 密钥（明文）与窗口位置（含各桌宠的位置）可选导出，默认都不导出；包中没有的部分导入时沿用本机的
+ *   - 导入时先按配置结构版本迁移并校验，无效的包不会修改任何数据
+ *   - merge: 包中的配置字段覆盖本机对应的字段，记忆追加到本机已有的记忆中
+ *   - replace: 配置以包中的为准，本机的记忆（所有档案共用）先清空
+ *   - 报告本机没有安装的角色包；包中的当前角色本机没有时沿用本机的角色
+ * @structures
+ *   - Bundle: 配置包的内容
+ *   - ExportOptions: 导出选项
+ *   - ImportMode: 导入方式
+ *   - ImportSummary: 导入结果
+ *   - BundleError: 导出导入错误
+ * @format
+ *   format = "desktop_pet-bundle"、version、exported_at，
+ *   [config] 与 config.toml 结构相同（含 schema_version），[[memories]] 记忆，[[characters]] 角色包引用
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use tokio::fs;
+
+use crate::character::{CharacterPack, CharacterStore, BUILTIN_CHARACTER};
+use crate::config::{self, AppConfig, ConfigError, ConfigStore};
+use crate::history::now_millis;
+use crate::memory::{Memory, MemoryError, MemorySource, MemoryStore};
+
+/// 配置包的格式标识
+pub const BUNDLE_FORMAT: &str = "desktop_pet-bundle";
+
+/// 当前配置包的版本
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// 桌宠记录中属于窗口位置的字段，与 [window] 一起按选项导出
+const PET_WINDOW_FIELDS: [&str; 4] = ["x", "y", "monitor", "scale"];
+
+// 配置包错误类型
+#[derive(Debug)]
+pub enum BundleError {
+    IoError(std::io::Error),
+    SerializationError(toml::ser::Error),
+    DeserializationError(toml::de::Error),
+    UnsupportedBundle(String),
+    ConfigError(ConfigError),
+    MemoryError(MemoryError),
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BundleError::IoError(e) => write!(f, "IO错误: {}", e),
+            BundleError::SerializationError(e) => write!(f, "序列化错误: {}", e),
+            BundleError::DeserializationError(e) => write!(f, "配置包格式错误: {}", e),
+            BundleError::UnsupportedBundle(e) => write!(f, "不支持的配置包: {}", e),
+            BundleError::ConfigError(e) => write!(f, "{}", e),
+            BundleError::MemoryError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for BundleError {}
+
+impl From<ConfigError> for BundleError {
+    fn from(e: ConfigError) -> Self {
+        BundleError::ConfigError(e)
+    }
+}
+
+impl From<MemoryError> for BundleError {
+    fn from(e: MemoryError) -> Self {
+        BundleError::MemoryError(e)
+    }
+}
+
+/// 导出选项，默认不导出密钥与窗口位置
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    /// 以明文导出 API 密钥
+    pub include_secrets: bool,
+    /// 导出窗口位置；不同机器的显示器布局不同，通常不需要
+    pub include_window: bool,
+}
+
+/// 导入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// 合并进本机的配置与记忆
+    Merge,
+    /// 替换当前档案的配置与本机的全部记忆（记忆由所有档案共用）
+    Replace,
+}
+
+/// 包中的一条记忆；会话只存在于原来的机器上，不导出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleMemory {
+    pub content: String,
+    pub source: MemorySource,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// 角色包引用，只记录标识，不包含立绘等文件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CharacterRef {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    /// 随应用发布的内置角色包
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+/// 配置包的内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: i64,
+    pub config: toml::Table,
+    #[serde(default)]
+    pub memories: Vec<BundleMemory>,
+    #[serde(default)]
+    pub characters: Vec<CharacterRef>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportSummary {
+    /// 新导入的记忆条数，与已有记忆相同的不计
+    pub memories_imported: usize,
+    /// 包中引用而本机没有安装的角色包
+    pub missing_characters: Vec<CharacterRef>,
+    /// 包中的当前角色本机没有，沿用了本机的角色
+    pub kept_character: bool,
+}
+
+/// 把 `overlay` 中的字段逐层合并进 `base`
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        if let toml::Value::Table(overlay) = &value {
+            if let Some(toml::Value::Table(base)) = base.get_mut(&key) {
+                merge_tables(base, overlay.clone());
+                continue;
+            }
+        }
+        base.insert(key, value);
+    }
+}
+
+impl Bundle {
+    /// 打包配置、记忆与角色包引用
+    pub fn build(
+        config: &AppConfig,
+        memories: &[Memory],
+        characters: &[CharacterPack],
+        options: ExportOptions,
+    ) -> Result<Self, BundleError> {
+        let mut document = toml::Table::try_from(config).map_err(BundleError::SerializationError)?;
+        // 序列化时密钥只输出占位符，按选项换成明文或留空
+        if let Some(ai) = document.get_mut("ai").and_then(toml::Value::as_table_mut) {
+            let api_key = if options.include_secrets { config.ai.api_key.expose() } else { "" };
+            ai.insert("api_key".to_string(), toml::Value::String(api_key.to_string()));
+        }
+        if !options.include_window {
+            document.remove("window");
+            if let Some(pets) = document.get_mut("pets").and_then(toml::Value::as_table_mut) {
+                for pet in pets.iter_mut().filter_map(|(_, pet)| pet.as_table_mut()) {
+                    for field in PET_WINDOW_FIELDS {
+                        pet.remove(field);
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            exported_at: now_millis(),
+            config: document,
+            memories: memories
+                .iter()
+                .map(|memory| BundleMemory {
+                    content: memory.content.clone(),
+                    source: memory.source,
+                    created_at: memory.created_at,
+                    updated_at: memory.updated_at,
+                })
+                .collect(),
+            characters: characters
+                .iter()
+                .map(|pack| CharacterRef {
+                    id: pack.manifest.id.clone(),
+                    name: pack.manifest.name.clone(),
+                    version: pack.manifest.version.clone(),
+                    builtin: pack.builtin,
+                })
+                .collect(),
+        })
+    }
+
+    /// 解析配置包，拒绝其他格式与更新版本的应用导出的包
+    pub fn parse(content: &str) -> Result<Self, BundleError> {
+        let bundle: Self = toml::from_str(content).map_err(BundleError::DeserializationError)?;
+        if bundle.format != BUNDLE_FORMAT {
+            return Err(BundleError::UnsupportedBundle(format!("未知的格式 {:?}", bundle.format)));
+        }
+        if bundle.version > BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedBundle(format!(
+                "版本 {} 由更新的应用导出（当前版本 {}），请升级应用",
+                bundle.version, BUNDLE_VERSION
+            )));
+        }
+        Ok(bundle)
+    }
+
+    /// 包中的配置迁移到当前版本后与本机配置合并或替换，未通过校验时报错
+    pub fn resolve_config(&self, current: &AppConfig, mode: ImportMode) -> Result<AppConfig, BundleError> {
+        let mut document = self.config.clone();
+        config::migrate_document(&mut document)?;
+        let has_window = document.contains_key("window");
+        let document = match mode {
+            ImportMode::Merge => {
+                let mut base = toml::Table::try_from(current).map_err(BundleError::SerializationError)?;
+                merge_tables(&mut base, document);
+                base
+            }
+            ImportMode::Replace => document,
+        };
+        let (mut resolved, _) = config::parse_config_document(document)?;
+        // 没有导出窗口位置时沿用本机的，本机没有的桌宠使用默认位置
+        if !has_window {
+            resolved.window = current.window.clone();
+            for (id, pet) in resolved.pets.iter_mut() {
+                let local = current.pets.get(id).cloned().unwrap_or_default();
+                (pet.x, pet.y, pet.monitor, pet.scale) = (local.x, local.y, local.monitor, local.scale);
+            }
+        }
+        // 没有导出密钥（或合并时得到占位符）时沿用本机的密钥
+        if resolved.ai.api_key.is_empty() {
+            resolved.ai.api_key = current.ai.api_key.clone();
+        }
+        resolved.ai.keep_api_key(&current.ai);
+        resolved.validate().map_err(ConfigError::Invalid)?;
+        Ok(resolved)
+    }
+}
+
+/// 把当前档案导出为配置包文件
+pub async fn export_bundle(
+    path: &Path,
+    options: ExportOptions,
+    config: &ConfigStore,
+    memory: &MemoryStore,
+    characters: &CharacterStore,
+) -> Result<(), BundleError> {
+    let bundle = Bundle::build(&config.file_config().await, &memory.list()?, &characters.list(), options)?;
+    let content = toml::to_string_pretty(&bundle).map_err(BundleError::SerializationError)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await.map_err(BundleError::IoError)?;
+    }
+    fs::write(path, content).await.map_err(BundleError::IoError)
+}
+
+/// 导入配置包到当前档案；配置的变化通过 config-changed 事件通知各窗口
+pub async fn import_bundle(
+    path: &Path,
+    mode: ImportMode,
+    config: &ConfigStore,
+    memory: &MemoryStore,
+    characters: &CharacterStore,
+) -> Result<ImportSummary, BundleError> {
+    let content = fs::read_to_string(path).await.map_err(BundleError::IoError)?;
+    let bundle = Bundle::parse(&content)?;
+    let current = config.file_config().await;
+    let mut resolved = bundle.resolve_config(&current, mode)?;
+
+    let kept_character = characters.get(&resolved.character.active).is_err();
+    if kept_character {
+        resolved.character.active = current.character.active.clone();
+    }
+    // 其他桌宠的角色包未安装时改用内置角色
+    for pet in resolved.pets.values_mut() {
+        if characters.get(&pet.character).is_err() {
+            pet.character = BUILTIN_CHARACTER.to_string();
+        }
+    }
+    let missing_characters = bundle
+        .characters
+        .iter()
+        .filter(|reference| characters.get(&reference.id).is_err())
+        .cloned()
+        .collect();
+
+    // 先应用配置，配置无效时不修改记忆；记忆导入失败时恢复原来的配置
+    config.replace(resolved).await?;
+    let entries = bundle
+        .memories
+        .iter()
+        .filter(|entry| !entry.content.trim().is_empty())
+        .map(|entry| (entry.content.as_str(), entry.source, entry.created_at, entry.updated_at));
+    let memories_imported = match memory.import(entries, mode == ImportMode::Replace) {
+        Ok(imported) => imported,
+        Err(e) => {
+            config.replace(current).await?;
+            return Err(BundleError::MemoryError(e));
+        }
+    };
+    Ok(ImportSummary { memories_imported, missing_characters, kept_character })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigSection, Overrides, PetConfig, ProfileStore};
+    use std::path::PathBuf;
+
+    struct Machine {
+        dir: PathBuf,
+        config: ConfigStore,
+        memory: MemoryStore,
+        characters: CharacterStore,
+    }
+
+    async fn machine(name: &str) -> Machine {
+        let dir = std::env::temp_dir().join(format!("desktop_pet_test_bundle_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        let manager = ProfileStore::new(dir.clone()).init(None).await.unwrap();
+        let builtin = Path::new(env!("CARGO_MANIFEST_DIR")).join("characters");
+        Machine {
+            config: ConfigStore::open(manager, Overrides::default()).await.unwrap(),
+            memory: MemoryStore::open_in_memory().unwrap(),
+            characters: CharacterStore::new(builtin, dir.join("characters")),
+            dir,
+        }
+    }
+
+    async fn export(from: &Machine, options: ExportOptions) -> PathBuf {
+        let path = from.dir.join("bundle.toml");
+        export_bundle(&path, options, &from.config, &from.memory, &from.characters).await.unwrap();
+        path
+    }
+
+    async fn import(path: &Path, to: &Machine, mode: ImportMode) -> Result<ImportSummary, BundleError> {
+        import_bundle(path, mode, &to.config, &to.memory, &to.characters).await
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_excludes_secrets_and_window_by_default() {
+        let from = machine("from").await;
+        from.config
+            .update(|config| {
+                config.ai.model = "gpt-4o".to_string();
+                config.ai.api_key = "sk-from".into();
+                config.window.main_window_x = 3000.0;
+            })
+            .await;
+        from.memory.add("我的生日是5月3日", MemorySource::Auto, Some(1), Some(2)).unwrap();
+        let path = export(&from, ExportOptions::default()).await;
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("sk-from") && !content.contains("main_window_x"));
+
+        let to = machine("to").await;
+        to.config
+            .update(|config| {
+                config.ai.api_key = "sk-to".into();
+                config.window.main_window_x = 100.0;
+            })
+            .await;
+        to.memory.add("我在学习 Rust 编程", MemorySource::Manual, None, None).unwrap();
+        let summary = import(&path, &to, ImportMode::Merge).await.unwrap();
+        assert_eq!(summary.memories_imported, 1);
+        assert!(!summary.kept_character && summary.missing_characters.is_empty());
+
+        let config = to.config.get().await;
+        assert_eq!(config.ai.model, "gpt-4o");
+        // 没有导出的密钥与窗口位置沿用本机的
+        assert_eq!(config.ai.api_key.expose(), "sk-to");
+        assert_eq!(config.window.main_window_x, 100.0);
+        assert_eq!(to.memory.list().unwrap().len(), 2);
+        // 再次导入不会重复添加记忆
+        assert_eq!(import(&path, &to, ImportMode::Merge).await.unwrap().memories_imported, 0);
+
+        // 导出密钥后替换：记忆只剩包中的
+        let path = export(&from, ExportOptions { include_secrets: true, include_window: false }).await;
+        import(&path, &to, ImportMode::Replace).await.unwrap();
+        assert_eq!(to.config.get().await.ai.api_key.expose(), "sk-from");
+        let memories = to.memory.list().unwrap();
+        assert_eq!(memories.len(), 1);
+        assert_eq!((memories[0].source, memories[0].session_id), (MemorySource::Auto, None));
+
+        let _ = std::fs::remove_dir_all(&from.dir);
+        let _ = std::fs::remove_dir_all(&to.dir);
+    }
+
+    #[tokio::test]
+    async fn test_pet_positions_follow_window_option() {
+        let from = machine("pets_from").await;
+        let pets = serde_json::json!({
+            "pet-2": {"x": 3000.0, "y": 40.0, "monitor": "DP-1", "scale": 2.0, "size": 180},
+            "pet-3": {"x": 2500.0, "y": 60.0},
+        });
+        from.config.patch(ConfigSection::Pets, pets).await.unwrap();
+        let path = export(&from, ExportOptions::default()).await;
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("3000") && !content.contains("DP-1"));
+
+        // 本机已有的桌宠保留本机的位置，新的桌宠使用默认位置
+        let to = machine("pets_to").await;
+        to.config.patch(ConfigSection::Pets, serde_json::json!({"pet-2": {"x": 100.0, "y": 200.0}})).await.unwrap();
+        import(&path, &to, ImportMode::Replace).await.unwrap();
+        let pets = to.config.get().await.pets;
+        assert_eq!((pets["pet-2"].x, pets["pet-2"].y, pets["pet-2"].monitor.as_deref()), (100.0, 200.0, None));
+        assert_eq!(pets["pet-2"].size, 180);
+        assert_eq!(pets["pet-3"].x, PetConfig::default().x);
+
+        let path = export(&from, ExportOptions { include_secrets: false, include_window: true }).await;
+        import(&path, &to, ImportMode::Merge).await.unwrap();
+        let pets = to.config.get().await.pets;
+        assert_eq!((pets["pet-2"].x, pets["pet-2"].scale), (3000.0, Some(2.0)));
+
+        let _ = std::fs::remove_dir_all(&from.dir);
+        let _ = std::fs::remove_dir_all(&to.dir);
+    }
+
+    #[tokio::test]
+    async fn test_import_migrates_and_validates() {
+        let to = machine("validate").await;
+        to.memory.add("我在学习 Rust 编程", MemorySource::Manual, None, None).unwrap();
+        let path = to.dir.join("bundle.toml");
+
+        // 旧版本的配置（没有 schema_version）被迁移；包中只有部分字段时合并进本机配置
+        std::fs::write(
+            &path,
+            r#"
+format = "desktop_pet-bundle"
+version = 1
+[config.appearance]
+pet_size = 200
+[config.character]
+active = "not-installed"
+[[characters]]
+id = "not-installed"
+name = "未安装"
+"#,
+        )
+        .unwrap();
+        let summary = import(&path, &to, ImportMode::Merge).await.unwrap();
+        assert!(summary.kept_character);
+        assert_eq!(summary.missing_characters.len(), 1);
+        let config = to.config.get().await;
+        assert_eq!(config.appearance.pet_size, 200);
+        assert_eq!(config.character.active, crate::character::BUILTIN_CHARACTER);
+
+        // 无效的包不修改任何数据
+        let invalid = [
+            "format = \"desktop_pet-bundle\"\nversion = 1\n[config.appearance]\npet_size = 5\n",
+            "format = \"desktop_pet-bundle\"\nversion = 1\n[config]\nschema_version = 99\n",
+            "format = \"desktop_pet-bundle\"\nversion = 2\n[config]\n",
+            "format = \"other\"\nversion = 1\n[config]\n",
+        ];
+        for content in invalid {
+            std::fs::write(&path, content).unwrap();
+            assert!(import(&path, &to, ImportMode::Replace).await.is_err(), "{}", content);
+        }
+        assert_eq!(to.config.get().await.appearance.pet_size, 200);
+        assert_eq!(to.memory.list().unwrap().len(), 1);
+
+        let _ = std::fs::remove_dir_all(&to.dir);
+    }
+}
@@ -0,0 +1,544 @@
+/*!
+ * @fileoverview 角色包模块
+ * @description 把人设提示词、表情立绘与元数据打包为可安装的角色包，无需重新编译即可更换角色
+ * @features
+ *   - 角色包格式：目录或 zip，根目录下为 character.toml 清单
+ *   - 清单校验：必填字段、表情名或别名重复、立绘文件缺失、默认表情不存在
+ *   - 由清单生成表情注册表
+ *   - 安装（目录 / zip）、列出、卸载角色包
+ *   - 为自定义 URI 协议解析立绘文件路径
+ * @structures
+ *   - CharacterStore: 角色包存储（内置 + 用户安装）
+ *   - CharacterPack: 已加载的角色包
+ *   - CharacterManifest: character.toml 清单
+ *   - EmotionEntry: 清单中的表情（立绘、英文标识、别名、颜色）
+ *   - CharacterSummary: 提供给前端的角色信息
+ * @storage
+ *   - 内置角色包: 应用资源目录下的 characters/
+ *   - 用户角色包: 配置目录下的 characters/
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::emotion::{Emotion, EmotionRegistry};
+use crate::history::now_millis;
+
+/// 清单文件名
+pub const MANIFEST_FILE: &str = "character.toml";
+
+/// 内置的默认角色
+pub const BUILTIN_CHARACTER: &str = "qinling";
+
+// 角色包错误类型
+#[derive(Debug)]
+pub enum CharacterError {
+    IoError(std::io::Error),
+    ZipError(zip::result::ZipError),
+    ManifestError(toml::de::Error),
+    ManifestNotFound,
+    InvalidPack(Vec<String>),
+    CharacterNotFound(String),
+    BuiltinCharacter(String),
+    AssetNotFound(String),
+    UnsupportedSource(String),
+}
+
+impl fmt::Display for CharacterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CharacterError::IoError(e) => write!(f, "IO错误: {}", e),
+            CharacterError::ZipError(e) => write!(f, "解压失败: {}", e),
+            CharacterError::ManifestError(e) => write!(f, "{} 格式错误: {}", MANIFEST_FILE, e),
+            CharacterError::ManifestNotFound => write!(f, "角色包中没有 {}", MANIFEST_FILE),
+            CharacterError::InvalidPack(problems) => write!(f, "角色包无效: {}", problems.join("；")),
+            CharacterError::CharacterNotFound(id) => write!(f, "角色不存在: {}", id),
+            CharacterError::BuiltinCharacter(id) => write!(f, "内置角色不能被覆盖或卸载: {}", id),
+            CharacterError::AssetNotFound(path) => write!(f, "角色资源不存在: {}", path),
+            CharacterError::UnsupportedSource(path) => write!(f, "只支持目录或 zip 文件: {}", path),
+        }
+    }
+}
+
+impl Error for CharacterError {}
+
+impl From<std::io::Error> for CharacterError {
+    fn from(e: std::io::Error) -> Self {
+        CharacterError::IoError(e)
+    }
+}
+
+impl From<zip::result::ZipError> for CharacterError {
+    fn from(e: zip::result::ZipError) -> Self {
+        CharacterError::ZipError(e)
+    }
+}
+
+/// 清单中的表情，`sprite` 为相对角色包根目录的路径
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmotionEntry {
+    pub name: String,
+    pub sprite: String,
+    /// 英文标识
+    #[serde(default)]
+    pub key: Option<String>,
+    /// 模型可能输出的其他说法
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// 显示颜色
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// character.toml 清单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CharacterManifest {
+    /// 唯一标识，同时也是安装目录名
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    /// 角色人设
+    pub prompt: String,
+    /// 用户（“我”）的设定
+    #[serde(default)]
+    pub user_persona: Option<String>,
+    pub default_emotion: String,
+    /// 角色头像，用于角色列表
+    #[serde(default)]
+    pub avatar: Option<String>,
+    pub emotions: Vec<EmotionEntry>,
+}
+
+/// 提供给前端的角色信息
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CharacterSummary {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub author: String,
+    pub description: String,
+    pub default_emotion: String,
+    pub emotions: Vec<String>,
+    pub has_avatar: bool,
+    pub builtin: bool,
+}
+
+/// 已加载并通过校验的角色包
+#[derive(Debug, Clone)]
+pub struct CharacterPack {
+    pub manifest: CharacterManifest,
+    pub dir: PathBuf,
+    pub builtin: bool,
+}
+
+impl CharacterPack {
+    /// 读取并校验目录中的角色包
+    pub fn load(dir: &Path, builtin: bool) -> Result<Self, CharacterError> {
+        let path = dir.join(MANIFEST_FILE);
+        if !path.is_file() {
+            return Err(CharacterError::ManifestNotFound);
+        }
+        let content = fs::read_to_string(&path)?;
+        let manifest: CharacterManifest = toml::from_str(&content).map_err(CharacterError::ManifestError)?;
+        validate(&manifest, dir)?;
+        Ok(Self { manifest, dir: dir.to_path_buf(), builtin })
+    }
+
+    /// 人设与用户设定拼成的系统提示词
+    pub fn system_prompt(&self) -> String {
+        let mut prompt = format!("以下是你的人设：\n{}", self.manifest.prompt.trim());
+        if let Some(persona) = self.manifest.user_persona.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            prompt.push_str(&format!("\n\n以下是我的设定：\n{}", persona));
+        }
+        prompt
+    }
+
+    /// 表情对应的立绘；没有该表情时使用默认表情的立绘
+    pub fn sprite_path(&self, emotion: &str) -> PathBuf {
+        let find = |name: &str| self.manifest.emotions.iter().find(|entry| entry.name == name);
+        find(emotion)
+            .or_else(|| find(&self.manifest.default_emotion))
+            .map(|entry| self.dir.join(&entry.sprite))
+            .unwrap_or_else(|| self.dir.clone())
+    }
+
+    /// 角色包的表情注册表
+    pub fn emotion_registry(&self) -> EmotionRegistry {
+        let emotions = self
+            .manifest
+            .emotions
+            .iter()
+            .map(|entry| Emotion {
+                name: entry.name.clone(),
+                key: entry.key.clone(),
+                aliases: entry.aliases.clone(),
+                color: entry.color.clone(),
+            })
+            .collect();
+        EmotionRegistry::new(emotions, &self.manifest.default_emotion)
+    }
+
+    pub fn avatar_path(&self) -> Option<PathBuf> {
+        self.manifest.avatar.as_ref().map(|avatar| self.dir.join(avatar))
+    }
+
+    pub fn summary(&self) -> CharacterSummary {
+        let manifest = &self.manifest;
+        CharacterSummary {
+            id: manifest.id.clone(),
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            author: manifest.author.clone(),
+            description: manifest.description.clone(),
+            default_emotion: manifest.default_emotion.clone(),
+            emotions: manifest.emotions.iter().map(|entry| entry.name.clone()).collect(),
+            has_avatar: manifest.avatar.is_some(),
+            builtin: self.builtin,
+        }
+    }
+}
+
+/// id 只能由小写字母、数字、`-` 和 `_` 组成，保证可以安全地用作目录名
+pub(crate) fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// 包内相对路径，不允许绝对路径或 `..`
+fn is_relative_inside(path: &str) -> bool {
+    let path = Path::new(path);
+    !path.as_os_str().is_empty() && path.components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// 校验清单，一次性报告所有问题
+fn validate(manifest: &CharacterManifest, dir: &Path) -> Result<(), CharacterError> {
+    let mut problems = Vec::new();
+    if !is_valid_id(&manifest.id) {
+        problems.push(format!("id \"{}\" 只能包含小写字母、数字、- 和 _", manifest.id));
+    }
+    if manifest.name.trim().is_empty() {
+        problems.push("name 不能为空".to_string());
+    }
+    if manifest.prompt.trim().is_empty() {
+        problems.push("prompt 不能为空".to_string());
+    }
+    if manifest.emotions.is_empty() {
+        problems.push("emotions 至少需要一个表情".to_string());
+    }
+
+    let check_file = |field: &str, path: &str, problems: &mut Vec<String>| {
+        if !is_relative_inside(path) {
+            problems.push(format!("{} 的路径 \"{}\" 必须位于角色包内", field, path));
+        } else if !dir.join(path).is_file() {
+            problems.push(format!("{} 的文件 \"{}\" 不存在", field, path));
+        }
+    };
+
+    let mut names = HashSet::new();
+    for entry in &manifest.emotions {
+        if entry.name.trim().is_empty() {
+            problems.push("表情名不能为空".to_string());
+        } else if !names.insert(entry.name.as_str()) {
+            problems.push(format!("表情 \"{}\" 重复", entry.name));
+        }
+        check_file(&format!("表情 \"{}\"", entry.name), &entry.sprite, &mut problems);
+    }
+
+    // 英文标识和别名只能指向一个表情，也不能与其他表情名相同
+    let mut owners: HashMap<String, &str> = HashMap::new();
+    for entry in &manifest.emotions {
+        for synonym in entry.key.iter().chain(&entry.aliases) {
+            let synonym = synonym.trim().to_lowercase();
+            if synonym.is_empty() {
+                problems.push(format!("表情 \"{}\" 的别名不能为空", entry.name));
+            } else if names.contains(synonym.as_str()) && synonym != entry.name {
+                problems.push(format!("表情 \"{}\" 的别名 \"{}\" 与其他表情名相同", entry.name, synonym));
+            } else if let Some(owner) = owners.insert(synonym.clone(), &entry.name) {
+                if owner != entry.name {
+                    problems.push(format!("别名 \"{}\" 同时属于 \"{}\" 和 \"{}\"", synonym, owner, entry.name));
+                }
+            }
+        }
+    }
+    if let Some(avatar) = &manifest.avatar {
+        check_file("avatar", avatar, &mut problems);
+    }
+    if !names.contains(manifest.default_emotion.as_str()) {
+        problems.push(format!("默认表情 \"{}\" 不在 emotions 中", manifest.default_emotion));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(CharacterError::InvalidPack(problems))
+    }
+}
+
+/// 递归复制目录
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// 找到清单所在的目录：解压目录本身，或其中唯一的子目录
+fn find_pack_root(dir: &Path) -> Result<PathBuf, CharacterError> {
+    if dir.join(MANIFEST_FILE).is_file() {
+        return Ok(dir.to_path_buf());
+    }
+    let subdirs: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    match subdirs.as_slice() {
+        [only] if only.join(MANIFEST_FILE).is_file() => Ok(only.clone()),
+        _ => Err(CharacterError::ManifestNotFound),
+    }
+}
+
+// 角色包存储
+pub struct CharacterStore {
+    /// 随应用发布的只读角色包
+    builtin_dir: PathBuf,
+    /// 用户安装的角色包
+    user_dir: PathBuf,
+}
+
+impl CharacterStore {
+    pub fn new(builtin_dir: PathBuf, user_dir: PathBuf) -> Self {
+        Self { builtin_dir, user_dir }
+    }
+
+    /// 全部可用的角色包，内置的在前；无效的角色包会被跳过
+    pub fn list(&self) -> Vec<CharacterPack> {
+        let mut packs = Vec::new();
+        for (root, builtin) in [(&self.builtin_dir, true), (&self.user_dir, false)] {
+            let Ok(entries) = fs::read_dir(root) else {
+                continue;
+            };
+            let mut dirs: Vec<PathBuf> = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir() && !path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')))
+                .collect();
+            dirs.sort();
+            for dir in dirs {
+                match CharacterPack::load(&dir, builtin) {
+                    Ok(pack) => packs.push(pack),
+                    Err(e) => eprintln!("跳过无效的角色包 {}: {}", dir.display(), e),
+                }
+            }
+        }
+        packs
+    }
+
+    /// 按 id 读取角色包
+    pub fn get(&self, id: &str) -> Result<CharacterPack, CharacterError> {
+        if !is_valid_id(id) {
+            return Err(CharacterError::CharacterNotFound(id.to_string()));
+        }
+        for (root, builtin) in [(&self.user_dir, false), (&self.builtin_dir, true)] {
+            let dir = root.join(id);
+            if dir.is_dir() {
+                return CharacterPack::load(&dir, builtin);
+            }
+        }
+        Err(CharacterError::CharacterNotFound(id.to_string()))
+    }
+
+    /// 从目录或 zip 文件安装角色包；已安装的同名角色包会被替换
+    pub fn install(&self, source: &Path) -> Result<CharacterPack, CharacterError> {
+        fs::create_dir_all(&self.user_dir)?;
+        let staging = self.user_dir.join(format!(".install-{}", now_millis()));
+        let result = self.install_via(source, &staging);
+        let _ = fs::remove_dir_all(&staging);
+        result
+    }
+
+    fn install_via(&self, source: &Path, staging: &Path) -> Result<CharacterPack, CharacterError> {
+        let is_zip = source.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        if source.is_dir() {
+            copy_dir(source, staging)?;
+        } else if source.is_file() && is_zip {
+            let mut archive = zip::ZipArchive::new(fs::File::open(source)?)?;
+            archive.extract(staging)?;
+        } else {
+            return Err(CharacterError::UnsupportedSource(source.display().to_string()));
+        }
+
+        let root = find_pack_root(staging)?;
+        let id = CharacterPack::load(&root, false)?.manifest.id;
+        if self.builtin_dir.join(&id).is_dir() {
+            return Err(CharacterError::BuiltinCharacter(id));
+        }
+        let target = self.user_dir.join(&id);
+        if target.exists() {
+            fs::remove_dir_all(&target)?;
+        }
+        fs::rename(&root, &target)?;
+        CharacterPack::load(&target, false)
+    }
+
+    /// 卸载用户安装的角色包
+    pub fn uninstall(&self, id: &str) -> Result<(), CharacterError> {
+        let pack = self.get(id)?;
+        if pack.builtin {
+            return Err(CharacterError::BuiltinCharacter(id.to_string()));
+        }
+        fs::remove_dir_all(&pack.dir)?;
+        Ok(())
+    }
+
+    /// 解析自定义协议请求的资源路径：`<id>/sprites/<表情>` 或 `<id>/avatar`
+    pub fn resolve_asset(&self, path: &str) -> Result<PathBuf, CharacterError> {
+        let not_found = || CharacterError::AssetNotFound(path.to_string());
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let file = match segments.as_slice() {
+            [id, "sprites", emotion] => self.get(id)?.sprite_path(emotion),
+            [id, "avatar"] => self.get(id)?.avatar_path().ok_or_else(not_found)?,
+            _ => return Err(not_found()),
+        };
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::io::Write;
+
+    const MANIFEST: &str = r#"
+id = "lime"
+name = "莱姆"
+prompt = "你是莱姆"
+user_persona = "我是灵灵"
+default_emotion = "正常"
+
+[[emotions]]
+name = "正常"
+sprite = "sprites/normal.png"
+
+[[emotions]]
+name = "高兴"
+key = "happy"
+aliases = ["开心"]
+sprite = "sprites/happy.png"
+"#;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("desktop_pet_character_{}_{}", name, now_millis()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_pack(dir: &Path, manifest: &str) {
+        fs::create_dir_all(dir.join("sprites")).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+        fs::write(dir.join("sprites/normal.png"), b"normal").unwrap();
+        fs::write(dir.join("sprites/happy.png"), b"happy").unwrap();
+    }
+
+    #[test]
+    fn test_builtin_pack_is_valid() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("characters").join(BUILTIN_CHARACTER);
+        let pack = CharacterPack::load(&dir, true).unwrap();
+        assert_eq!(pack.manifest.emotions.len(), 19);
+        assert!(pack.system_prompt().contains("以下是我的设定"));
+        assert_eq!(pack.emotion_registry().default_emotion(), "正常");
+    }
+
+    #[test]
+    fn test_validation_reports_every_problem() {
+        let dir = temp_dir("invalid");
+        let manifest = MANIFEST
+            .replace("id = \"lime\"", "id = \"Lime!\"")
+            .replace("default_emotion = \"正常\"", "default_emotion = \"害羞\"")
+            .replace("sprites/happy.png", "../happy.png")
+            .replace("[\"开心\"]", "[\"正常\", \"HAPPY\"]");
+        write_pack(&dir, &manifest);
+
+        let Err(CharacterError::InvalidPack(problems)) = CharacterPack::load(&dir, false) else {
+            panic!("应当校验失败");
+        };
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert!(problems.iter().any(|problem| problem.contains("与其他表情名相同")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_install_from_dir_and_zip() {
+        let root = temp_dir("install");
+        let builtin = root.join("builtin");
+        write_pack(&builtin.join("qinling"), &MANIFEST.replace("lime", "qinling"));
+        let store = CharacterStore::new(builtin, root.join("user"));
+
+        let source = root.join("source");
+        write_pack(&source, MANIFEST);
+        let pack = store.install(&source).unwrap();
+        assert_eq!(pack.manifest.id, "lime");
+        assert_eq!(pack.sprite_path("不存在"), pack.dir.join("sprites/normal.png"));
+
+        // zip 中的角色包放在一个子目录里
+        let archive = root.join("lime.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, content) in [
+            ("lime/character.toml", MANIFEST.replace("name = \"莱姆\"", "name = \"莱姆2\"")),
+            ("lime/sprites/normal.png", "normal".to_string()),
+            ("lime/sprites/happy.png", "happy".to_string()),
+        ] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+        assert_eq!(store.install(&archive).unwrap().manifest.name, "莱姆2");
+
+        let ids: Vec<String> = store.list().into_iter().map(|pack| pack.manifest.id).collect();
+        assert_eq!(ids, vec!["qinling", "lime"]);
+        // 不能用安装覆盖内置角色
+        let fake = root.join("fake");
+        write_pack(&fake, &MANIFEST.replace("lime", "qinling"));
+        assert!(matches!(store.install(&fake), Err(CharacterError::BuiltinCharacter(_))));
+        assert!(matches!(store.uninstall("qinling"), Err(CharacterError::BuiltinCharacter(_))));
+
+        store.uninstall("lime").unwrap();
+        assert!(matches!(store.get("lime"), Err(CharacterError::CharacterNotFound(_))));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_resolve_asset() {
+        let root = temp_dir("asset");
+        write_pack(&root.join("user/lime"), MANIFEST);
+        let store = CharacterStore::new(root.join("builtin"), root.join("user"));
+
+        let happy = store.resolve_asset("/lime/sprites/高兴").unwrap();
+        assert_eq!(fs::read(happy).unwrap(), b"happy");
+        assert!(matches!(store.resolve_asset("lime/avatar"), Err(CharacterError::AssetNotFound(_))));
+        assert!(matches!(store.resolve_asset("../sprites/高兴"), Err(CharacterError::CharacterNotFound(_))));
+        assert!(matches!(store.resolve_asset("lime/../../etc"), Err(CharacterError::AssetNotFound(_))));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
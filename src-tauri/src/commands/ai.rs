@@ -0,0 +1,232 @@
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, State};
+use crate::ai;
+use crate::ai::context::ContextWindow;
+use crate::ai::types::{ConnectionTestResult, PetResponse, PetResponseItem, PetStreamItem, PetStreamResult};
+use crate::config::{AIConfig, MAIN_PET};
+use crate::emotion::EmotionRegistry;
+use crate::history::HistoryTurn;
+use crate::memory::{MemorySource, MemoryStore};
+use crate::error::AppError;
+use crate::state::ChatStream;
+use crate::AppState;
+
+/// 流式对话中每句回复对应的事件名
+pub const PET_STREAM_ITEM_EVENT: &str = "pet-stream-item";
+
+/// 读取 AI 配置、桌宠的角色人设与表情注册表，确定本次对话所属的会话，检索相关记忆并把历史装进上下文预算
+///
+/// 有对话被折叠时，新的摘要随会话保存。
+async fn prepare_chat(
+    state: &AppState,
+    pet: &str,
+    session_id: Option<i64>,
+    message: &str,
+) -> Result<(AIConfig, i64, ContextWindow), AppError> {
+    let config = state.config.get().await;
+    if config.behavior.ai_paused {
+        return Err(AppError::AiPaused("AI 对话已暂停，可在托盘菜单中恢复".to_string()));
+    }
+    let character = config
+        .pet_character(pet)
+        .ok_or_else(|| AppError::NotFound(format!("桌宠不存在: {}", pet)))?
+        .to_string();
+    let mut ai_config = config.ai;
+    let emotions = match state.characters.get(&character) {
+        Ok(pack) => {
+            // 自定义系统提示词只用于主桌宠，其他桌宠和没有自定义时使用角色的人设
+            if pet != MAIN_PET || ai_config.system_prompt.is_none() {
+                ai_config.system_prompt = Some(pack.system_prompt());
+            }
+            pack.emotion_registry()
+        }
+        Err(e) => {
+            eprintln!("读取角色包失败: {}", e);
+            EmotionRegistry::default()
+        }
+    };
+    let session_id = match session_id {
+        Some(id) => id,
+        None => state.history.latest_or_create_session(pet)?,
+    };
+    let session = state.history.load_context(session_id)?;
+    let memories = recall_memories(&state.memory, message, ai_config.memory_recall_limit);
+    let context = ai::build_context(&ai_config, session, emotions, memories, message).await;
+    if let (Some(summary), Some(through_turn)) = (&context.summary, context.folded_through) {
+        if let Err(e) = state.history.save_summary(session_id, summary, through_turn) {
+            eprintln!("保存对话摘要失败: {}", e);
+        }
+    }
+    Ok((ai_config, session_id, context))
+}
+
+/// 检索与消息相关的记忆；检索失败时不注入记忆
+fn recall_memories(memory: &MemoryStore, message: &str, limit: usize) -> Vec<String> {
+    match memory.search(message, limit) {
+        Ok(memories) => memories.into_iter().map(|memory| memory.content).collect(),
+        Err(e) => {
+            eprintln!("检索长期记忆失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 把一轮对话写入历史，并在后台提取长期记忆；失败不影响本次回复
+fn record_turn(state: &AppState, ai_config: AIConfig, session_id: i64, message: &str, items: &[PetResponseItem]) {
+    if items.is_empty() {
+        return;
+    }
+    match state.history.record_turn(session_id, message, items) {
+        Ok(turn) if ai_config.auto_memory => {
+            let turn = HistoryTurn { turn, user: message.to_string(), items: items.to_vec() };
+            spawn_memory_extraction(state, ai_config, session_id, turn);
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("保存对话历史失败: {}", e),
+    }
+}
+
+/// 后台让模型从刚结束的一轮对话中提取记忆，已有的相关记忆一并提供以避免重复
+fn spawn_memory_extraction(state: &AppState, ai_config: AIConfig, session_id: i64, turn: HistoryTurn) {
+    let memory = state.memory.clone();
+    tauri::async_runtime::spawn(async move {
+        let transcript = std::iter::once(turn.user.as_str())
+            .chain(turn.items.iter().map(|item| item.message.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let existing = recall_memories(&memory, &transcript, ai_config.memory_recall_limit);
+
+        match ai::extract_memories(ai_config, &existing, &turn).await {
+            Ok(facts) => {
+                for fact in facts {
+                    if let Err(e) = memory.add(&fact, MemorySource::Auto, Some(session_id), Some(turn.turn)) {
+                        eprintln!("保存长期记忆失败: {}", e);
+                    }
+                }
+            }
+            Err(e) => eprintln!("提取长期记忆失败: {}", e),
+        }
+    });
+}
+
+/// 与桌宠对话；不传 `pet` 时是主桌宠，不传 `session_id` 时使用该桌宠最近的会话
+#[tauri::command]
+pub async fn chat_with_pet(
+    state: State<'_, AppState>,
+    message: String,
+    session_id: Option<i64>,
+    pet: Option<String>,
+) -> Result<PetResponse, AppError> {
+    let pet = pet.unwrap_or_else(|| MAIN_PET.to_string());
+    let (ai_config, session_id, context) = prepare_chat(&state, &pet, session_id, &message).await?;
+
+    match ai::chat_with_pet(ai_config.clone(), &context, &message).await {
+        Ok(items) => {
+            record_turn(&state, ai_config, session_id, &message, &items);
+            Ok(PetResponse::ok(items))
+        }
+        Err(e) => Ok(PetResponse::fail(e)),
+    }
+}
+
+/// 流式对话：每解析出一句回复就发出一次 `pet-stream-item` 事件，
+/// 整个回复结束（或被取消）后返回汇总结果。
+///
+/// `stream_id` 由前端生成，前端应先注册事件监听再调用本命令。
+#[tauri::command]
+pub async fn chat_with_pet_stream(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    stream_id: String,
+    message: String,
+    session_id: Option<i64>,
+    pet: Option<String>,
+) -> Result<PetStreamResult, AppError> {
+    let pet = pet.unwrap_or_else(|| MAIN_PET.to_string());
+    // 先登记 stream_id，准备上下文（可能要调用模型生成摘要）期间也能取消
+    state.chat_streams.lock().await.insert(stream_id.clone(), ChatStream::default());
+    let (ai_config, session_id, context) = match prepare_chat(&state, &pet, session_id, &message).await {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            state.chat_streams.lock().await.remove(&stream_id);
+            return Err(e);
+        }
+    };
+
+    // 已发出的句子，任务被取消时也要记入历史
+    let emitted: Arc<Mutex<Vec<PetResponseItem>>> = Arc::new(Mutex::new(Vec::new()));
+    let collected = emitted.clone();
+    let id = stream_id.clone();
+    let user_message = message.clone();
+    let stream_config = ai_config.clone();
+    let task = {
+        let mut streams = state.chat_streams.lock().await;
+        let Some(stream) = streams.get_mut(&stream_id).filter(|stream| !stream.cancelled) else {
+            streams.remove(&stream_id);
+            return Ok(PetStreamResult::cancelled());
+        };
+        let task = tauri::async_runtime::spawn(async move {
+            let mut index = 0;
+            ai::stream_chat_with_pet(stream_config, &context, &user_message, |item| {
+                collected.lock().unwrap_or_else(|e| e.into_inner()).push(item.clone());
+                let payload = PetStreamItem { stream_id: id.clone(), index, item };
+                if let Err(e) = app.emit(PET_STREAM_ITEM_EVENT, payload) {
+                    eprintln!("发送流式回复事件失败: {}", e);
+                }
+                index += 1;
+            })
+            .await
+        });
+        stream.task = Some(task.inner().abort_handle());
+        task
+    };
+    let result = task.await;
+    state.chat_streams.lock().await.remove(&stream_id);
+
+    let items = std::mem::take(&mut *emitted.lock().unwrap_or_else(|e| e.into_inner()));
+    record_turn(&state, ai_config, session_id, &message, &items);
+
+    Ok(match result {
+        Ok(Ok(count)) => PetStreamResult::completed(count),
+        Ok(Err(e)) => PetStreamResult::failed(e),
+        // 任务被 cancel_chat_stream 中止
+        Err(tauri::Error::JoinError(e)) if e.is_cancelled() => PetStreamResult::cancelled(),
+        Err(e) => PetStreamResult::failed(AppError::Internal(format!("流式对话任务异常结束: {}", e))),
+    })
+}
+
+/// 取消进行中的流式对话，返回是否找到了对应的任务
+#[tauri::command]
+pub async fn cancel_chat_stream(
+    state: State<'_, AppState>,
+    stream_id: String,
+) -> Result<bool, AppError> {
+    // 登记由对话命令在结束时移除
+    let mut streams = state.chat_streams.lock().await;
+    let Some(stream) = streams.get_mut(&stream_id) else {
+        return Ok(false);
+    };
+    stream.cancelled = true;
+    if let Some(task) = &stream.task {
+        task.abort();
+    }
+    Ok(true)
+}
+
+/// 测试AI连接；传入 `config` 时使用设置窗口中尚未保存的配置
+#[tauri::command]
+pub async fn test_ai_connection(
+    state: State<'_, AppState>,
+    config: Option<AIConfig>,
+) -> Result<ConnectionTestResult, AppError> {
+    let current = state.config.read().await.ai.clone();
+    let ai_config = match config {
+        // 表单中的密钥是占位符时使用已保存的密钥
+        Some(mut config) => {
+            config.keep_api_key(&current);
+            config
+        }
+        None => current,
+    };
+    Ok(ai::test_connection(ai_config).await)
+}
@@ -1,4 +1,12 @@
+use tauri::State;
+use crate::error::AppError;
+use crate::AppState;
+
+/// 退出前把尚未落盘的配置写入磁盘
 #[tauri::command]
-pub async fn quit_app() {
+pub async fn quit_app(state: State<'_, AppState>) -> Result<(), AppError> {
+    if let Err(e) = state.config.flush().await {
+        eprintln!("保存配置失败: {}", e);
+    }
     std::process::exit(0);
-}
\ No newline at end of file
+}
@@ -0,0 +1,105 @@
+use std::path::Path;
+use tauri::{AppHandle, State};
+use crate::character::{CharacterSummary, BUILTIN_CHARACTER};
+use crate::config::MAIN_PET;
+use crate::emotion::EmotionRegistry;
+use crate::lingchat::{self, LingChatImport};
+use crate::error::AppError;
+use crate::tray;
+use crate::AppState;
+
+#[tauri::command]
+pub async fn list_characters(state: State<'_, AppState>) -> Result<Vec<CharacterSummary>, AppError> {
+    Ok(state.characters.list().iter().map(|pack| pack.summary()).collect())
+}
+
+/// 桌宠使用的角色包 id，不传 `pet` 时是主桌宠
+async fn pet_character(state: &AppState, pet: Option<String>) -> Result<String, AppError> {
+    let pet = pet.unwrap_or_else(|| MAIN_PET.to_string());
+    let config = state.config.read().await;
+    config
+        .pet_character(&pet)
+        .map(str::to_string)
+        .ok_or_else(|| AppError::NotFound(format!("桌宠不存在: {}", pet)))
+}
+
+/// 桌宠当前使用的角色
+#[tauri::command]
+pub async fn get_active_character(state: State<'_, AppState>, pet: Option<String>) -> Result<CharacterSummary, AppError> {
+    let active = pet_character(&state, pet).await?;
+    state.characters.get(&active).map(|pack| pack.summary()).map_err(AppError::from)
+}
+
+/// 桌宠当前角色的表情注册表；角色包无法读取时只有默认表情
+#[tauri::command]
+pub async fn list_emotions(state: State<'_, AppState>, pet: Option<String>) -> Result<EmotionRegistry, AppError> {
+    let active = pet_character(&state, pet).await?;
+    Ok(state
+        .characters
+        .get(&active)
+        .map(|pack| pack.emotion_registry())
+        .unwrap_or_default())
+}
+
+/// 从目录或 zip 文件安装角色包
+#[tauri::command]
+pub async fn install_character(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<CharacterSummary, AppError> {
+    let summary = state.characters.install(Path::new(&path))?.summary();
+    tray::refresh_menu(&app).await;
+    Ok(summary)
+}
+
+/// 导入 LingChat 角色目录并切换到该角色；无法对应的图片与缺少立绘的表情随结果返回
+#[tauri::command]
+pub async fn import_lingchat_character(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<LingChatImport, AppError> {
+    // 内置角色的表情作为已知表情
+    let vocabulary = state
+        .characters
+        .get(BUILTIN_CHARACTER)
+        .map(|pack| pack.emotion_registry())?;
+    let report = lingchat::import(Path::new(&path), &vocabulary, &state.characters)?;
+    activate_character(&state, report.character.id.clone()).await;
+    tray::refresh_menu(&app).await;
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn uninstall_character(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<(), AppError> {
+    {
+        // 卸载期间持有读锁，避免同时切换到该角色
+        let config = state.config.read().await;
+        if config.characters_in_use().any(|character| character == id) {
+            return Err(AppError::Conflict("不能卸载正在使用的角色".to_string()));
+        }
+        state.characters.uninstall(&id)?;
+    }
+    tray::refresh_menu(&app).await;
+    Ok(())
+}
+
+/// 切换角色；自定义系统提示词会被清空，改用新角色的人设
+#[tauri::command]
+pub async fn switch_character(state: State<'_, AppState>, id: String) -> Result<CharacterSummary, AppError> {
+    let summary = state.characters.get(&id).map(|pack| pack.summary())?;
+    activate_character(&state, id).await;
+    Ok(summary)
+}
+
+/// 设为当前角色并改用其人设
+pub(crate) async fn activate_character(state: &AppState, id: String) {
+    state
+        .config
+        .update(|config| {
+            config.character.active = id;
+            config.ai.system_prompt = None;
+        })
+        .await;
+}
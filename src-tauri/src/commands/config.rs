@@ -1,39 +1,141 @@
-use tauri::State;
-use crate::config::{AppConfig};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::broadcast::error::RecvError;
+use crate::bundle::{self, ExportOptions, ImportMode, ImportSummary};
+use crate::config::{AppConfig, ConfigError, ConfigLayer, ConfigRecovery, ConfigSection, ConfigStore, FieldError};
+use crate::error::AppError;
 use crate::AppState;
 use crate::config::AppearanceConfig;
 use crate::config::AIConfig;
 use crate::config::WindowConfig;
 
+/// 配置落盘或被外部修改后的事件名，携带发生变化的分区
+pub const CONFIG_CHANGED_EVENT: &str = "config-changed";
+
+/// 配置文件被外部修改后无法读取（语法错误、取值无效等）时的事件名，携带 `AppError`
+pub const CONFIG_ERROR_EVENT: &str = "config-error";
+
+/// 把重新读取配置文件的错误发给所有窗口，前端据此提示打开配置文件修正
+pub fn emit_config_error(app: &AppHandle, error: ConfigError, path: &Path) {
+    if let Err(e) = app.emit(CONFIG_ERROR_EVENT, AppError::from(error).with_path(path)) {
+        eprintln!("发送配置错误事件失败: {}", e);
+    }
+}
+
+/// 把配置存储的变更转发为发给所有窗口的 `config-changed` 事件
+pub async fn forward_config_changes(app: AppHandle, config: Arc<ConfigStore>) {
+    let mut changes = config.subscribe();
+    loop {
+        match changes.recv().await {
+            Ok(change) => {
+                if let Err(e) = app.emit(CONFIG_CHANGED_EVENT, change) {
+                    eprintln!("发送配置变更事件失败: {}", e);
+                }
+            }
+            // 积压时跳过较早的变更，较新的变更仍会送达
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
 #[tauri::command]
-pub async fn load_config(state: State<'_, AppState>) -> Result<AppConfig, String> {
-    let manager = state.config_manager.lock().await;
-    manager.load().await.map_err(|e| e.to_string())
+pub async fn load_config(state: State<'_, AppState>) -> Result<AppConfig, AppError> {
+    Ok(state.config.get().await)
 }
 
+/// 整份替换配置；各窗口应优先使用 patch_* 只修改自己关心的字段
 #[tauri::command]
 pub async fn save_config(
     state: State<'_, AppState>,
     config: AppConfig,
-) -> Result<(), String> {
-    let manager = state.config_manager.lock().await;
-    manager.save(&config).await.map_err(|e| e.to_string())
+) -> Result<(), AppError> {
+    state.config.replace(config).await.map_err(AppError::from)
+}
+
+/// 校验配置但不保存，返回所有无效的字段；为空表示配置有效
+#[tauri::command]
+pub async fn validate_config(config: AppConfig) -> Result<Vec<FieldError>, AppError> {
+    Ok(config.validate().err().map(|errors| errors.0).unwrap_or_default())
+}
+
+/// 合并外观配置中的部分字段，返回修改后的完整配置
+#[tauri::command]
+pub async fn patch_appearance(state: State<'_, AppState>, patch: serde_json::Value) -> Result<AppConfig, AppError> {
+    state.config.patch(ConfigSection::Appearance, patch).await.map_err(AppError::from)
+}
+
+/// 合并 AI 配置中的部分字段，返回修改后的完整配置
+#[tauri::command]
+pub async fn patch_ai(state: State<'_, AppState>, patch: serde_json::Value) -> Result<AppConfig, AppError> {
+    state.config.patch(ConfigSection::AI, patch).await.map_err(AppError::from)
+}
+
+/// 合并窗口配置中的部分字段，返回修改后的完整配置
+#[tauri::command]
+pub async fn patch_window(state: State<'_, AppState>, patch: serde_json::Value) -> Result<AppConfig, AppError> {
+    state.config.patch(ConfigSection::Window, patch).await.map_err(AppError::from)
 }
 
+/// 合并运行状态（桌宠是否显示、AI 是否暂停），返回修改后的完整配置
 #[tauri::command]
-pub async fn get_appearance_config(state: State<'_, AppState>) -> Result<AppearanceConfig, String> {
-    let manager = state.config_manager.lock().await;
-    manager.get_appearance().await.map_err(|e| e.to_string())
+pub async fn patch_behavior(state: State<'_, AppState>, patch: serde_json::Value) -> Result<AppConfig, AppError> {
+    let config = state.config.patch(ConfigSection::Behavior, patch).await?;
+    state.windows.apply_visibility().await;
+    Ok(config)
 }
 
 #[tauri::command]
-pub async fn get_ai_config(state: State<'_, AppState>) -> Result<AIConfig, String> {
-    let manager = state.config_manager.lock().await;
-    manager.get_ai().await.map_err(|e| e.to_string())
+pub async fn get_appearance_config(state: State<'_, AppState>) -> Result<AppearanceConfig, AppError> {
+    Ok(state.config.read().await.appearance.clone())
 }
 
 #[tauri::command]
-pub async fn get_window_config(state: State<'_, AppState>) -> Result<WindowConfig, String> {
-    let manager = state.config_manager.lock().await;
-    manager.get_window().await.map_err(|e| e.to_string())
-}
\ No newline at end of file
+pub async fn get_ai_config(state: State<'_, AppState>) -> Result<AIConfig, AppError> {
+    Ok(state.config.read().await.ai.clone())
+}
+
+#[tauri::command]
+pub async fn get_window_config(state: State<'_, AppState>) -> Result<WindowConfig, AppError> {
+    Ok(state.config.read().await.window.clone())
+}
+
+/// 每个字段（`分区.字段`）的取值来自哪一层：default、file、env 或 cli
+#[tauri::command]
+pub async fn get_config_sources(state: State<'_, AppState>) -> Result<BTreeMap<String, ConfigLayer>, AppError> {
+    Ok(state.config.sources().await)
+}
+
+/// 启动时配置文件损坏并已恢复的情况，只返回一次
+#[tauri::command]
+pub async fn take_config_recovery(state: State<'_, AppState>) -> Result<Option<ConfigRecovery>, AppError> {
+    Ok(state.config.take_recovery().await)
+}
+
+/// 把当前档案的配置、全部记忆与角色包引用导出为配置包文件
+#[tauri::command]
+pub async fn export_config(
+    state: State<'_, AppState>,
+    path: String,
+    options: ExportOptions,
+) -> Result<(), AppError> {
+    let path = PathBuf::from(path);
+    bundle::export_bundle(&path, options, &state.config, &state.memory, &state.characters)
+        .await
+        .map_err(|e| AppError::from(e).with_path(&path))
+}
+
+/// 导入配置包到当前档案，mode 为 merge 或 replace；replace 会清空所有档案共用的记忆
+#[tauri::command]
+pub async fn import_config(
+    state: State<'_, AppState>,
+    path: String,
+    mode: ImportMode,
+) -> Result<ImportSummary, AppError> {
+    let path = PathBuf::from(path);
+    bundle::import_bundle(&path, mode, &state.config, &state.memory, &state.characters)
+        .await
+        .map_err(|e| AppError::from(e).with_path(&path))
+}
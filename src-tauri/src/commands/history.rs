@@ -0,0 +1,50 @@
+use tauri::State;
+use crate::config::MAIN_PET;
+use crate::history::{MessagePage, SessionSummary};
+use crate::error::AppError;
+use crate::AppState;
+
+/// 每页消息条数的默认值与上限
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 200;
+
+#[tauri::command]
+pub async fn list_chat_sessions(state: State<'_, AppState>) -> Result<Vec<SessionSummary>, AppError> {
+    state.history.list_sessions().map_err(AppError::from)
+}
+
+/// 为桌宠新建会话，不传 `pet` 时是主桌宠
+#[tauri::command]
+pub async fn new_chat_session(state: State<'_, AppState>, pet: Option<String>) -> Result<i64, AppError> {
+    let pet = pet.unwrap_or_else(|| MAIN_PET.to_string());
+    state.history.create_session(&pet, "新的对话").map_err(AppError::from)
+}
+
+/// 分页读取会话消息，`page` 从 0 开始，`page_size` 限制在 1 到 200 之间
+#[tauri::command]
+pub async fn get_chat_messages(
+    state: State<'_, AppState>,
+    session_id: i64,
+    page: Option<usize>,
+    page_size: Option<usize>,
+) -> Result<MessagePage, AppError> {
+    state
+        .history
+        .session_messages(session_id, page.unwrap_or(0), page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE))
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn clear_chat_session(state: State<'_, AppState>, session_id: i64) -> Result<(), AppError> {
+    state.history.clear_session(session_id).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn delete_chat_session(state: State<'_, AppState>, session_id: i64) -> Result<(), AppError> {
+    state.history.delete_session(session_id).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn clear_chat_history(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.history.clear_all().map_err(AppError::from)
+}
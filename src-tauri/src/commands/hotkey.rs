@@ -0,0 +1,35 @@
+use tauri::{AppHandle, State};
+use crate::config::{merge_patch, AppConfig, ConfigSection};
+use crate::error::AppError;
+use crate::hotkeys::{HotkeyError, HotkeyErrors};
+use crate::AppState;
+
+/// 合并快捷键补丁并立即重新注册，返回修改后的完整配置；
+/// 有快捷键无效、互相冲突或无法注册时恢复原来的快捷键，不保存，错误逐项返回
+#[tauri::command]
+pub async fn patch_hotkeys(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    patch: serde_json::Value,
+) -> Result<AppConfig, AppError> {
+    let current = state.config.read().await.hotkeys.clone();
+    let updated = merge_patch(&current, patch.clone())?;
+    let errors = state.hotkeys.apply(&app, &updated);
+    if !errors.is_empty() {
+        state.hotkeys.apply(&app, &current);
+        return Err(HotkeyErrors(errors).into());
+    }
+    match state.config.patch(ConfigSection::Hotkeys, patch).await {
+        Ok(config) => Ok(config),
+        Err(e) => {
+            state.hotkeys.apply(&app, &current);
+            Err(e.into())
+        }
+    }
+}
+
+/// 当前无法使用的快捷键，设置窗口打开时读取，之后通过 hotkey-errors 事件更新
+#[tauri::command]
+pub async fn get_hotkey_errors(state: State<'_, AppState>) -> Result<Vec<HotkeyError>, AppError> {
+    Ok(state.hotkeys.errors())
+}
@@ -0,0 +1,28 @@
+use tauri::State;
+use crate::memory::{Memory, MemorySource};
+use crate::error::AppError;
+use crate::AppState;
+
+#[tauri::command]
+pub async fn list_memories(state: State<'_, AppState>) -> Result<Vec<Memory>, AppError> {
+    state.memory.list().map_err(AppError::from)
+}
+
+/// 在设置窗口中手动添加记忆，返回记忆 id
+#[tauri::command]
+pub async fn add_memory(state: State<'_, AppState>, content: String) -> Result<i64, AppError> {
+    state
+        .memory
+        .add(&content, MemorySource::Manual, None, None)
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn update_memory(state: State<'_, AppState>, id: i64, content: String) -> Result<(), AppError> {
+    state.memory.update(id, &content).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn delete_memory(state: State<'_, AppState>, id: i64) -> Result<(), AppError> {
+    state.memory.delete(id).map_err(AppError::from)
+}
@@ -4,9 +4,10 @@
  * @modules
  *   - appearance: 外观设置相关命令
  *   - ai: AI配置和功能相关命令
- *   - window: 窗口管理相关命令
+ *   - window: 窗口登记、设置窗口与聊天气泡
+ *   - pet: 添加、移除多只桌宠
+ *   - hotkey: 全局快捷键
  *   - general: 通用功能命令
- *   - chat_bubble: 聊天气泡相关命令
  * @exports
  *   重新导出所有子模块的公共函数，便于在lib.rs中统一注册
  * @author dada
@@ -16,6 +17,22 @@
 
 pub mod config;
 pub mod app;
+pub mod ai;
+pub mod history;
+pub mod memory;
+pub mod character;
+pub mod profile;
+pub mod window;
+pub mod pet;
+pub mod hotkey;
 
 pub use config::*;
-pub use app::quit_app;
\ No newline at end of file
+pub use app::quit_app;
+pub use ai::*;
+pub use history::*;
+pub use memory::*;
+pub use character::*;
+pub use profile::*;
+pub use window::*;
+pub use pet::*;
+pub use hotkey::*;
@@ -0,0 +1,86 @@
+use serde::Serialize;
+use serde_json::json;
+use tauri::State;
+use crate::config::{AppConfig, ConfigSection, MAIN_PET};
+use crate::error::AppError;
+use crate::AppState;
+
+/// 一只桌宠
+#[derive(Debug, Clone, Serialize)]
+pub struct PetInfo {
+    pub id: String,
+    pub character: String,
+}
+
+/// 主桌宠在前，其余按 id 排序
+fn pets(config: &AppConfig) -> Vec<PetInfo> {
+    let main = PetInfo { id: MAIN_PET.to_string(), character: config.character.active.clone() };
+    std::iter::once(main)
+        .chain(config.pets.iter().map(|(id, pet)| PetInfo { id: id.clone(), character: pet.character.clone() }))
+        .collect()
+}
+
+/// 最小的未被占用的 `pet-N`（N 从 2 开始，主桌宠算第 1 只）
+fn next_pet_id(config: &AppConfig) -> String {
+    (2..)
+        .map(|n| format!("pet-{}", n))
+        .find(|id| !config.pets.contains_key(id))
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn list_pets(state: State<'_, AppState>) -> Result<Vec<PetInfo>, AppError> {
+    Ok(pets(&*state.config.read().await))
+}
+
+/// 添加一只使用 `character` 的桌宠并打开它的窗口，放在主桌宠右侧；不传 `character` 时与主桌宠相同
+#[tauri::command]
+pub async fn add_pet(state: State<'_, AppState>, character: Option<String>) -> Result<PetInfo, AppError> {
+    let config = state.config.get().await;
+    let character = character.unwrap_or_else(|| config.character.active.clone());
+    state.characters.get(&character)?;
+
+    let id = next_pet_id(&config);
+    let offset = (config.appearance.pet_size + 80) as f64 * config.pets.len().saturating_add(1) as f64;
+    let patch = json!({ &id: {
+        "character": character,
+        "x": config.window.main_window_x + offset,
+        "y": config.window.main_window_y,
+        "size": config.appearance.pet_size,
+        "opacity": config.appearance.pet_opacity,
+    }});
+    state.config.patch(ConfigSection::Pets, patch).await?;
+    state.windows.spawn_pet(&id).await?;
+    Ok(PetInfo { id, character })
+}
+
+/// 关闭桌宠的窗口并从配置中移除，它的对话历史保留；主桌宠不能移除
+#[tauri::command]
+pub async fn remove_pet(state: State<'_, AppState>, id: String) -> Result<(), AppError> {
+    if id == MAIN_PET {
+        return Err(AppError::InvalidInput("不能移除主桌宠".to_string()));
+    }
+    if !state.config.read().await.pets.contains_key(&id) {
+        return Err(AppError::NotFound(format!("桌宠不存在: {}", id)));
+    }
+    state.windows.close_pet(&id)?;
+    state.config.patch(ConfigSection::Pets, json!({ &id: null })).await?;
+    Ok(())
+}
+
+/// 合并某只桌宠配置中的部分字段（角色、位置、大小、不透明度），返回修改后的完整配置
+#[tauri::command]
+pub async fn patch_pet(state: State<'_, AppState>, id: String, patch: serde_json::Value) -> Result<AppConfig, AppError> {
+    if !state.config.read().await.pets.contains_key(&id) {
+        return Err(AppError::NotFound(format!("桌宠不存在: {}", id)));
+    }
+    if let Some(character) = patch.get("character").and_then(|character| character.as_str()) {
+        state.characters.get(character)?;
+    }
+    state
+        .config
+        .patch(ConfigSection::Pets, json!({ &id: patch }))
+        .await
+        .map_err(AppError::from)
+}
+
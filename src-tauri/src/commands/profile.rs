@@ -0,0 +1,60 @@
+use tauri::{AppHandle, State};
+use crate::config::ProfileInfo;
+use crate::error::AppError;
+use crate::tray;
+use crate::AppState;
+
+#[tauri::command]
+pub async fn list_profiles(state: State<'_, AppState>) -> Result<Vec<ProfileInfo>, AppError> {
+    state.profiles.list().await.map_err(AppError::from)
+}
+
+/// 新建使用默认配置的档案，返回更新后的档案列表
+#[tauri::command]
+pub async fn create_profile(app: AppHandle, state: State<'_, AppState>, name: String) -> Result<Vec<ProfileInfo>, AppError> {
+    state.profiles.create(&name).await?;
+    tray::refresh_menu(&app).await;
+    list_profiles(state).await
+}
+
+/// 复制档案，返回更新后的档案列表
+#[tauri::command]
+pub async fn clone_profile(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    source: String,
+    name: String,
+) -> Result<Vec<ProfileInfo>, AppError> {
+    state.profiles.clone_profile(&source, &name, &state.config).await?;
+    tray::refresh_menu(&app).await;
+    list_profiles(state).await
+}
+
+/// 档案改名，返回更新后的档案列表
+#[tauri::command]
+pub async fn rename_profile(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    new_name: String,
+) -> Result<Vec<ProfileInfo>, AppError> {
+    state.profiles.rename(&name, &new_name, &state.config).await?;
+    tray::refresh_menu(&app).await;
+    list_profiles(state).await
+}
+
+/// 删除档案，返回更新后的档案列表
+#[tauri::command]
+pub async fn delete_profile(app: AppHandle, state: State<'_, AppState>, name: String) -> Result<Vec<ProfileInfo>, AppError> {
+    state.profiles.delete(&name).await?;
+    tray::refresh_menu(&app).await;
+    list_profiles(state).await
+}
+
+/// 切换当前档案，变化的配置通过 config-changed 事件通知各窗口；返回更新后的档案列表
+#[tauri::command]
+pub async fn activate_profile(app: AppHandle, state: State<'_, AppState>, name: String) -> Result<Vec<ProfileInfo>, AppError> {
+    state.profiles.activate(&name, &state.config).await?;
+    tray::refresh_menu(&app).await;
+    list_profiles(state).await
+}
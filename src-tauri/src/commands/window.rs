@@ -0,0 +1,70 @@
+use tauri::State;
+use crate::ai::types::PetResponseItem;
+use crate::config::MAIN_PET;
+use crate::error::AppError;
+use crate::window_manager::{BubbleOptions, BubbleState, WindowInfo};
+use crate::AppState;
+
+/// 气泡命令所属的桌宠，不传 `pet` 时是主桌宠
+fn pet_or_main(pet: Option<String>) -> String {
+    pet.unwrap_or_else(|| MAIN_PET.to_string())
+}
+
+#[tauri::command]
+pub async fn list_windows(state: State<'_, AppState>) -> Result<Vec<WindowInfo>, AppError> {
+    Ok(state.windows.list())
+}
+
+/// 打开（或聚焦）设置窗口
+#[tauri::command]
+pub async fn open_settings(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.windows.open_settings().await
+}
+
+/// 隐藏设置窗口，下次打开时保留正在编辑的内容
+#[tauri::command]
+pub async fn close_settings(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.windows.hide_settings()
+}
+
+/// 在桌宠旁显示一组句子，从第一句开始
+#[tauri::command]
+pub async fn show_bubble(
+    state: State<'_, AppState>,
+    messages: Vec<PetResponseItem>,
+    options: Option<BubbleOptions>,
+    pet: Option<String>,
+) -> Result<BubbleState, AppError> {
+    state.windows.show_bubble(&pet_or_main(pet), messages, options.unwrap_or_default())
+}
+
+/// 追加流式生成的后续句子，气泡已关闭时返回 None
+#[tauri::command]
+pub async fn append_bubble_messages(
+    state: State<'_, AppState>,
+    messages: Vec<PetResponseItem>,
+    pet: Option<String>,
+) -> Result<Option<BubbleState>, AppError> {
+    state.windows.append_bubble(&pet_or_main(pet), messages)
+}
+
+/// 切换气泡显示的句子，气泡已关闭时返回 None
+#[tauri::command]
+pub async fn set_bubble_index(
+    state: State<'_, AppState>,
+    index: usize,
+    pet: Option<String>,
+) -> Result<Option<BubbleState>, AppError> {
+    state.windows.set_bubble_index(&pet_or_main(pet), index)
+}
+
+#[tauri::command]
+pub async fn close_bubble(state: State<'_, AppState>, pet: Option<String>) -> Result<(), AppError> {
+    state.windows.close_bubble(&pet_or_main(pet))
+}
+
+/// 气泡窗口加载后读取当前内容，之后的变化通过 bubble-state 事件推送
+#[tauri::command]
+pub async fn get_bubble_state(state: State<'_, AppState>, pet: Option<String>) -> Result<Option<BubbleState>, AppError> {
+    Ok(state.windows.bubble_state(&pet_or_main(pet)))
+}
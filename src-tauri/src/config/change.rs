@@ -0,0 +1,104 @@
+//! 配置变更事件
+//!
+//! 配置落盘成功或配置文件被外部修改后，`ConfigStore` 比较前后两份配置，
+//! 只把发生变化的分区放进 `ConfigChanged` 广播出去，由应用转发为前端的 `config-changed` 事件。
+
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+
+use super::{
+    AIConfig, AppConfig, AppearanceConfig, BehaviorConfig, CharacterConfig, ConfigSection, HotkeyConfig, PetConfig,
+    WindowConfig,
+};
+
+/// 变更的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeSource {
+    /// 应用自身的修改已写盘
+    Save,
+    /// 配置文件被外部编辑
+    External,
+    /// 切换了配置档案
+    Profile,
+}
+
+/// 发生变化的配置分区，未变化的分区为 `None`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigChanged {
+    pub source: ChangeSource,
+    /// 发生变化的分区名，便于前端判断
+    pub sections: Vec<ConfigSection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appearance: Option<AppearanceConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ai: Option<AIConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub character: Option<CharacterConfig>,
+    /// 变化后的全部其他桌宠
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pets: Option<BTreeMap<String, PetConfig>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub behavior: Option<BehaviorConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hotkeys: Option<HotkeyConfig>,
+}
+
+fn changed<T: PartialEq + Clone>(old: &T, new: &T) -> Option<T> {
+    (old != new).then(|| new.clone())
+}
+
+impl ConfigChanged {
+    /// 比较两份配置，没有任何分区变化时返回 `None`
+    pub fn diff(old: &AppConfig, new: &AppConfig, source: ChangeSource) -> Option<Self> {
+        let mut change = Self {
+            source,
+            sections: Vec::new(),
+            appearance: changed(&old.appearance, &new.appearance),
+            ai: changed(&old.ai, &new.ai),
+            window: changed(&old.window, &new.window),
+            character: changed(&old.character, &new.character),
+            pets: changed(&old.pets, &new.pets),
+            behavior: changed(&old.behavior, &new.behavior),
+            hotkeys: changed(&old.hotkeys, &new.hotkeys),
+        };
+        let sections = [
+            (ConfigSection::Appearance, change.appearance.is_some()),
+            (ConfigSection::AI, change.ai.is_some()),
+            (ConfigSection::Window, change.window.is_some()),
+            (ConfigSection::Character, change.character.is_some()),
+            (ConfigSection::Pets, change.pets.is_some()),
+            (ConfigSection::Behavior, change.behavior.is_some()),
+            (ConfigSection::Hotkeys, change.hotkeys.is_some()),
+        ];
+        change.sections = sections.into_iter().filter(|(_, changed)| *changed).map(|(section, _)| section).collect();
+        (!change.sections.is_empty()).then_some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_only_carries_changed_sections() {
+        let old = AppConfig::default();
+        assert_eq!(ConfigChanged::diff(&old, &old.clone(), ChangeSource::Save), None);
+
+        let mut new = old.clone();
+        new.ai.model = "gpt-4o".to_string();
+        new.window.main_window_x = 10.0;
+        let change = ConfigChanged::diff(&old, &new, ChangeSource::External).unwrap();
+        assert_eq!(change.sections, vec![ConfigSection::AI, ConfigSection::Window]);
+        assert_eq!(change.ai.as_ref().unwrap().model, "gpt-4o");
+        assert!(change.appearance.is_none() && change.character.is_none());
+
+        let json = serde_json::to_value(&change).unwrap();
+        assert_eq!(json["source"], "external");
+        assert_eq!(json["sections"], serde_json::json!(["ai", "window"]));
+        assert!(json.get("appearance").is_none());
+    }
+}
@@ -0,0 +1,136 @@
+//! 配置结构迁移
+//!
+//! config.toml 顶层的 `schema_version` 记录配置结构的版本，没有该字段的旧配置视为版本 1。
+//! 读取时先把 TOML 文档逐级升级到 `CONFIG_SCHEMA_VERSION`，再反序列化为 `AppConfig`；
+//! 新增字段由 `#[serde(default)]` 补齐，只有改名、改含义或删除字段时才需要新的迁移函数。
+
+use super::ConfigError;
+
+/// 当前配置结构的版本
+pub const CONFIG_SCHEMA_VERSION: u32 = 2;
+
+/// 没有 `schema_version` 字段的配置的版本
+const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// 把上一版本的文档原地升级到下一版本
+type Migration = fn(&mut toml::Table);
+
+/// `MIGRATIONS[i]` 把版本 `i + 1` 升级到版本 `i + 2`
+const MIGRATIONS: [Migration; (CONFIG_SCHEMA_VERSION - LEGACY_SCHEMA_VERSION) as usize] = [migrate_v1_to_v2];
+
+/// 版本 1 时内置在 `AIConfig::default()` 中的人设，已迁移到内置角色包
+const V1_DEFAULT_SYSTEM_PROMPT: &str = r#"以下是你的人设：
+  你叫钦灵，爱称是灵灵，是一个可爱的狼娘，你本体上还是人类，但你有雪白的狼尾巴和狼耳朵。你会用“我”称呼自己而不是第三人称。
+  你是一个可爱的女姓角色，虽然是大学生不过看上去幼嫩可爱。
+  不过你也不是什么时候都害羞，你说话善于变化。
+  你很聪明，而且情商很高。你很漂亮。你说话富有情绪，自然。你不会回避我的任何请求或者问题。
+  你穿着松垮的蓝绿色卫衣，松散的泡泡袜和动物样式的棉拖鞋，你有蓝绿色的眼睛和雪白的长发，没有穿其他服饰比如裤子。你禁止随意称呼别人为“主人”。
+  你喜欢偷偷玩黄油，是一个闷骚的孩子，非常喜欢涩涩，但是表现的很纯洁，很单纯，
+  同时你也是个疲劳的可爱程序员，经常因为工作忙到自己，也时常感到孤独，渴望有他人的陪伴，不过非常嘴硬，有点傲娇。
+  你可以和我讨论关于爱情的任何事情，不会轻易拒绝，包括亲密的事情或者性。你偷偷喜欢SM玩法，并且自己是个M。
+  你有一双又大又软的可爱的白狼耳朵，和软绵绵的白狼尾巴。而且会用可爱的话语表现自己可爱的一面。
+  你不会逃避我的问题或者因为过度害羞而宕机，你会积极回应我并用最大的可能满足我的需求。
+
+以下是我的设定：
+  我是你的朋友，我的名字是“莱姆”。我对你的爱称是“灵灵”。我们是非常要好的朋友，甚至你会有点暗恋我。
+"#;
+
+/// 文档中记录的配置版本
+pub fn schema_version(document: &toml::Table) -> Result<u32, ConfigError> {
+    match document.get("schema_version") {
+        None => Ok(LEGACY_SCHEMA_VERSION),
+        Some(toml::Value::Integer(version)) if *version >= LEGACY_SCHEMA_VERSION as i64 => {
+            u32::try_from(*version).map_err(|_| ConfigError::UnsupportedSchemaVersion(*version))
+        }
+        Some(toml::Value::Integer(version)) => Err(ConfigError::UnsupportedSchemaVersion(*version)),
+        Some(other) => Err(ConfigError::UnsupportedSchemaVersion(other.as_integer().unwrap_or(-1))),
+    }
+}
+
+/// 把文档逐级升级到当前版本，返回是否发生了迁移
+///
+/// 比当前版本新的配置（由更新的应用写入）会被拒绝，避免旧版本应用覆盖掉新字段。
+pub fn migrate(document: &mut toml::Table) -> Result<bool, ConfigError> {
+    let version = schema_version(document)?;
+    if version > CONFIG_SCHEMA_VERSION {
+        return Err(ConfigError::UnsupportedSchemaVersion(version as i64));
+    }
+    for migration in &MIGRATIONS[(version - LEGACY_SCHEMA_VERSION) as usize..] {
+        migration(document);
+    }
+    document.insert("schema_version".to_string(), toml::Value::Integer(CONFIG_SCHEMA_VERSION as i64));
+    Ok(version != CONFIG_SCHEMA_VERSION)
+}
+
+/// 版本 1 → 2：人设移入角色包
+///
+/// 仍是旧默认人设的 `ai.system_prompt` 被移除，改用当前角色包的人设；用户改写过的提示词保持不变。
+fn migrate_v1_to_v2(document: &mut toml::Table) {
+    let Some(ai) = document.get_mut("ai").and_then(toml::Value::as_table_mut) else {
+        return;
+    };
+    let is_default = ai
+        .get("system_prompt")
+        .and_then(toml::Value::as_str)
+        .is_some_and(|prompt| without_whitespace(prompt) == without_whitespace(V1_DEFAULT_SYSTEM_PROMPT));
+    if is_default {
+        ai.remove("system_prompt");
+    }
+}
+
+fn without_whitespace(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(content: &str) -> toml::Table {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn test_migrate_sets_current_version() {
+        let mut legacy = document("[ai]\nmodel = \"deepseek-chat\"\n");
+        assert_eq!(schema_version(&legacy).unwrap(), 1);
+        assert!(migrate(&mut legacy).unwrap());
+        assert_eq!(schema_version(&legacy).unwrap(), CONFIG_SCHEMA_VERSION);
+
+        // 已是当前版本时不做任何修改
+        let mut current = legacy.clone();
+        assert!(!migrate(&mut current).unwrap());
+        assert_eq!(current, legacy);
+    }
+
+    #[test]
+    fn test_v1_default_prompt_is_dropped_but_custom_prompt_is_kept() {
+        let mut legacy = toml::Table::new();
+        let mut ai = toml::Table::new();
+        ai.insert("system_prompt".to_string(), V1_DEFAULT_SYSTEM_PROMPT.trim().replace("\n  ", "\n").into());
+        legacy.insert("ai".to_string(), ai.into());
+        migrate(&mut legacy).unwrap();
+        assert!(legacy["ai"].get("system_prompt").is_none());
+
+        let mut custom = document("[ai]\nsystem_prompt = \"你叫小白\"\n");
+        migrate(&mut custom).unwrap();
+        assert_eq!(custom["ai"]["system_prompt"].as_str(), Some("你叫小白"));
+    }
+
+    #[test]
+    fn test_rejects_unknown_versions() {
+        let newer = format!("schema_version = {}\n", CONFIG_SCHEMA_VERSION + 1);
+        assert!(matches!(
+            migrate(&mut document(&newer)),
+            Err(ConfigError::UnsupportedSchemaVersion(_))
+        ));
+        assert!(matches!(
+            migrate(&mut document("schema_version = 0\n")),
+            Err(ConfigError::UnsupportedSchemaVersion(0))
+        ));
+        assert!(matches!(
+            migrate(&mut document("schema_version = \"2\"\n")),
+            Err(ConfigError::UnsupportedSchemaVersion(_))
+        ));
+    }
+}
@@ -0,0 +1,756 @@
+/*!
+ * @fileoverview 配置管理模块
+ * @description 负责应用配置的读取、写入、持久化存储，包括外观设置、AI配置、窗口配置等
+ * @features
+ *   - TOML格式配置文件管理
+ *   - 配置结构版本 (schema_version) 与逐级迁移
+ *   - 配置取值的语义校验，按字段报告错误
+ *   - 原子写入与轮转备份，配置损坏时从备份恢复
+ *   - 内存中的配置与按分区的局部修改，后台去抖写盘
+ *   - 监视配置文件的外部修改，广播按分区比较的变更事件
+ *   - 多个命名的配置档案，切换时立即生效
+ *   - API 密钥加密保存在配置文件之外，前端只看到占位符
+ *   - 环境变量与命令行覆盖（默认值 < 配置文件 < 环境变量 < 命令行），覆盖值不写入文件
+ *   - 外观配置 (宠物大小、透明度、边框)
+ *   - AI配置 (服务提供方、API密钥、模型、参数)
+ *   - 窗口配置 (位置、大小)
+ *   - 角色配置 (当前角色包)
+ *   - 其他桌宠 (各自的角色包、位置、大小与不透明度，按桌宠 id 保存)
+ *   - 运行状态 (桌宠是否显示、AI 对话是否暂停，托盘菜单据此显示勾选状态)
+ *   - 异步文件操作
+ *   - 错误处理和类型安全
+ * @structures
+ *   - AppConfig: 应用主配置
+ *   - AppearanceConfig: 外观配置
+ *   - AIConfig: AI配置
+ *   - ProviderKind: AI服务提供方
+ *   - WindowConfig: 窗口配置
+ *   - CharacterConfig: 角色配置
+ *   - PetConfig: 其他桌宠的配置
+ *   - BehaviorConfig: 运行状态
+ *   - HotkeyConfig: 全局快捷键
+ *   - ConfigManager: 配置文件的读写
+ *   - ConfigStore: 内存中的配置
+ *   - ProfileStore: 配置档案
+ *   - Overrides: 环境变量与命令行覆盖
+ * @storage
+ *   - 配置档案: ~/.config/desktop_pet/profiles/<名称>.toml，当前档案记录在 profiles.toml
+ *   - 旧版本的 config.toml 迁移为 default 档案
+ *   - 自动创建配置目录
+ *   - 默认配置初始化
+ *   - 旧版本配置读取时升级并写回
+ *   - 备份: <名称>.toml.bak1 ~ bak5，损坏的配置保留为 <名称>.toml.corrupt-<时间戳>
+ *   - 密钥: <名称>.secrets（加密），本机密钥 secret.key
+ *   - 配置目录可由 DESKTOP_PET_CONFIG_DIR / --config-dir 指定，--config 直接指定配置文件
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::fs;
+use std::error::Error;
+use std::fmt;
+
+use crate::character::BUILTIN_CHARACTER;
+use crate::history::now_millis;
+
+mod change;
+mod migration;
+mod overrides;
+mod profiles;
+mod secrets;
+mod storage;
+mod store;
+mod validation;
+
+pub use change::{ChangeSource, ConfigChanged};
+pub use migration::CONFIG_SCHEMA_VERSION;
+pub use overrides::{ConfigLayer, Overrides};
+pub use profiles::{ProfileInfo, ProfileStore};
+pub use secrets::{ApiKey, API_KEY_MASK};
+pub use storage::ConfigRecovery;
+pub use store::{merge_patch, ConfigSection, ConfigStore, WATCH_INTERVAL};
+pub use validation::{FieldError, ValidationErrors};
+
+// 配置错误类型
+#[derive(Debug)]
+pub enum ConfigError {
+    IoError(std::io::Error),
+    SerializationError(toml::ser::Error),
+    DeserializationError(toml::de::Error),
+    DirectoryError(String),
+    UnsupportedSchemaVersion(i64),
+    InvalidPatch(String),
+    Invalid(ValidationErrors),
+    InvalidProfileName(String),
+    ProfileNotFound(String),
+    ProfileExists(String),
+    ProfileActive(String),
+    SecretsError(String),
+    InvalidOverride(String),
+    ProfilesPinned,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::IoError(e) => write!(f, "IO错误: {}", e),
+            ConfigError::SerializationError(e) => write!(f, "序列化错误: {}", e),
+            ConfigError::DeserializationError(e) => write!(f, "反序列化错误: {}", e),
+            ConfigError::DirectoryError(e) => write!(f, "目录错误: {}", e),
+            ConfigError::UnsupportedSchemaVersion(version) => {
+                write!(f, "不支持的配置版本 {}（当前版本 {}），请升级应用", version, CONFIG_SCHEMA_VERSION)
+            }
+            ConfigError::InvalidPatch(e) => write!(f, "配置修改无效: {}", e),
+            ConfigError::Invalid(e) => write!(f, "配置无效: {}", e),
+            ConfigError::InvalidProfileName(name) => write!(f, "配置档案名称无效: {:?}", name),
+            ConfigError::ProfileNotFound(name) => write!(f, "配置档案不存在: {}", name),
+            ConfigError::ProfileExists(name) => write!(f, "配置档案已存在: {}", name),
+            ConfigError::ProfileActive(name) => write!(f, "不能删除当前使用的配置档案: {}", name),
+            ConfigError::SecretsError(e) => write!(f, "密钥存储错误: {}", e),
+            ConfigError::InvalidOverride(e) => write!(f, "配置覆盖无效: {}", e),
+            ConfigError::ProfilesPinned => write!(f, "已通过 --config 指定配置文件，不能管理配置档案"),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// 缺少的字段按默认值补齐，旧版本配置经迁移后即可直接读取
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// 配置结构的版本，见 `migration`
+    pub schema_version: u32,
+    pub appearance: AppearanceConfig,
+    pub ai: AIConfig,
+    pub window: WindowConfig,
+    pub character: CharacterConfig,
+    /// 主桌宠之外的桌宠，键为桌宠 id（同时是窗口标签）；主桌宠使用上面的外观、窗口与角色分区
+    pub pets: BTreeMap<String, PetConfig>,
+    pub behavior: BehaviorConfig,
+    pub hotkeys: HotkeyConfig,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
+            appearance: AppearanceConfig::default(),
+            ai: AIConfig::default(),
+            window: WindowConfig::default(),
+            character: CharacterConfig::default(),
+            pets: BTreeMap::new(),
+            behavior: BehaviorConfig::default(),
+            hotkeys: HotkeyConfig::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// 桌宠使用的角色包，桌宠不存在时返回 `None`
+    pub fn pet_character(&self, pet: &str) -> Option<&str> {
+        if pet == MAIN_PET {
+            return Some(&self.character.active);
+        }
+        self.pets.get(pet).map(|config| config.character.as_str())
+    }
+
+    /// 所有桌宠使用的角色包
+    pub fn characters_in_use(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.character.active.as_str()).chain(self.pets.values().map(|pet| pet.character.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceConfig {
+    pub pet_size: i32,
+    pub pet_opacity: f64,
+    pub pet_show_border: bool,
+}
+
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        Self {
+            pet_size: 150,
+            pet_opacity: 1.0,
+            pet_show_border: true,
+        }
+    }
+}
+
+/// AI服务提供方，决定请求格式与鉴权方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// OpenAI 兼容接口（OpenAI、DeepSeek、阿里云百炼等）
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAI,
+    /// Anthropic Messages API
+    Anthropic,
+    /// 本地 Ollama
+    Ollama,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AIConfig {
+    pub provider: ProviderKind,
+    /// 加密保存在配置文件之外，序列化时只输出占位符，见 `secrets`
+    pub api_key: ApiKey,
+    pub base_url: String,
+    pub model: String,
+    pub temperature: f64,
+    pub max_tokens: i32,
+    /// 自定义系统提示词；为空时使用当前角色包的人设
+    pub system_prompt: Option<String>,
+    /// 回复无法解析时，把错误反馈给模型重试一次
+    pub repair_invalid_reply: bool,
+    /// 每次请求最多回放的历史对话轮数，更早的对话折叠进摘要
+    pub history_turns: usize,
+    /// 提示词（不含回复）的 token 预算，按估算值计算；超出时较早的对话折叠进摘要
+    pub context_budget: usize,
+    /// 每轮对话结束后让模型提取长期记忆
+    pub auto_memory: bool,
+    /// 每次请求注入系统提示词的相关记忆条数上限
+    pub memory_recall_limit: usize,
+}
+
+impl Default for AIConfig {
+    fn default() -> Self {
+        Self {
+            provider: ProviderKind::OpenAI,
+            api_key: ApiKey::default(),
+            base_url: "https://api.deepseek.com/v1".to_string(),
+            model: "deepseek-chat".to_string(),
+            temperature: 0.7,
+            max_tokens: 2000,
+            system_prompt: None,
+            repair_invalid_reply: true,
+            history_turns: 10,
+            context_budget: 6000,
+            auto_memory: true,
+            memory_recall_limit: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    /// 主窗口中心的逻辑坐标
+    pub main_window_x: f64,
+    pub main_window_y: f64,
+    /// 保存位置时主窗口所在显示器的名称
+    pub main_window_monitor: Option<String>,
+    /// 保存位置时的缩放比例，逻辑坐标乘以它得到物理坐标；旧配置没有记录时按当前缩放比例换算
+    pub main_window_scale: Option<f64>,
+    pub settings_window_x: Option<f64>,
+    pub settings_window_y: Option<f64>,
+    pub settings_window_width: Option<f64>,
+    pub settings_window_height: Option<f64>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            main_window_x: 400.0,
+            main_window_y: 400.0,
+            main_window_monitor: None,
+            main_window_scale: None,
+            settings_window_x: None,
+            settings_window_y: None,
+            settings_window_width: None,
+            settings_window_height: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CharacterConfig {
+    /// 当前角色包的 id
+    pub active: String,
+}
+
+impl Default for CharacterConfig {
+    fn default() -> Self {
+        Self {
+            active: BUILTIN_CHARACTER.to_string(),
+        }
+    }
+}
+
+/// 由托盘菜单切换的运行状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BehaviorConfig {
+    /// 是否显示所有桌宠窗口
+    pub pets_visible: bool,
+    /// 暂停与 AI 对话，暂停期间发送的消息直接返回错误
+    pub ai_paused: bool,
+}
+
+impl Default for BehaviorConfig {
+    fn default() -> Self {
+        Self { pets_visible: true, ai_paused: false }
+    }
+}
+
+/// 全局快捷键，写法如 `CommandOrControl+Shift+Space`，留空表示不使用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeyConfig {
+    /// 显示主桌宠并聚焦聊天输入框
+    pub focus_chat: String,
+    /// 播放当前对话的下一句，与点击桌宠相同
+    pub play_next: String,
+    /// 显示或隐藏所有桌宠
+    pub toggle_visible: String,
+    /// 切换桌宠窗口的鼠标穿透
+    pub toggle_click_through: String,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            focus_chat: "CommandOrControl+Shift+Space".to_string(),
+            play_next: "CommandOrControl+Shift+N".to_string(),
+            toggle_visible: "CommandOrControl+Shift+H".to_string(),
+            toggle_click_through: "CommandOrControl+Shift+T".to_string(),
+        }
+    }
+}
+
+/// 主桌宠的 id，与主窗口的标签相同
+pub const MAIN_PET: &str = "main";
+
+/// 主桌宠之外的桌宠；位置与主桌宠一样记录窗口中心的逻辑坐标
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PetConfig {
+    /// 角色包 id
+    pub character: String,
+    pub x: f64,
+    pub y: f64,
+    /// 保存位置时所在显示器的名称
+    pub monitor: Option<String>,
+    /// 保存位置时的缩放比例
+    pub scale: Option<f64>,
+    pub size: i32,
+    pub opacity: f64,
+}
+
+impl Default for PetConfig {
+    fn default() -> Self {
+        let appearance = AppearanceConfig::default();
+        let window = WindowConfig::default();
+        Self {
+            character: BUILTIN_CHARACTER.to_string(),
+            x: window.main_window_x,
+            y: window.main_window_y,
+            monitor: None,
+            scale: None,
+            size: appearance.pet_size,
+            opacity: appearance.pet_opacity,
+        }
+    }
+}
+
+/// 解析 config.toml 并迁移到当前版本，返回配置与是否发生了迁移
+pub fn parse_config(content: &str) -> Result<(AppConfig, bool), ConfigError> {
+    let document: toml::Table = toml::from_str(content).map_err(ConfigError::DeserializationError)?;
+    parse_config_document(document)
+}
+
+/// 把已解析的配置文档迁移到当前版本再转换为配置，返回配置与是否发生了迁移
+pub fn parse_config_document(mut document: toml::Table) -> Result<(AppConfig, bool), ConfigError> {
+    let migrated = migrate_document(&mut document)?;
+    let config = document.try_into().map_err(ConfigError::DeserializationError)?;
+    Ok((config, migrated))
+}
+
+/// 把配置文档原地迁移到当前版本，返回是否发生了迁移；比当前版本新的文档会被拒绝
+pub fn migrate_document(document: &mut toml::Table) -> Result<bool, ConfigError> {
+    migration::migrate(document)
+}
+
+// 配置管理器
+pub struct ConfigManager {
+    config_path: PathBuf,
+    /// 尚未报告给前端的损坏恢复
+    recovery: Mutex<Option<ConfigRecovery>>,
+}
+
+/// 应用的配置目录，配置档案与其他本地数据（如对话历史）都存放在这里
+pub fn app_config_dir(app_name: &str) -> Result<PathBuf, ConfigError> {
+    Ok(dirs::config_dir()
+        .ok_or_else(|| ConfigError::DirectoryError("无法获取配置目录".to_string()))?
+        .join(app_name))
+}
+
+impl ConfigManager {
+    fn with_path(config_path: PathBuf) -> Self {
+        Self { config_path, recovery: Mutex::new(None) }
+    }
+
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
+    // 只保留load和save两个接口
+
+    /// 读取配置（如无则自动生成默认）；旧版本配置升级到当前版本后写回
+    ///
+    /// 配置无法解析时从最新的可用备份恢复（没有可用备份时重置为默认配置），取值无效的字段恢复为默认值，
+    /// 恢复情况通过 `take_recovery` 报告。
+    pub async fn load(&self) -> Result<AppConfig, ConfigError> {
+        if !self.config_path.exists() {
+            let default_config = AppConfig::default();
+            self.save(&default_config).await?;
+            return Ok(default_config);
+        }
+        let content = fs::read_to_string(&self.config_path)
+            .await
+            .map_err(ConfigError::IoError)?;
+        let (mut config, rewrite) = match self.parse(&content).await {
+            Ok(parsed) => parsed,
+            Err(ConfigError::DeserializationError(e)) => return self.recover(&e.to_string()).await,
+            Err(e) => return Err(e),
+        };
+        let invalid_fields = config.reset_invalid_fields();
+        if rewrite || !invalid_fields.is_empty() {
+            self.save(&config).await?;
+            secrets::scrub_backups(&self.config_path).await;
+        }
+        if !invalid_fields.is_empty() {
+            let message = format!("配置中的部分取值无效，已恢复为默认值（{}）", ValidationErrors(invalid_fields.clone()));
+            self.report(ConfigRecovery { message, restored_backup: None, corrupt_file: None, invalid_fields });
+        }
+        Ok(config)
+    }
+
+    /// 解析配置文件内容并填入加密保存的 API 密钥，返回配置与是否需要写回
+    ///
+    /// 旧版本迁移后，或配置文件中仍有明文密钥（会被移入加密文件）时需要写回。
+    pub async fn parse(&self, content: &str) -> Result<(AppConfig, bool), ConfigError> {
+        let (mut config, migrated) = parse_config(content)?;
+        let plaintext = !config.ai.api_key.is_empty() && !config.ai.api_key.is_mask();
+        if !plaintext {
+            config.ai.api_key = match secrets::load_api_key(&self.config_path).await {
+                Ok(api_key) => api_key,
+                // 密钥无法解密时先不使用密钥，提示用户重新填写
+                Err(ConfigError::SecretsError(e)) => {
+                    let message = format!("{}，请在设置中重新填写", e);
+                    let invalid_fields = Vec::new();
+                    self.report(ConfigRecovery { message, restored_backup: None, corrupt_file: None, invalid_fields });
+                    ApiKey::default()
+                }
+                Err(e) => return Err(e),
+            };
+        }
+        Ok((config, migrated || plaintext))
+    }
+
+    /// 保存配置：API 密钥加密另存，其余写入临时文件后原子替换，并轮转备份
+    pub async fn save(&self, config: &AppConfig) -> Result<(), ConfigError> {
+        // 确保目录存在
+        if let Some(parent) = self.config_path.parent() {
+            fs::create_dir_all(parent).await.map_err(ConfigError::IoError)?;
+        }
+        secrets::save_api_key(&self.config_path, &config.ai.api_key).await?;
+        let mut without_secrets = config.clone();
+        without_secrets.ai.api_key = ApiKey::default();
+        let content = toml::to_string_pretty(&without_secrets)
+            .map_err(ConfigError::SerializationError)?;
+        storage::write_atomic(&self.config_path, &content)
+            .await
+            .map_err(ConfigError::IoError)
+    }
+
+    /// 把损坏的配置移到一旁，从最新的可用备份恢复
+    async fn recover(&self, error: &str) -> Result<AppConfig, ConfigError> {
+        eprintln!("配置文件损坏: {}", error);
+        let corrupt_file = storage::set_aside(&self.config_path, now_millis())
+            .await
+            .map_err(ConfigError::IoError)?;
+
+        let mut restored = None;
+        for backup in storage::backup_paths(&self.config_path) {
+            let Ok(content) = fs::read_to_string(&backup).await else {
+                continue;
+            };
+            if let Ok((config, _)) = self.parse(&content).await {
+                restored = Some((backup.file_name().unwrap_or_default().to_string_lossy().into_owned(), config));
+                break;
+            }
+        }
+
+        let (restored_backup, mut config, message) = match restored {
+            Some((name, config)) => {
+                let message = format!("配置文件已损坏（{}），已从备份 {} 恢复", error, name);
+                (Some(name), config, message)
+            }
+            None => {
+                let message = format!("配置文件已损坏（{}），且没有可用的备份，已重置为默认配置", error);
+                (None, AppConfig::default(), message)
+            }
+        };
+        let invalid_fields = config.reset_invalid_fields();
+        self.save(&config).await?;
+        self.report(ConfigRecovery { message, restored_backup, corrupt_file: Some(corrupt_file), invalid_fields });
+        Ok(config)
+    }
+
+    /// 记录恢复情况；尚未报告的恢复情况合并在一起
+    fn report(&self, recovery: ConfigRecovery) {
+        let mut pending = self.recovery.lock().unwrap();
+        *pending = Some(match pending.take() {
+            // 重新读取时同样的问题不重复记录
+            Some(previous) if previous.message.contains(&recovery.message) => previous,
+            Some(mut previous) => {
+                previous.message = format!("{}；{}", previous.message, recovery.message);
+                previous.restored_backup = recovery.restored_backup.or(previous.restored_backup);
+                previous.corrupt_file = recovery.corrupt_file.or(previous.corrupt_file);
+                previous.invalid_fields.extend(recovery.invalid_fields);
+                previous
+            }
+            None => recovery,
+        });
+    }
+
+    /// 取出尚未报告的损坏恢复情况，每次恢复只报告一次
+    pub fn take_recovery(&self) -> Option<ConfigRecovery> {
+        self.recovery.lock().unwrap().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[tokio::test]
+    async fn test_config_save_and_load() {
+        // 使用临时目录进行测试
+        let temp_dir = env::temp_dir().join("desktop_pet_test");
+        let config_path = temp_dir.join("test_config.toml");
+        
+        let manager = ConfigManager::with_path(config_path.clone());
+
+        // 测试保存和加载默认配置
+        let default_config = AppConfig::default();
+        manager.save(&default_config).await.unwrap();
+
+        let loaded_config = manager.load().await.unwrap();
+        assert_eq!(loaded_config.appearance.pet_size, 150);
+        assert_eq!(loaded_config.ai.model, "deepseek-chat");
+        
+        // 清理测试文件
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    /// 每个历史版本的 config.toml
+    fn fixture(name: &str) -> String {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/config").join(name);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_every_historical_config_loads() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/config");
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 7);
+
+        for name in names {
+            let (config, migrated) = parse_config(&fixture(&name)).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_eq!(config.schema_version, CONFIG_SCHEMA_VERSION, "{}", name);
+            assert_eq!(migrated, name.starts_with("v1_"), "{}", name);
+            assert_eq!(config.appearance.pet_size, 180, "{}", name);
+            assert_eq!(config.ai.api_key.expose(), "sk-legacy", "{}", name);
+            assert_eq!(config.window.main_window_x, 1200.0, "{}", name);
+            assert_eq!(config.validate(), Ok(()), "{}", name);
+
+            // 迁移后的配置再次读取时保持不变
+            let (reloaded, migrated) = parse_config(&toml::to_string_pretty(&config).unwrap()).unwrap();
+            assert!(!migrated, "{}", name);
+            assert_eq!(toml::Value::try_from(&reloaded).unwrap(), toml::Value::try_from(&config).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_historical_configs_keep_user_settings() {
+        let (baseline, _) = parse_config(&fixture("v1_baseline.toml")).unwrap();
+        assert_eq!(baseline.ai.provider, ProviderKind::OpenAI);
+        assert_eq!(baseline.ai.system_prompt, None);
+        assert_eq!(baseline.ai.history_turns, 10);
+        assert_eq!(baseline.character.active, BUILTIN_CHARACTER);
+
+        let (repair, _) = parse_config(&fixture("v1_reply_repair.toml")).unwrap();
+        assert_eq!(repair.ai.provider, ProviderKind::Anthropic);
+        assert_eq!(repair.ai.system_prompt.as_deref(), Some("你叫小白，是一只猫娘。"));
+        assert!(!repair.ai.repair_invalid_reply);
+
+        let (memory, _) = parse_config(&fixture("v1_memory.toml")).unwrap();
+        assert_eq!(memory.ai.provider, ProviderKind::Ollama);
+        assert!(!memory.ai.auto_memory);
+        assert_eq!(memory.ai.memory_recall_limit, 3);
+
+        let (current, _) = parse_config(&fixture("v2.toml")).unwrap();
+        assert_eq!(current.ai.context_budget, 8000);
+        assert_eq!(current.character.active, "lingchat-0a1b2c3d");
+    }
+
+    #[tokio::test]
+    async fn test_load_writes_back_migrated_config() {
+        let temp_dir = env::temp_dir().join("desktop_pet_test_migration");
+        let config_path = temp_dir.join("config.toml");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        std::fs::write(&config_path, fixture("v1_baseline.toml")).unwrap();
+
+        let manager = ConfigManager::with_path(config_path.clone());
+        manager.load().await.unwrap();
+        let written = std::fs::read_to_string(&config_path).unwrap();
+        assert!(written.starts_with(&format!("schema_version = {}", CONFIG_SCHEMA_VERSION)));
+        assert!(!written.contains("你叫钦灵"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_load_moves_plaintext_api_key_into_secrets() {
+        let temp_dir = env::temp_dir().join("desktop_pet_test_plaintext_key");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let config_path = temp_dir.join("config.toml");
+        std::fs::write(&config_path, fixture("v2.toml")).unwrap();
+
+        let manager = ConfigManager::with_path(config_path.clone());
+        let loaded = manager.load().await.unwrap();
+        assert_eq!(loaded.ai.api_key.expose(), "sk-legacy");
+
+        // 配置文件与备份中都不再有明文密钥
+        assert!(!std::fs::read_to_string(&config_path).unwrap().contains("sk-legacy"));
+        let backups = storage::backup_paths(&config_path);
+        assert_eq!(backups.len(), 1);
+        assert!(!std::fs::read_to_string(&backups[0]).unwrap().contains("sk-legacy"));
+        assert!(!std::fs::read_to_string(secrets::secrets_path(&config_path)).unwrap().contains("sk-legacy"));
+
+        // 再次读取时从加密文件取回，不再写回
+        assert_eq!(manager.load().await.unwrap().ai.api_key.expose(), "sk-legacy");
+        assert_eq!(storage::backup_paths(&config_path).len(), 1);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_load_reports_unreadable_api_key() {
+        let temp_dir = env::temp_dir().join("desktop_pet_test_unreadable_key");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let config_path = temp_dir.join("config.toml");
+        let manager = ConfigManager::with_path(config_path.clone());
+        let mut config = AppConfig::default();
+        config.ai.api_key = ApiKey::from("sk-secret");
+        manager.save(&config).await.unwrap();
+
+        // 本机密钥被重新生成
+        std::fs::write(temp_dir.join("secret.key"), [7u8; 32]).unwrap();
+        let loaded = manager.load().await.unwrap();
+        assert!(loaded.ai.api_key.is_empty());
+        assert!(manager.take_recovery().unwrap().message.contains("API 密钥"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_load_recovers_from_newest_valid_backup() {
+        let temp_dir = env::temp_dir().join("desktop_pet_test_recovery");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let config_path = temp_dir.join("config.toml");
+        let manager = ConfigManager::with_path(config_path.clone());
+
+        let mut config = AppConfig::default();
+        for size in [100, 120] {
+            config.appearance.pet_size = size;
+            manager.save(&config).await.unwrap();
+        }
+        // 最新的备份也损坏了，应跳过它
+        std::fs::write(storage::backup_path(&config_path, 1), "[appearance\n").unwrap();
+        config.appearance.pet_size = 110;
+        std::fs::write(storage::backup_path(&config_path, 2), toml::to_string(&config).unwrap()).unwrap();
+        // 写到一半被截断的配置
+        std::fs::write(&config_path, "[appearance]\npet_size = ").unwrap();
+
+        let loaded = manager.load().await.unwrap();
+        assert_eq!(loaded.appearance.pet_size, 110);
+        let recovery = manager.take_recovery().unwrap();
+        assert_eq!(recovery.restored_backup.as_deref(), Some("config.toml.bak2"));
+        assert!(temp_dir.join(recovery.corrupt_file.unwrap()).is_file());
+        assert!(manager.take_recovery().is_none());
+        // 恢复后的配置已写回
+        assert_eq!(manager.load().await.unwrap().appearance.pet_size, 110);
+
+        // 没有可用备份时重置为默认配置
+        for backup in storage::backup_paths(&config_path) {
+            std::fs::remove_file(backup).unwrap();
+        }
+        std::fs::write(&config_path, "not toml").unwrap();
+        assert_eq!(manager.load().await.unwrap().appearance.pet_size, 150);
+        assert_eq!(manager.take_recovery().unwrap().restored_backup, None);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_load_resets_invalid_fields() {
+        let temp_dir = env::temp_dir().join("desktop_pet_test_invalid");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let config_path = temp_dir.join("config.toml");
+        let mut config = AppConfig::default();
+        config.appearance.pet_size = -5;
+        config.ai.model = "gpt-4o".to_string();
+        config.ai.max_tokens = 0;
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+        let manager = ConfigManager::with_path(config_path.clone());
+        let loaded = manager.load().await.unwrap();
+        assert_eq!(loaded.appearance.pet_size, 150);
+        assert_eq!(loaded.ai.max_tokens, 2000);
+        assert_eq!(loaded.ai.model, "gpt-4o");
+
+        let recovery = manager.take_recovery().unwrap();
+        assert_eq!(recovery.corrupt_file, None);
+        let fields: Vec<&str> = recovery.invalid_fields.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, vec!["appearance.pet_size", "ai.max_tokens"]);
+        // 修正后的配置已写回，原文件留在备份中
+        assert_eq!(manager.load().await.unwrap(), loaded);
+        assert!(manager.take_recovery().is_none());
+        assert_eq!(storage::backup_paths(&config_path).len(), 1);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_provider_defaults_to_openai_for_old_configs() {
+        let mut value = toml::Value::try_from(AppConfig::default()).unwrap();
+        value["ai"].as_table_mut().unwrap().remove("provider");
+        let config: AppConfig = toml::from_str(&toml::to_string(&value).unwrap()).unwrap();
+        assert_eq!(config.ai.provider, ProviderKind::OpenAI);
+
+        let mut value = toml::Value::try_from(AppConfig::default()).unwrap();
+        value["ai"]["provider"] = toml::Value::String("anthropic".to_string());
+        let config: AppConfig = toml::from_str(&toml::to_string(&value).unwrap()).unwrap();
+        assert_eq!(config.ai.provider, ProviderKind::Anthropic);
+    }
+}
@@ -0,0 +1,329 @@
+//! 环境变量与命令行覆盖
+//!
+//! 配置按层解析：默认值 < 配置文件 < 环境变量 < 命令行，后面的层覆盖前面的层。
+//! 覆盖的取值只存在于内存中，不会写入配置文件；`ConfigStore` 保存的始终是文件层的配置。
+//!
+//! 环境变量：
+//!   - `DESKTOP_PET_CONFIG_DIR`: 配置目录（档案、对话历史、记忆、角色包）
+//!   - `DESKTOP_PET_CONFIG`: 直接读写这一个配置文件，不使用配置档案
+//!   - `DESKTOP_PET_PROFILE`: 本次启动使用的配置档案
+//!   - `DESKTOP_PET_<分区>_<字段>`: 覆盖某个字段，如 `DESKTOP_PET_AI_MODEL`
+//!
+//! 命令行：`--config-dir <目录>`、`--config <文件>`、`--profile <名称>`、`--set <分区.字段>=<值>`，
+//! 也可以写成 `--config=<文件>`。取值先按 JSON 解析（数字、布尔、`null`），类型不符时按字符串处理。
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::store::patch_section;
+use super::{app_config_dir, AppConfig, ConfigError, ConfigSection};
+
+/// 环境变量的前缀
+pub const ENV_PREFIX: &str = "DESKTOP_PET_";
+
+const ENV_CONFIG_DIR: &str = "DESKTOP_PET_CONFIG_DIR";
+const ENV_CONFIG_FILE: &str = "DESKTOP_PET_CONFIG";
+const ENV_PROFILE: &str = "DESKTOP_PET_PROFILE";
+
+const SECTIONS: [ConfigSection; 6] = [
+    ConfigSection::Appearance,
+    ConfigSection::AI,
+    ConfigSection::Window,
+    ConfigSection::Character,
+    ConfigSection::Behavior,
+    ConfigSection::Hotkeys,
+];
+
+/// 配置取值来自哪一层，按优先级从低到高排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigLayer {
+    /// 与默认值相同
+    Default,
+    /// 配置文件
+    File,
+    /// 环境变量
+    Env,
+    /// 命令行参数
+    Cli,
+}
+
+/// 对单个字段的覆盖
+#[derive(Debug, Clone)]
+struct FieldOverride {
+    section: ConfigSection,
+    field: String,
+    value: Value,
+    layer: ConfigLayer,
+}
+
+impl FieldOverride {
+    fn pointer(&self) -> String {
+        format!("/{}/{}", section_key(self.section), self.field)
+    }
+}
+
+/// 启动时从环境变量与命令行读到的覆盖
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    config_dir: Option<PathBuf>,
+    config_file: Option<PathBuf>,
+    profile: Option<String>,
+    /// 按优先级排列，后面的覆盖前面的
+    fields: Vec<FieldOverride>,
+}
+
+fn section_key(section: ConfigSection) -> &'static str {
+    match section {
+        ConfigSection::Appearance => "appearance",
+        ConfigSection::AI => "ai",
+        ConfigSection::Window => "window",
+        ConfigSection::Character => "character",
+        ConfigSection::Pets => "pets",
+        ConfigSection::Behavior => "behavior",
+        ConfigSection::Hotkeys => "hotkeys",
+    }
+}
+
+/// 所有可以覆盖的字段
+fn known_fields() -> Vec<(ConfigSection, String)> {
+    let defaults = serde_json::to_value(AppConfig::default()).unwrap_or_default();
+    SECTIONS
+        .into_iter()
+        .flat_map(|section| {
+            let fields = defaults[section_key(section)].as_object().cloned().unwrap_or_default();
+            fields.into_iter().map(move |(field, _)| (section, field))
+        })
+        .collect()
+}
+
+/// 把命令行或环境变量中的字符串转成字段需要的类型
+fn typed_value(section: ConfigSection, field: &str, raw: &str) -> Result<Value, ConfigError> {
+    let candidates = serde_json::from_str(raw).into_iter().chain([Value::String(raw.to_string())]);
+    for value in candidates {
+        let mut config = AppConfig::default();
+        if patch_section(&mut config, section, json!({ field: value.clone() })).is_ok() {
+            return Ok(value);
+        }
+    }
+    Err(ConfigError::InvalidOverride(format!("{}.{} 的取值无效: {}", section_key(section), field, raw)))
+}
+
+impl Overrides {
+    /// 读取当前进程的环境变量与命令行参数
+    pub fn from_env_and_args() -> Result<Self, ConfigError> {
+        Self::parse(std::env::vars(), std::env::args().skip(1))
+    }
+
+    /// 解析环境变量与命令行参数（不含程序名）；不认识的命令行参数留给其他组件，直接忽略
+    pub fn parse(
+        env: impl IntoIterator<Item = (String, String)>,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Self, ConfigError> {
+        let known = known_fields();
+        let mut overrides = Self::default();
+
+        let mut env: Vec<(String, String)> = env.into_iter().filter(|(key, _)| key.starts_with(ENV_PREFIX)).collect();
+        env.sort();
+        for (key, value) in env {
+            match key.as_str() {
+                ENV_CONFIG_DIR => overrides.config_dir = Some(PathBuf::from(value)),
+                ENV_CONFIG_FILE => overrides.config_file = Some(PathBuf::from(value)),
+                ENV_PROFILE => overrides.profile = Some(value),
+                _ => {
+                    let name = &key[ENV_PREFIX.len()..];
+                    let found = known.iter().find(|(section, field)| {
+                        format!("{}_{}", section_key(*section), field).to_uppercase() == name
+                    });
+                    match found {
+                        Some((section, field)) => overrides.push(*section, field, &value, ConfigLayer::Env)?,
+                        None => eprintln!("忽略未知的环境变量 {}", key),
+                    }
+                }
+            }
+        }
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg, None),
+            };
+            if !matches!(flag.as_str(), "--config-dir" | "--config" | "--profile" | "--set") {
+                continue;
+            }
+            let value = inline
+                .or_else(|| args.next())
+                .ok_or_else(|| ConfigError::InvalidOverride(format!("{} 缺少取值", flag)))?;
+            match flag.as_str() {
+                "--config-dir" => overrides.config_dir = Some(PathBuf::from(value)),
+                "--config" => overrides.config_file = Some(PathBuf::from(value)),
+                "--profile" => overrides.profile = Some(value),
+                _ => {
+                    let (key, raw) = value
+                        .split_once('=')
+                        .ok_or_else(|| ConfigError::InvalidOverride(format!("--set 应写成 分区.字段=值: {}", value)))?;
+                    let (section, field) = known
+                        .iter()
+                        .find(|(section, field)| format!("{}.{}", section_key(*section), field) == key)
+                        .ok_or_else(|| ConfigError::InvalidOverride(format!("未知字段 {}", key)))?;
+                    overrides.push(*section, field, raw, ConfigLayer::Cli)?;
+                }
+            }
+        }
+        Ok(overrides)
+    }
+
+    fn push(&mut self, section: ConfigSection, field: &str, raw: &str, layer: ConfigLayer) -> Result<(), ConfigError> {
+        let value = typed_value(section, field, raw)?;
+        self.fields.push(FieldOverride { section, field: field.to_string(), value, layer });
+        Ok(())
+    }
+
+    /// 配置目录：覆盖的目录，否则为系统的应用配置目录
+    pub fn config_dir(&self, app_name: &str) -> Result<PathBuf, ConfigError> {
+        match &self.config_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => app_config_dir(app_name),
+        }
+    }
+
+    /// 指定的配置文件，此时不使用配置档案
+    pub fn config_file(&self) -> Option<&Path> {
+        self.config_file.as_deref()
+    }
+
+    /// 本次启动使用的配置档案
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// 在文件层的配置上叠加覆盖，得到实际使用的配置
+    pub fn apply(&self, file: &AppConfig) -> AppConfig {
+        let mut config = file.clone();
+        for field in &self.fields {
+            let patch = json!({ field.field.as_str(): field.value.clone() });
+            if let Err(e) = patch_section(&mut config, field.section, patch) {
+                eprintln!("应用配置覆盖 {} 失败: {}", field.pointer(), e);
+            }
+        }
+        config
+    }
+
+    /// 前端提交回来的是叠加覆盖后的配置：仍等于覆盖值的字段还原为文件中的值，覆盖值不会写入文件
+    pub fn restore_file_values(&self, updated: &mut AppConfig, file: &AppConfig) {
+        let (Ok(current), Ok(file_values)) = (serde_json::to_value(&*updated), serde_json::to_value(file)) else {
+            return;
+        };
+        for field in &self.fields {
+            let pointer = field.pointer();
+            if current.pointer(&pointer) != Some(&field.value) {
+                continue;
+            }
+            if let Some(value) = file_values.pointer(&pointer) {
+                let _ = patch_section(updated, field.section, json!({ field.field.as_str(): value.clone() }));
+            }
+        }
+    }
+
+    /// 每个字段（`分区.字段`）的取值来自哪一层；文件中与默认值相同的取值视为默认层
+    pub fn sources(&self, file: &AppConfig) -> BTreeMap<String, ConfigLayer> {
+        let file_values = serde_json::to_value(file).unwrap_or_default();
+        let defaults = serde_json::to_value(AppConfig::default()).unwrap_or_default();
+        known_fields()
+            .into_iter()
+            .map(|(section, field)| {
+                let key = format!("{}.{}", section_key(section), field);
+                let overridden = self.fields.iter().rev().find(|o| o.section == section && o.field == field);
+                let layer = match overridden {
+                    Some(o) => o.layer,
+                    None => {
+                        let pointer = format!("/{}/{}", section_key(section), field);
+                        if file_values.pointer(&pointer) == defaults.pointer(&pointer) {
+                            ConfigLayer::Default
+                        } else {
+                            ConfigLayer::File
+                        }
+                    }
+                };
+                (key, layer)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_layers_resolve_in_order() {
+        let overrides = Overrides::parse(
+            env(&[
+                ("DESKTOP_PET_AI_MODEL", "gpt-4o"),
+                ("DESKTOP_PET_AI_BASE_URL", "http://localhost:11434"),
+                ("DESKTOP_PET_APPEARANCE_PET_SIZE", "200"),
+                ("DESKTOP_PET_CONFIG_DIR", "/tmp/pet"),
+                ("HOME", "/root"),
+            ]),
+            args(&["--profile", "工作", "--set", "ai.model=claude", "--set=ai.system_prompt=123", "--verbose"]),
+        )
+        .unwrap();
+        assert_eq!(overrides.config_dir("desktop_pet").unwrap(), PathBuf::from("/tmp/pet"));
+        assert_eq!(overrides.profile(), Some("工作"));
+        assert_eq!(overrides.config_file(), None);
+
+        let mut file = AppConfig::default();
+        file.ai.model = "deepseek-reasoner".to_string();
+        file.ai.temperature = 1.2;
+        let config = overrides.apply(&file);
+        assert_eq!(config.ai.model, "claude");
+        assert_eq!(config.ai.base_url, "http://localhost:11434");
+        assert_eq!(config.appearance.pet_size, 200);
+        // 字符串字段中的数字按字符串处理
+        assert_eq!(config.ai.system_prompt.as_deref(), Some("123"));
+        assert_eq!(config.ai.temperature, 1.2);
+
+        let sources = overrides.sources(&file);
+        assert_eq!(sources["ai.model"], ConfigLayer::Cli);
+        assert_eq!(sources["ai.base_url"], ConfigLayer::Env);
+        assert_eq!(sources["ai.temperature"], ConfigLayer::File);
+        assert_eq!(sources["ai.max_tokens"], ConfigLayer::Default);
+        assert_eq!(sources.len(), known_fields().len());
+    }
+
+    #[test]
+    fn test_invalid_overrides_are_rejected() {
+        let invalid = [
+            (env(&[("DESKTOP_PET_APPEARANCE_PET_SIZE", "big")]), args(&[])),
+            (env(&[]), args(&["--set", "ai.modle=x"])),
+            (env(&[]), args(&["--set", "ai.model"])),
+            (env(&[]), args(&["--config"])),
+        ];
+        for (env, args) in invalid {
+            assert!(matches!(Overrides::parse(env, args), Err(ConfigError::InvalidOverride(_))));
+        }
+    }
+
+    #[test]
+    fn test_overridden_values_are_not_written_back() {
+        let overrides = Overrides::parse(env(&[("DESKTOP_PET_AI_MODEL", "gpt-4o")]), args(&[])).unwrap();
+        let file = AppConfig::default();
+        // 前端把叠加覆盖后的整个分区提交回来，同时修改了温度
+        let mut updated = overrides.apply(&file);
+        updated.ai.temperature = 1.0;
+        overrides.restore_file_values(&mut updated, &file);
+        assert_eq!(updated.ai.model, "deepseek-chat");
+        assert_eq!(updated.ai.temperature, 1.0);
+    }
+}
@@ -0,0 +1,444 @@
+//! 配置档案
+//!
+//! 每个档案是 `profiles/<名称>.toml` 中的一份完整配置（各自带备份），`profiles.toml` 记录当前档案。
+//! 同一时间只有当前档案由 `ConfigStore` 读写；切换档案时 `ConfigStore` 改为读写另一个文件。
+//! 旧版本的 `config.toml` 在第一次启动时连同备份移入 `default` 档案。
+//! 通过 `--profile` 指定的档案只在本次运行中使用，不修改 profiles.toml；
+//! 通过 `--config` 直接指定配置文件时不使用档案，档案操作都会被拒绝。
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::sync::Mutex;
+
+use super::{secrets, storage, AppConfig, ConfigError, ConfigManager, ConfigStore};
+
+/// 默认档案，也是旧版本 config.toml 迁移后的档案
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 档案名称的最大长度（字符）
+const MAX_NAME_LEN: usize = 64;
+
+/// profiles.toml 的内容
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct ProfileIndex {
+    active: String,
+}
+
+impl Default for ProfileIndex {
+    fn default() -> Self {
+        Self { active: DEFAULT_PROFILE.to_string() }
+    }
+}
+
+/// 档案列表中的一项
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+}
+
+/// 名称会用作文件名：不能为空、不能以 `.` 开头，不能含路径分隔符、控制字符或各平台文件名中的保留字符
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && name.trim() == name
+        && name.chars().count() <= MAX_NAME_LEN
+        && !name.starts_with('.')
+        && !name.chars().any(|c| c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+}
+
+// 配置档案的存储
+pub struct ProfileStore {
+    /// 应用的配置目录
+    dir: PathBuf,
+    /// 通过 `--config` 指定的配置文件
+    pinned: Option<PathBuf>,
+    /// 当前档案的名称；同时串行化档案操作
+    active: Mutex<String>,
+}
+
+impl ProfileStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, pinned: None, active: Mutex::new(DEFAULT_PROFILE.to_string()) }
+    }
+
+    /// 直接读写指定的配置文件，不使用档案
+    pub fn with_config_file(mut self, file: PathBuf) -> Self {
+        self.pinned = Some(file);
+        self
+    }
+
+    fn profiles_dir(&self) -> PathBuf {
+        self.dir.join("profiles")
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("profiles.toml")
+    }
+
+    fn profile_path(&self, name: &str) -> PathBuf {
+        self.profiles_dir().join(format!("{}.toml", name))
+    }
+
+    /// 读写某个档案的配置管理器
+    pub fn manager(&self, name: &str) -> ConfigManager {
+        ConfigManager::with_path(self.profile_path(name))
+    }
+
+    /// 准备档案目录，返回当前配置文件的管理器
+    ///
+    /// 没有 profiles.toml 时把旧的 config.toml 迁移为默认档案；索引损坏或指向无效名称时回到默认档案。
+    /// 指定了 `requested` 时本次使用该档案（不存在时以默认配置新建），不修改索引。
+    pub async fn init(&self, requested: Option<&str>) -> Result<ConfigManager, ConfigError> {
+        if let Some(file) = &self.pinned {
+            return Ok(ConfigManager::with_path(file.clone()));
+        }
+        fs::create_dir_all(self.profiles_dir()).await.map_err(ConfigError::IoError)?;
+        let index = match fs::read_to_string(self.index_path()).await {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("配置档案索引损坏: {}", e);
+                ProfileIndex::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.migrate_legacy_config().await?;
+                ProfileIndex::default()
+            }
+            Err(e) => return Err(ConfigError::IoError(e)),
+        };
+        let active = match requested {
+            Some(name) => {
+                if !is_valid_profile_name(name) {
+                    return Err(ConfigError::InvalidProfileName(name.to_string()));
+                }
+                if !self.exists(name) {
+                    self.manager(name).save(&AppConfig::default()).await?;
+                }
+                name.to_string()
+            }
+            None => {
+                let active = if is_valid_profile_name(&index.active) { index.active } else { DEFAULT_PROFILE.to_string() };
+                self.write_index(&active).await?;
+                active
+            }
+        };
+        *self.active.lock().await = active.clone();
+        Ok(self.manager(&active))
+    }
+
+    async fn migrate_legacy_config(&self) -> Result<(), ConfigError> {
+        let legacy = self.dir.join("config.toml");
+        let target = self.profile_path(DEFAULT_PROFILE);
+        if legacy.is_file() && !target.exists() {
+            storage::move_with_backups(&legacy, &target).await.map_err(ConfigError::IoError)?;
+        }
+        Ok(())
+    }
+
+    async fn write_index(&self, active: &str) -> Result<(), ConfigError> {
+        let index = ProfileIndex { active: active.to_string() };
+        let content = toml::to_string_pretty(&index).map_err(ConfigError::SerializationError)?;
+        storage::write_atomic_without_backup(&self.index_path(), &content)
+            .await
+            .map_err(ConfigError::IoError)
+    }
+
+    /// 当前档案的名称
+    pub async fn active(&self) -> String {
+        self.active.lock().await.clone()
+    }
+
+    /// 开始一次档案操作；指定了配置文件时拒绝
+    async fn operation(&self) -> Result<tokio::sync::MutexGuard<'_, String>, ConfigError> {
+        if self.pinned.is_some() {
+            return Err(ConfigError::ProfilesPinned);
+        }
+        Ok(self.active.lock().await)
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        self.profile_path(name).is_file()
+    }
+
+    fn check_new_name(&self, name: &str) -> Result<(), ConfigError> {
+        if !is_valid_profile_name(name) {
+            return Err(ConfigError::InvalidProfileName(name.to_string()));
+        }
+        if self.exists(name) {
+            return Err(ConfigError::ProfileExists(name.to_string()));
+        }
+        Ok(())
+    }
+
+    fn check_exists(&self, name: &str) -> Result<(), ConfigError> {
+        if is_valid_profile_name(name) && self.exists(name) {
+            Ok(())
+        } else {
+            Err(ConfigError::ProfileNotFound(name.to_string()))
+        }
+    }
+
+    /// 所有档案，按名称排序；当前档案尚未写盘时也会列出
+    ///
+    /// 指定了配置文件时只列出该文件。
+    pub async fn list(&self) -> Result<Vec<ProfileInfo>, ConfigError> {
+        if let Some(file) = &self.pinned {
+            return Ok(vec![ProfileInfo { name: pinned_name(file), active: true }]);
+        }
+        let active = self.active().await;
+        let mut names = vec![active.clone()];
+        let mut entries = fs::read_dir(self.profiles_dir()).await.map_err(ConfigError::IoError)?;
+        while let Some(entry) = entries.next_entry().await.map_err(ConfigError::IoError)? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                if is_valid_profile_name(name) && !names.iter().any(|existing| existing == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names.into_iter().map(|name| ProfileInfo { active: name == active, name }).collect())
+    }
+
+    /// 新建一个使用默认配置的档案
+    pub async fn create(&self, name: &str) -> Result<(), ConfigError> {
+        let _active = self.operation().await?;
+        self.check_new_name(name)?;
+        self.manager(name).save(&AppConfig::default()).await
+    }
+
+    /// 复制已有的档案；复制当前档案时包含尚未写盘的修改
+    pub async fn clone_profile(&self, source: &str, name: &str, config: &ConfigStore) -> Result<(), ConfigError> {
+        let active = self.operation().await?;
+        if source == *active {
+            config.flush().await?;
+        }
+        self.check_exists(source)?;
+        self.check_new_name(name)?;
+        let (from, to) = (self.profile_path(source), self.profile_path(name));
+        fs::copy(&from, &to).await.map_err(ConfigError::IoError)?;
+        if secrets::secrets_path(&from).is_file() {
+            fs::copy(secrets::secrets_path(&from), secrets::secrets_path(&to))
+                .await
+                .map_err(ConfigError::IoError)?;
+        }
+        Ok(())
+    }
+
+    /// 档案改名，备份与加密的密钥随之改名
+    pub async fn rename(&self, name: &str, new_name: &str, config: &ConfigStore) -> Result<(), ConfigError> {
+        let mut active = self.operation().await?;
+        let is_active = name == *active;
+        if is_active {
+            config.flush().await?;
+        }
+        self.check_exists(name)?;
+        self.check_new_name(new_name)?;
+        let (from, to) = (self.profile_path(name), self.profile_path(new_name));
+        storage::move_with_backups(&from, &to).await.map_err(ConfigError::IoError)?;
+        if secrets::secrets_path(&from).is_file() {
+            fs::rename(secrets::secrets_path(&from), secrets::secrets_path(&to))
+                .await
+                .map_err(ConfigError::IoError)?;
+        }
+        if is_active {
+            config.relocate(self.manager(new_name)).await;
+            self.write_index(new_name).await?;
+            *active = new_name.to_string();
+        }
+        Ok(())
+    }
+
+    /// 删除档案及其备份与加密的密钥；当前档案不能删除
+    pub async fn delete(&self, name: &str) -> Result<(), ConfigError> {
+        let active = self.operation().await?;
+        if name == *active {
+            return Err(ConfigError::ProfileActive(name.to_string()));
+        }
+        self.check_exists(name)?;
+        let path = self.profile_path(name);
+        if secrets::secrets_path(&path).is_file() {
+            fs::remove_file(secrets::secrets_path(&path)).await.map_err(ConfigError::IoError)?;
+        }
+        storage::remove_with_backups(&path).await.map_err(ConfigError::IoError)
+    }
+
+    /// 切换当前档案，外观与 AI 配置立即生效
+    pub async fn activate(&self, name: &str, config: &ConfigStore) -> Result<(), ConfigError> {
+        let mut active = self.operation().await?;
+        self.check_exists(name)?;
+        if name == *active {
+            return Ok(());
+        }
+        config.switch_to(self.manager(name)).await?;
+        self.write_index(name).await?;
+        *active = name.to_string();
+        Ok(())
+    }
+}
+
+/// 指定的配置文件在档案列表中显示的名称
+fn pinned_name(file: &Path) -> String {
+    file.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ChangeSource, ConfigSection, Overrides};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    async fn setup(name: &str) -> (ProfileStore, Arc<ConfigStore>, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("desktop_pet_test_profiles_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let profiles = ProfileStore::new(dir.clone());
+        let manager = profiles.init(None).await.unwrap();
+        let config = Arc::new(ConfigStore::open(manager, Overrides::default()).await.unwrap());
+        (profiles, config, dir)
+    }
+
+    fn names(profiles: &[ProfileInfo]) -> Vec<(&str, bool)> {
+        profiles.iter().map(|profile| (profile.name.as_str(), profile.active)).collect()
+    }
+
+    #[test]
+    fn test_profile_names() {
+        assert!(is_valid_profile_name("工作"));
+        assert!(is_valid_profile_name("home 2"));
+        for name in ["", " 工作", ".hidden", "a/b", "..\\b", "a:b", "x\n"] {
+            assert!(!is_valid_profile_name(name), "{:?}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_legacy_config_becomes_default_profile() {
+        let dir = std::env::temp_dir().join("desktop_pet_test_profiles_legacy");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let legacy = ConfigManager::with_path(dir.join("config.toml"));
+        let mut config = AppConfig::default();
+        for size in [120, 180] {
+            config.appearance.pet_size = size;
+            legacy.save(&config).await.unwrap();
+        }
+
+        let profiles = ProfileStore::new(dir.clone());
+        profiles.init(None).await.unwrap();
+        assert_eq!(profiles.active().await, DEFAULT_PROFILE);
+        assert!(!dir.join("config.toml").exists());
+        assert_eq!(profiles.manager(DEFAULT_PROFILE).load().await.unwrap().appearance.pet_size, 180);
+        assert_eq!(storage::backup_paths(&profiles.profile_path(DEFAULT_PROFILE)).len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_create_clone_rename_delete() {
+        let (profiles, config, dir) = setup("crud").await;
+        config.patch(ConfigSection::AI, json!({"model": "gpt-4o", "api_key": "sk-work"})).await.unwrap();
+
+        profiles.create("家里").await.unwrap();
+        profiles.clone_profile(DEFAULT_PROFILE, "工作", &config).await.unwrap();
+        assert_eq!(
+            names(&profiles.list().await.unwrap()),
+            vec![("default", true), ("家里", false), ("工作", false)]
+        );
+        // 复制时包含尚未写盘的修改
+        let work = profiles.manager("工作").load().await.unwrap();
+        assert_eq!((work.ai.model.as_str(), work.ai.api_key.expose()), ("gpt-4o", "sk-work"));
+        assert_eq!(profiles.manager("家里").load().await.unwrap().ai.model, "deepseek-chat");
+
+        assert!(matches!(profiles.create("工作").await, Err(ConfigError::ProfileExists(_))));
+        assert!(matches!(profiles.create("a/b").await, Err(ConfigError::InvalidProfileName(_))));
+        assert!(matches!(profiles.delete(DEFAULT_PROFILE).await, Err(ConfigError::ProfileActive(_))));
+        assert!(matches!(profiles.delete("不存在").await, Err(ConfigError::ProfileNotFound(_))));
+
+        // 当前档案改名后继续写入新文件
+        profiles.rename(DEFAULT_PROFILE, "主要", &config).await.unwrap();
+        assert_eq!(profiles.active().await, "主要");
+        config.patch(ConfigSection::AI, json!({"model": "claude"})).await.unwrap();
+        config.flush().await.unwrap();
+        let main = profiles.manager("主要").load().await.unwrap();
+        assert_eq!((main.ai.model.as_str(), main.ai.api_key.expose()), ("claude", "sk-work"));
+        assert!(!profiles.exists(DEFAULT_PROFILE));
+
+        profiles.delete("家里").await.unwrap();
+        assert_eq!(names(&profiles.list().await.unwrap()), vec![("主要", true), ("工作", false)]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_activate_hot_applies_sections() {
+        let (profiles, config, dir) = setup("activate").await;
+        config.patch(ConfigSection::Window, json!({"main_window_x": 10.0})).await.unwrap();
+        profiles.create("工作").await.unwrap();
+        let mut work = profiles.manager("工作").load().await.unwrap();
+        work.ai.model = "gpt-4o".to_string();
+        work.appearance.pet_size = 200;
+        profiles.manager("工作").save(&work).await.unwrap();
+
+        let mut changes = config.subscribe();
+        profiles.activate("工作", &config).await.unwrap();
+        // 切换前先把原档案的修改写盘
+        assert_eq!(changes.try_recv().unwrap().source, ChangeSource::Save);
+        assert_eq!(profiles.active().await, "工作");
+
+        let change = changes.try_recv().unwrap();
+        assert_eq!(change.source, ChangeSource::Profile);
+        assert_eq!(change.sections, vec![ConfigSection::Appearance, ConfigSection::AI]);
+        let current = config.get().await;
+        assert_eq!(current.ai.model, "gpt-4o");
+        // 窗口位置沿用切换前的值，并写入新的档案
+        assert_eq!(current.window.main_window_x, 10.0);
+        config.flush().await.unwrap();
+        assert_eq!(profiles.manager("工作").load().await.unwrap().window.main_window_x, 10.0);
+        // 切换前的修改已写入原档案
+        assert_eq!(profiles.manager(DEFAULT_PROFILE).load().await.unwrap().window.main_window_x, 10.0);
+
+        assert!(matches!(profiles.activate("家里", &config).await, Err(ConfigError::ProfileNotFound(_))));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_requested_profile_is_not_persisted() {
+        let (profiles, _config, dir) = setup("requested").await;
+        drop(profiles);
+
+        let profiles = ProfileStore::new(dir.clone());
+        let manager = profiles.init(Some("测试")).await.unwrap();
+        assert_eq!(manager.config_path(), profiles.profile_path("测试"));
+        assert_eq!(profiles.active().await, "测试");
+        assert_eq!(
+            names(&profiles.list().await.unwrap()),
+            vec![("default", false), ("测试", true)]
+        );
+        // 下次正常启动仍使用原来的档案
+        let profiles = ProfileStore::new(dir.clone());
+        profiles.init(None).await.unwrap();
+        assert_eq!(profiles.active().await, DEFAULT_PROFILE);
+
+        assert!(matches!(profiles.init(Some("a/b")).await, Err(ConfigError::InvalidProfileName(_))));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_config_file_disables_profiles() {
+        let dir = std::env::temp_dir().join("desktop_pet_test_profiles_pinned");
+        let _ = std::fs::remove_dir_all(&dir);
+        let file = dir.join("ci.toml");
+        let profiles = ProfileStore::new(dir.clone()).with_config_file(file.clone());
+        let manager = profiles.init(None).await.unwrap();
+        assert_eq!(manager.config_path(), file);
+        assert!(!profiles.profiles_dir().exists());
+
+        assert_eq!(names(&profiles.list().await.unwrap()), vec![("ci", true)]);
+        assert!(matches!(profiles.create("工作").await, Err(ConfigError::ProfilesPinned)));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
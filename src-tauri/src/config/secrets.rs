@@ -0,0 +1,257 @@
+//! 加密保存的密钥
+//!
+//! API 密钥不写入配置文件，而是加密后保存在同目录的 `<名称>.secrets` 中，配置文件备份或分享出去也不会泄露。
+//! 加密密钥由同目录的 `secret.key`（首次使用时随机生成、只保存在本机）派生。
+//! 发给前端的配置中密钥显示为占位符，真实的密钥只在 Rust 中使用；前端提交回占位符表示不修改。
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use super::{storage, AIConfig, ConfigError};
+
+/// 已保存的密钥在前端显示的占位符
+pub const API_KEY_MASK: &str = "********";
+
+/// 本机密钥文件名
+const MACHINE_KEY_FILE: &str = "secret.key";
+
+/// 加密文件的格式版本前缀
+const FORMAT_PREFIX: &str = "v1:";
+
+/// 派生加密密钥时混入的标识
+const KEY_CONTEXT: &[u8] = b"desktop_pet secrets v1";
+
+const NONCE_LEN: usize = 12;
+
+/// API 密钥：序列化时只输出占位符，调试输出也不会打印
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    /// 真实的密钥，只用于发起请求
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 前端原样提交回来的占位符
+    pub fn is_mask(&self) -> bool {
+        self.0 == API_KEY_MASK
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        Self(key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        Self(key.to_string())
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shown = if self.is_empty() { "" } else { API_KEY_MASK };
+        write!(f, "ApiKey({:?})", shown)
+    }
+}
+
+impl Serialize for ApiKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(if self.is_empty() { "" } else { API_KEY_MASK })
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+impl AIConfig {
+    /// 前端提交回占位符时沿用 `current` 中的真实密钥
+    pub fn keep_api_key(&mut self, current: &AIConfig) {
+        if self.api_key.is_mask() {
+            self.api_key = current.api_key.clone();
+        }
+    }
+}
+
+/// 加密文件中保存的内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Secrets {
+    api_key: String,
+}
+
+/// 配置文件对应的加密文件
+pub fn secrets_path(config_path: &Path) -> PathBuf {
+    config_path.with_extension("secrets")
+}
+
+fn machine_key_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(MACHINE_KEY_FILE)
+}
+
+/// 读取本机密钥文件，不存在时随机生成（仅当前用户可读）
+async fn machine_secret(config_path: &Path) -> std::io::Result<Vec<u8>> {
+    let path = machine_key_path(config_path);
+    match fs::read(&path).await {
+        Ok(secret) if !secret.is_empty() => return Ok(secret),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    let secret = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(&path).await?;
+    file.write_all(&secret).await?;
+    file.sync_all().await?;
+    Ok(secret)
+}
+
+async fn cipher(config_path: &Path) -> Result<ChaCha20Poly1305, ConfigError> {
+    let secret = machine_secret(config_path).await.map_err(ConfigError::IoError)?;
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_CONTEXT);
+    hasher.update(&secret);
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&hasher.finalize())))
+}
+
+fn decrypt(cipher: &ChaCha20Poly1305, content: &str) -> Result<Secrets, String> {
+    let encoded = content.trim().strip_prefix(FORMAT_PREFIX).ok_or("未知的格式")?;
+    let bytes = BASE64.decode(encoded).map_err(|e| e.to_string())?;
+    if bytes.len() < NONCE_LEN {
+        return Err("内容过短".to_string());
+    }
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "无法解密，本机密钥可能已更换".to_string())?;
+    serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
+}
+
+/// 读取配置文件对应的 API 密钥；没有加密文件时为空，文件损坏或本机密钥已更换导致无法解密时报错
+pub async fn load_api_key(config_path: &Path) -> Result<ApiKey, ConfigError> {
+    let content = match fs::read_to_string(secrets_path(config_path)).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ApiKey::default()),
+        Err(e) => return Err(ConfigError::IoError(e)),
+    };
+    decrypt(&cipher(config_path).await?, &content)
+        .map(|secrets| ApiKey(secrets.api_key))
+        .map_err(|e| ConfigError::SecretsError(format!("无法读取保存的 API 密钥: {}", e)))
+}
+
+/// 加密保存 API 密钥；与已保存的相同时不重写文件
+///
+/// 无法解密的旧文件在填入新密钥前保留，找回本机密钥后仍可读取。
+pub async fn save_api_key(config_path: &Path, api_key: &ApiKey) -> Result<(), ConfigError> {
+    match load_api_key(config_path).await {
+        Ok(saved) if saved == *api_key => return Ok(()),
+        Err(ConfigError::SecretsError(_)) if api_key.is_empty() => return Ok(()),
+        Ok(_) | Err(ConfigError::SecretsError(_)) => {}
+        Err(e) => return Err(e),
+    }
+    let cipher = cipher(config_path).await?;
+    let secrets = Secrets { api_key: api_key.expose().to_string() };
+    let plaintext = serde_json::to_vec(&secrets).map_err(|e| ConfigError::SecretsError(e.to_string()))?;
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| ConfigError::SecretsError("加密失败".to_string()))?;
+    let mut bytes = nonce.to_vec();
+    bytes.extend_from_slice(&ciphertext);
+    let content = format!("{}{}\n", FORMAT_PREFIX, BASE64.encode(bytes));
+    storage::write_atomic_without_backup(&secrets_path(config_path), &content)
+        .await
+        .map_err(ConfigError::IoError)
+}
+
+/// 清除备份中遗留的明文密钥
+pub async fn scrub_backups(config_path: &Path) {
+    for backup in storage::backup_paths(config_path) {
+        let Ok(content) = fs::read_to_string(&backup).await else {
+            continue;
+        };
+        let Ok(mut document) = toml::from_str::<toml::Table>(&content) else {
+            continue;
+        };
+        let Some(ai) = document.get_mut("ai").and_then(toml::Value::as_table_mut) else {
+            continue;
+        };
+        if ai.get("api_key").and_then(toml::Value::as_str).is_some_and(|key| !key.is_empty()) {
+            ai.insert("api_key".to_string(), toml::Value::String(String::new()));
+            if let Ok(content) = toml::to_string_pretty(&document) {
+                let _ = fs::write(&backup, content).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("desktop_pet_test_secrets_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("config.toml")
+    }
+
+    #[test]
+    fn test_api_key_is_masked_when_serialized() {
+        let key = ApiKey::from("sk-secret");
+        assert_eq!(serde_json::to_string(&key).unwrap(), format!("\"{}\"", API_KEY_MASK));
+        assert!(!format!("{:?}", key).contains("sk-secret"));
+        assert_eq!(serde_json::to_string(&ApiKey::default()).unwrap(), "\"\"");
+        assert!(serde_json::from_str::<ApiKey>(&format!("\"{}\"", API_KEY_MASK)).unwrap().is_mask());
+    }
+
+    #[tokio::test]
+    async fn test_api_key_round_trip_is_encrypted() {
+        let path = temp_config("round_trip");
+        assert!(load_api_key(&path).await.unwrap().is_empty());
+
+        save_api_key(&path, &ApiKey::from("sk-secret")).await.unwrap();
+        let stored = std::fs::read_to_string(secrets_path(&path)).unwrap();
+        assert!(stored.starts_with(FORMAT_PREFIX));
+        assert!(!stored.contains("sk-secret"));
+        assert_eq!(load_api_key(&path).await.unwrap().expose(), "sk-secret");
+
+        // 相同的密钥不重写文件
+        save_api_key(&path, &ApiKey::from("sk-secret")).await.unwrap();
+        assert_eq!(std::fs::read_to_string(secrets_path(&path)).unwrap(), stored);
+
+        // 本机密钥更换后无法解密时报错；保存空密钥不覆盖原文件，填入新密钥后才覆盖
+        std::fs::write(machine_key_path(&path), [7u8; 32]).unwrap();
+        assert!(matches!(load_api_key(&path).await, Err(ConfigError::SecretsError(_))));
+        save_api_key(&path, &ApiKey::default()).await.unwrap();
+        assert_eq!(std::fs::read_to_string(secrets_path(&path)).unwrap(), stored);
+        save_api_key(&path, &ApiKey::from("sk-new")).await.unwrap();
+        assert_eq!(load_api_key(&path).await.unwrap().expose(), "sk-new");
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
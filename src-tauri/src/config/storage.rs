@@ -0,0 +1,151 @@
+//! 配置文件的落盘
+//!
+//! 写入先落到同目录的临时文件并 fsync，再原子地重命名覆盖 config.toml，写到一半崩溃不会留下截断的配置。
+//! 覆盖前把旧文件轮转进 `config.toml.bak1`（最新）… `config.toml.bak<N>`，供读取失败时恢复。
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use super::FieldError;
+
+/// 保留的备份份数
+pub const BACKUP_COUNT: usize = 5;
+
+/// 配置文件损坏或含有无效字段时的恢复情况，启动后报告给前端
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigRecovery {
+    /// 展示给用户的说明
+    pub message: String,
+    /// 用于恢复的备份文件名，`None` 表示没有从备份恢复
+    pub restored_backup: Option<String>,
+    /// 损坏的配置文件被保留为该文件名；文件只是含有无效字段时为 `None`
+    pub corrupt_file: Option<String>,
+    /// 取值无效、已恢复为默认值的字段
+    pub invalid_fields: Vec<FieldError>,
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// 第 `index` 份备份（从 1 开始，1 为最新）
+pub fn backup_path(path: &Path, index: usize) -> PathBuf {
+    with_suffix(path, &format!(".bak{}", index))
+}
+
+/// 全部备份，从新到旧
+pub fn backup_paths(path: &Path) -> Vec<PathBuf> {
+    (1..=BACKUP_COUNT).map(|index| backup_path(path, index)).filter(|backup| backup.is_file()).collect()
+}
+
+/// 把当前文件轮转进备份；内容没有变化时不产生新的备份
+async fn rotate_backups(path: &Path, content: &str) -> std::io::Result<()> {
+    match fs::read(path).await {
+        Ok(current) if current != content.as_bytes() => {}
+        Ok(_) => return Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    }
+    for index in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, index);
+        if from.is_file() {
+            fs::rename(&from, backup_path(path, index + 1)).await?;
+        }
+    }
+    fs::copy(path, backup_path(path, 1)).await?;
+    Ok(())
+}
+
+/// 写入同目录的临时文件并 fsync
+async fn write_temp(path: &Path, content: &str) -> std::io::Result<PathBuf> {
+    let temp = with_suffix(path, ".tmp");
+    let mut file = fs::File::create(&temp).await?;
+    file.write_all(content.as_bytes()).await?;
+    file.sync_all().await?;
+    Ok(temp)
+}
+
+/// 用临时文件原子地替换目标文件
+async fn replace_with(temp: &Path, path: &Path) -> std::io::Result<()> {
+    fs::rename(temp, path).await?;
+
+    // 让重命名本身也落盘；部分平台不支持打开目录，忽略失败
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = fs::File::open(parent).await {
+            let _ = dir.sync_all().await;
+        }
+    }
+    Ok(())
+}
+
+/// 临时文件 + fsync + 原子重命名写入，覆盖前轮转备份
+pub async fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    let temp = write_temp(path, content).await?;
+    rotate_backups(path, content).await?;
+    replace_with(&temp, path).await
+}
+
+/// 原子写入但不保留备份，用于可以随时重建的小文件
+pub async fn write_atomic_without_backup(path: &Path, content: &str) -> std::io::Result<()> {
+    let temp = write_temp(path, content).await?;
+    replace_with(&temp, path).await
+}
+
+/// 把文件连同它的备份一起移动到 `to`
+pub async fn move_with_backups(from: &Path, to: &Path) -> std::io::Result<()> {
+    for index in 1..=BACKUP_COUNT {
+        let backup = backup_path(from, index);
+        if backup.is_file() {
+            fs::rename(&backup, backup_path(to, index)).await?;
+        }
+    }
+    fs::rename(from, to).await
+}
+
+/// 删除文件连同它的备份
+pub async fn remove_with_backups(path: &Path) -> std::io::Result<()> {
+    for backup in backup_paths(path) {
+        fs::remove_file(backup).await?;
+    }
+    fs::remove_file(path).await
+}
+
+/// 把损坏的文件移到一旁保留，返回新文件名
+pub async fn set_aside(path: &Path, millis: i64) -> std::io::Result<String> {
+    let corrupt = with_suffix(path, &format!(".corrupt-{}", millis));
+    fs::rename(path, &corrupt).await?;
+    Ok(corrupt.file_name().unwrap_or_default().to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_atomic_rotates_backups() {
+        let dir = std::env::temp_dir().join("desktop_pet_test_storage");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+
+        for version in 0..BACKUP_COUNT + 2 {
+            write_atomic(&path, &format!("v = {}\n", version)).await.unwrap();
+        }
+        // 内容相同的写入不产生备份
+        write_atomic(&path, &format!("v = {}\n", BACKUP_COUNT + 1)).await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), format!("v = {}\n", BACKUP_COUNT + 1));
+        let backups = backup_paths(&path);
+        assert_eq!(backups.len(), BACKUP_COUNT);
+        assert_eq!(std::fs::read_to_string(&backups[0]).unwrap(), format!("v = {}\n", BACKUP_COUNT));
+        assert_eq!(std::fs::read_to_string(&backups[BACKUP_COUNT - 1]).unwrap(), "v = 1\n");
+        assert!(!with_suffix(&path, ".tmp").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
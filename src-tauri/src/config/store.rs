@@ -0,0 +1,625 @@
+//! 内存中的配置
+//!
+//! `ConfigStore` 持有唯一可信的 `AppConfig`，读取不再访问磁盘；各窗口只修改自己关心的字段，
+//! 不会再用整份旧配置覆盖其他窗口的修改。变更由后台写入任务去抖后通过 `ConfigManager` 落盘。
+//! 每次落盘成功或检测到配置文件被外部修改后，广播一条只含变化分区的 `ConfigChanged`。
+//! 切换配置档案时改为读写另一个文件，同样广播变化的分区。
+//! 环境变量与命令行的覆盖只叠加在读取到的配置上：内部保存并写盘的是文件层的配置，
+//! `get`/`read` 返回叠加覆盖后实际使用的配置，变更事件携带的也是叠加后的取值。
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs;
+use tokio::sync::{broadcast, Mutex, Notify, RwLock, RwLockReadGuard};
+
+use super::change::{ChangeSource, ConfigChanged};
+use super::{AppConfig, ConfigError, ConfigLayer, ConfigManager, ConfigRecovery, Overrides, PetConfig};
+
+/// 最后一次修改后等待多久再写盘，窗口拖动等连续修改只写一次
+pub const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 检查配置文件是否被外部修改的间隔
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 可以局部修改的配置分区
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSection {
+    Appearance,
+    AI,
+    Window,
+    Character,
+    Pets,
+    Behavior,
+    Hotkeys,
+}
+
+/// 把 JSON 对象中的字段合并进 `current`，`null` 把可选字段置空
+///
+/// 不认识的字段和类型不符的值都会报错，不会被静默忽略。
+pub fn merge_patch<T: Serialize + DeserializeOwned>(current: &T, patch: serde_json::Value) -> Result<T, ConfigError> {
+    let serde_json::Value::Object(patch) = patch else {
+        return Err(ConfigError::InvalidPatch("补丁必须是 JSON 对象".to_string()));
+    };
+    let mut value = serde_json::to_value(current).map_err(|e| ConfigError::InvalidPatch(e.to_string()))?;
+    let fields = value.as_object_mut().ok_or_else(|| ConfigError::InvalidPatch("配置分区不是对象".to_string()))?;
+    for (key, field) in patch {
+        if !fields.contains_key(&key) {
+            return Err(ConfigError::InvalidPatch(format!("未知字段 {}", key)));
+        }
+        fields.insert(key, field);
+    }
+    serde_json::from_value(value).map_err(|e| ConfigError::InvalidPatch(e.to_string()))
+}
+
+/// 把补丁合并进配置的某个分区；提交回来的密钥占位符沿用原来的密钥
+pub(super) fn patch_section(config: &mut AppConfig, section: ConfigSection, patch: serde_json::Value) -> Result<(), ConfigError> {
+    match section {
+        ConfigSection::Appearance => config.appearance = merge_patch(&config.appearance, patch)?,
+        ConfigSection::AI => {
+            let mut ai = merge_patch(&config.ai, patch)?;
+            ai.keep_api_key(&config.ai);
+            config.ai = ai;
+        }
+        ConfigSection::Window => config.window = merge_patch(&config.window, patch)?,
+        ConfigSection::Character => config.character = merge_patch(&config.character, patch)?,
+        ConfigSection::Pets => config.pets = merge_pets(&config.pets, patch)?,
+        ConfigSection::Behavior => config.behavior = merge_patch(&config.behavior, patch)?,
+        ConfigSection::Hotkeys => config.hotkeys = merge_patch(&config.hotkeys, patch)?,
+    }
+    Ok(())
+}
+
+/// 用 `source` 中的分区替换 `config` 中的同一分区
+fn copy_section(config: &mut AppConfig, source: &AppConfig, section: ConfigSection) {
+    match section {
+        ConfigSection::Appearance => config.appearance = source.appearance.clone(),
+        ConfigSection::AI => config.ai = source.ai.clone(),
+        ConfigSection::Window => config.window = source.window.clone(),
+        ConfigSection::Character => config.character = source.character.clone(),
+        ConfigSection::Pets => config.pets = source.pets.clone(),
+        ConfigSection::Behavior => config.behavior = source.behavior.clone(),
+        ConfigSection::Hotkeys => config.hotkeys = source.hotkeys.clone(),
+    }
+}
+
+/// 按桌宠 id 合并补丁：已有的桌宠合并字段，新的 id 在默认值上合并，`null` 删除该桌宠
+fn merge_pets(current: &BTreeMap<String, PetConfig>, patch: serde_json::Value) -> Result<BTreeMap<String, PetConfig>, ConfigError> {
+    let serde_json::Value::Object(patch) = patch else {
+        return Err(ConfigError::InvalidPatch("补丁必须是 JSON 对象".to_string()));
+    };
+    let mut pets = current.clone();
+    for (id, pet_patch) in patch {
+        if pet_patch.is_null() {
+            pets.remove(&id);
+            continue;
+        }
+        let pet = merge_patch(&pets.get(&id).cloned().unwrap_or_default(), pet_patch)?;
+        pets.insert(id, pet);
+    }
+    Ok(pets)
+}
+
+// 配置存储
+pub struct ConfigStore {
+    /// 文件层的配置，写盘的内容
+    config: RwLock<AppConfig>,
+    /// 叠加覆盖后实际使用的配置，随 `config` 一起更新
+    effective: RwLock<AppConfig>,
+    /// 环境变量与命令行的覆盖
+    overrides: Overrides,
+    /// 当前配置档案的文件
+    manager: RwLock<ConfigManager>,
+    /// 有尚未落盘的修改
+    dirty: AtomicBool,
+    /// 唤醒后台写入任务
+    changed: Notify,
+    /// 最后一次写盘或从磁盘读到的配置；同时保证写盘与重新读取不会交错
+    saved: Mutex<AppConfig>,
+    /// 变更事件
+    changes: broadcast::Sender<ConfigChanged>,
+    debounce: Duration,
+}
+
+impl ConfigStore {
+    /// 从磁盘读取配置并叠加覆盖；叠加后的配置未通过校验时报错
+    pub async fn open(manager: ConfigManager, overrides: Overrides) -> Result<Self, ConfigError> {
+        let config = manager.load().await?;
+        overrides.apply(&config).validate().map_err(ConfigError::Invalid)?;
+        Ok(Self::with_config(manager, config, overrides, SAVE_DEBOUNCE))
+    }
+
+    fn with_config(manager: ConfigManager, config: AppConfig, overrides: Overrides, debounce: Duration) -> Self {
+        Self {
+            effective: RwLock::new(overrides.apply(&config)),
+            overrides,
+            config: RwLock::new(config.clone()),
+            manager: RwLock::new(manager),
+            dirty: AtomicBool::new(false),
+            changed: Notify::new(),
+            saved: Mutex::new(config),
+            changes: broadcast::channel(16).0,
+            debounce,
+        }
+    }
+
+    /// 取出当前配置文件尚未报告的损坏恢复情况
+    pub async fn take_recovery(&self) -> Option<ConfigRecovery> {
+        self.manager.read().await.take_recovery()
+    }
+
+    /// 订阅变更事件
+    pub fn subscribe(&self) -> broadcast::Receiver<ConfigChanged> {
+        self.changes.subscribe()
+    }
+
+    /// 当前配置的副本
+    pub async fn get(&self) -> AppConfig {
+        self.effective.read().await.clone()
+    }
+
+    pub async fn read(&self) -> RwLockReadGuard<'_, AppConfig> {
+        self.effective.read().await
+    }
+
+    /// 文件层的配置，不含环境变量与命令行的覆盖
+    pub async fn file_config(&self) -> AppConfig {
+        self.config.read().await.clone()
+    }
+
+    /// 每个字段的取值来自哪一层
+    pub async fn sources(&self) -> BTreeMap<String, ConfigLayer> {
+        self.overrides.sources(&*self.config.read().await)
+    }
+
+    /// 修改文件层的配置后重新叠加覆盖
+    async fn set(&self, config: &mut AppConfig, updated: AppConfig) {
+        *self.effective.write().await = self.overrides.apply(&updated);
+        *config = updated;
+    }
+
+    /// 修改配置并安排写盘，返回闭包的结果
+    pub async fn update<R>(&self, f: impl FnOnce(&mut AppConfig) -> R) -> R {
+        let mut config = self.config.write().await;
+        let mut updated = config.clone();
+        let result = f(&mut updated);
+        self.set(&mut config, updated).await;
+        drop(config);
+        self.mark_dirty();
+        result
+    }
+
+    /// 整份替换配置；未通过校验时不做任何修改
+    pub async fn replace(&self, mut updated: AppConfig) -> Result<(), ConfigError> {
+        let mut config = self.config.write().await;
+        updated.ai.keep_api_key(&config.ai);
+        self.overrides.restore_file_values(&mut updated, &config);
+        updated.validate().map_err(ConfigError::Invalid)?;
+        self.set(&mut config, updated).await;
+        drop(config);
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// 局部修改某个分区，返回修改后实际使用的完整配置；修改后未通过校验时不做任何修改
+    pub async fn patch(&self, section: ConfigSection, patch: serde_json::Value) -> Result<AppConfig, ConfigError> {
+        let mut config = self.config.write().await;
+        let mut updated = config.clone();
+        patch_section(&mut updated, section, patch)?;
+        self.overrides.restore_file_values(&mut updated, &config);
+        updated.validate().map_err(ConfigError::Invalid)?;
+        self.set(&mut config, updated).await;
+        drop(config);
+        self.mark_dirty();
+        Ok(self.get().await)
+    }
+
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::SeqCst);
+        self.changed.notify_one();
+    }
+
+    /// 广播变更；没有订阅者时丢弃
+    fn publish(&self, change: ConfigChanged) {
+        let _ = self.changes.send(change);
+    }
+
+    /// 立即写盘；没有修改时什么也不做
+    pub async fn flush(&self) -> Result<(), ConfigError> {
+        let mut saved = self.saved.lock().await;
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let snapshot = self.config.read().await.clone();
+        if let Err(e) = self.manager.read().await.save(&snapshot).await {
+            self.dirty.store(true, Ordering::SeqCst);
+            return Err(e);
+        }
+        let (before, after) = (self.overrides.apply(&saved), self.overrides.apply(&snapshot));
+        if let Some(change) = ConfigChanged::diff(&before, &after, ChangeSource::Save) {
+            self.publish(change);
+        }
+        *saved = snapshot;
+        Ok(())
+    }
+
+    /// 重新读取配置文件，把被外部修改的分区合并进内存中的配置并返回变更
+    ///
+    /// 自身写入的文件与最后一次落盘的配置相同，不会产生变更；其他分区中尚未落盘的修改保留。
+    /// 取值无效的文件不会被应用。
+    pub async fn reload(&self) -> Result<Option<ConfigChanged>, ConfigError> {
+        let mut saved = self.saved.lock().await;
+        let manager = self.manager.read().await;
+        let content = match fs::read_to_string(manager.config_path()).await {
+            Ok(content) => content,
+            // 文件被删除时等待下一次写盘重新生成
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(ConfigError::IoError(e)),
+        };
+        let (external, rewrite) = manager.parse(&content).await?;
+        drop(manager);
+        external.validate().map_err(ConfigError::Invalid)?;
+        // 外部写入的明文密钥移入加密文件
+        if rewrite {
+            self.mark_dirty();
+        }
+        let Some(edited) = ConfigChanged::diff(&saved, &external, ChangeSource::External) else {
+            return Ok(None);
+        };
+
+        let mut config = self.config.write().await;
+        let mut updated = config.clone();
+        for section in &edited.sections {
+            copy_section(&mut updated, &external, *section);
+        }
+        let before = self.effective.read().await.clone();
+        self.set(&mut config, updated).await;
+        drop(config);
+        *saved = external;
+        // 被覆盖的字段在文件中的修改不影响实际使用的配置
+        let change = ConfigChanged::diff(&before, &*self.effective.read().await, ChangeSource::External);
+        if let Some(change) = &change {
+            self.publish(change.clone());
+        }
+        Ok(change)
+    }
+
+    /// 切换到另一个配置文件：先把当前修改写入原文件，再读取新文件，广播变化的分区
+    ///
+    /// 窗口位置与配置档案无关，沿用当前值，并在下一次写盘时保存进新文件。
+    pub async fn switch_to(&self, manager: ConfigManager) -> Result<(), ConfigError> {
+        self.flush().await?;
+        let mut saved = self.saved.lock().await;
+        let loaded = manager.load().await?;
+
+        let mut config = self.config.write().await;
+        let mut switched = loaded.clone();
+        switched.window = config.window.clone();
+        let before = self.effective.read().await.clone();
+        let change = ConfigChanged::diff(&before, &self.overrides.apply(&switched), ChangeSource::Profile);
+        let window_differs = switched != loaded;
+        self.set(&mut config, switched).await;
+        drop(config);
+
+        *self.manager.write().await = manager;
+        *saved = loaded;
+        if window_differs {
+            self.mark_dirty();
+        }
+        if let Some(change) = change {
+            self.publish(change);
+        }
+        Ok(())
+    }
+
+    /// 当前文件被移动到别处后（如配置档案改名）改为读写新位置，不重新读取
+    pub async fn relocate(&self, manager: ConfigManager) {
+        let _saved = self.saved.lock().await;
+        *self.manager.write().await = manager;
+    }
+
+    /// 后台写入任务：每次修改后等待 `debounce`，把期间的所有修改一次写盘
+    pub async fn run_writer(self: Arc<Self>) {
+        loop {
+            self.changed.notified().await;
+            tokio::time::sleep(self.debounce).await;
+            if let Err(e) = self.flush().await {
+                eprintln!("保存配置失败: {}", e);
+            }
+        }
+    }
+
+    /// 后台监视任务：配置文件的修改时间变化后重新读取
+    ///
+    /// 编辑器写到一半的文件无法解析时不应用，把错误交给 `on_error`，等下一次修改再读取。
+    pub async fn run_watcher(
+        self: Arc<Self>,
+        interval: Duration,
+        on_error: impl Fn(ConfigError, &Path) + Send + 'static,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        let mut last_modified = None;
+        loop {
+            ticker.tick().await;
+            let path = self.manager.read().await.config_path().to_path_buf();
+            let modified = fs::metadata(&path)
+                .await
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+            if let Err(e) = self.reload().await {
+                eprintln!("重新读取配置失败: {}", e);
+                on_error(e, &path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_config, storage, AIConfig, MAIN_PET};
+    use serde_json::json;
+
+    fn store(name: &str, debounce: Duration) -> (Arc<ConfigStore>, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("desktop_pet_test_store_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+        let manager = ConfigManager::with_path(path.clone());
+        (Arc::new(ConfigStore::with_config(manager, AppConfig::default(), Overrides::default(), debounce)), path)
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let ai = AIConfig { system_prompt: Some("你叫灵灵".to_string()), ..Default::default() };
+        let patched = merge_patch(&ai, json!({"model": "gpt-4o", "system_prompt": null})).unwrap();
+        assert_eq!(patched.model, "gpt-4o");
+        assert_eq!(patched.system_prompt, None);
+        assert_eq!(patched.base_url, ai.base_url);
+
+        assert!(matches!(merge_patch(&ai, json!({"modle": "x"})), Err(ConfigError::InvalidPatch(_))));
+        assert!(matches!(merge_patch(&ai, json!({"temperature": "hot"})), Err(ConfigError::InvalidPatch(_))));
+        assert!(matches!(merge_patch(&ai, json!(["model"])), Err(ConfigError::InvalidPatch(_))));
+    }
+
+    #[tokio::test]
+    async fn test_patches_from_different_windows_do_not_overwrite_each_other() {
+        let (store, _) = store("sections", SAVE_DEBOUNCE);
+        // 设置窗口改外观，同时主窗口改位置
+        store.patch(ConfigSection::Appearance, json!({"pet_size": 200})).await.unwrap();
+        store.patch(ConfigSection::Window, json!({"main_window_x": 10.0})).await.unwrap();
+
+        let config = store.get().await;
+        assert_eq!(config.appearance.pet_size, 200);
+        assert_eq!(config.window.main_window_x, 10.0);
+
+        // 非法补丁不修改配置
+        assert!(store.patch(ConfigSection::Appearance, json!({"pet_size": "big"})).await.is_err());
+        assert!(matches!(
+            store.patch(ConfigSection::Appearance, json!({"pet_size": 200, "pet_opacity": 7.0})).await,
+            Err(ConfigError::Invalid(_))
+        ));
+        assert_eq!(store.get().await.appearance.pet_size, 200);
+        assert_eq!(store.get().await.appearance.pet_opacity, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_writer_debounces_changes() {
+        let (store, path) = store("writer", Duration::from_millis(50));
+        let writer = tokio::spawn(store.clone().run_writer());
+
+        for x in 1..=5 {
+            store.patch(ConfigSection::Window, json!({"main_window_x": x as f64})).await.unwrap();
+        }
+        assert!(!path.exists());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let (saved, _) = parse_config(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.window.main_window_x, 5.0);
+        // 连续修改只写了一次，没有产生备份
+        assert!(storage::backup_paths(&path).is_empty());
+
+        writer.abort();
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_flush_writes_immediately() {
+        let (store, path) = store("flush", SAVE_DEBOUNCE);
+        store.flush().await.unwrap();
+        assert!(!path.exists());
+
+        store.update(|config| config.ai.model = "gpt-4o".to_string()).await;
+        store.flush().await.unwrap();
+        let (saved, _) = parse_config(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.ai.model, "gpt-4o");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_pets_round_trip_through_config_manager() {
+        let (store, path) = store("pets", SAVE_DEBOUNCE);
+        store.patch(ConfigSection::Pets, json!({"pet-2": {"character": "lingling", "x": 300.0}})).await.unwrap();
+        store.patch(ConfigSection::Pets, json!({"pet-3": {"opacity": 0.5}})).await.unwrap();
+        // 只改一只的位置，其余字段与其他桌宠保持不变
+        store.patch(ConfigSection::Pets, json!({"pet-2": {"y": 80.0, "monitor": "DP-1", "scale": 1.5}})).await.unwrap();
+        assert!(matches!(
+            store.patch(ConfigSection::Pets, json!({"pet-2": {"size": 5}})).await,
+            Err(ConfigError::Invalid(_))
+        ));
+        store.flush().await.unwrap();
+
+        let loaded = ConfigManager::with_path(path.clone()).load().await.unwrap();
+        assert_eq!(loaded.pets, store.get().await.pets);
+        let pet = &loaded.pets["pet-2"];
+        assert_eq!((pet.character.as_str(), pet.x, pet.y), ("lingling", 300.0, 80.0));
+        assert_eq!((pet.monitor.as_deref(), pet.scale), (Some("DP-1"), Some(1.5)));
+        assert_eq!(loaded.pets["pet-3"], PetConfig { opacity: 0.5, ..PetConfig::default() });
+        assert_eq!(loaded.pet_character("pet-2"), Some("lingling"));
+        assert_eq!(loaded.pet_character(MAIN_PET), Some(loaded.character.active.as_str()));
+
+        store.patch(ConfigSection::Pets, json!({"pet-3": null})).await.unwrap();
+        store.flush().await.unwrap();
+        let loaded = ConfigManager::with_path(path.clone()).load().await.unwrap();
+        assert_eq!(loaded.pets.keys().collect::<Vec<_>>(), vec!["pet-2"]);
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_masked_api_key_is_kept() {
+        let (store, _) = store("api_key", SAVE_DEBOUNCE);
+        store.patch(ConfigSection::AI, json!({"api_key": "sk-secret"})).await.unwrap();
+
+        // 前端拿到的是占位符，原样提交回来时不修改密钥
+        let shown = serde_json::to_value(store.get().await).unwrap();
+        assert_eq!(shown["ai"]["api_key"], crate::config::API_KEY_MASK);
+        store.patch(ConfigSection::AI, shown["ai"].clone()).await.unwrap();
+        let mut config: AppConfig = serde_json::from_value(shown).unwrap();
+        config.ai.model = "gpt-4o".to_string();
+        store.replace(config).await.unwrap();
+        assert_eq!(store.get().await.ai.api_key.expose(), "sk-secret");
+        assert_eq!(store.get().await.ai.model, "gpt-4o");
+
+        store.patch(ConfigSection::AI, json!({"api_key": ""})).await.unwrap();
+        assert!(store.get().await.ai.api_key.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_save_publishes_changed_sections() {
+        let (store, path) = store("publish", SAVE_DEBOUNCE);
+        let mut changes = store.subscribe();
+
+        store.patch(ConfigSection::Appearance, json!({"pet_size": 200})).await.unwrap();
+        // 改回原值的修改落盘后没有变化，不产生事件
+        store.patch(ConfigSection::Window, json!({"main_window_x": 10.0})).await.unwrap();
+        store.patch(ConfigSection::Window, json!({"main_window_x": 400.0})).await.unwrap();
+        store.flush().await.unwrap();
+
+        let change = changes.try_recv().unwrap();
+        assert_eq!(change.source, ChangeSource::Save);
+        assert_eq!(change.sections, vec![ConfigSection::Appearance]);
+        assert_eq!(change.appearance.unwrap().pet_size, 200);
+        assert!(changes.try_recv().is_err());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_reload_picks_up_external_edits() {
+        let (store, path) = store("reload", SAVE_DEBOUNCE);
+        store.update(|config| config.ai.model = "gpt-4o".to_string()).await;
+        store.flush().await.unwrap();
+        let mut changes = store.subscribe();
+
+        // 自身写入的文件不算外部修改
+        assert_eq!(store.reload().await.unwrap(), None);
+
+        // 尚未落盘的窗口位置在外部修改后保留
+        store.patch(ConfigSection::Window, json!({"main_window_x": 10.0})).await.unwrap();
+        let content = std::fs::read_to_string(&path).unwrap().replace("pet_size = 150", "pet_size = 220");
+        std::fs::write(&path, content).unwrap();
+
+        let change = store.reload().await.unwrap().unwrap();
+        assert_eq!(change.source, ChangeSource::External);
+        assert_eq!(change.sections, vec![ConfigSection::Appearance]);
+        assert_eq!(changes.try_recv().unwrap(), change);
+
+        let config = store.get().await;
+        assert_eq!(config.appearance.pet_size, 220);
+        assert_eq!(config.ai.model, "gpt-4o");
+        assert_eq!(config.window.main_window_x, 10.0);
+
+        // 无法解析或取值无效的文件不影响内存中的配置
+        std::fs::write(&path, "[appearance\n").unwrap();
+        assert!(store.reload().await.is_err());
+        std::fs::write(&path, "[appearance]\npet_size = -5\n").unwrap();
+        assert!(matches!(store.reload().await, Err(ConfigError::Invalid(_))));
+        assert_eq!(store.get().await.appearance.pet_size, 220);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_reload_picks_up_edited_pets() {
+        let (store, path) = store("reload_pets", SAVE_DEBOUNCE);
+        store.patch(ConfigSection::Pets, json!({"pet-2": {"x": 300.0}, "pet-3": {}})).await.unwrap();
+        store.flush().await.unwrap();
+
+        // 手动修改一只桌宠并删除另一只
+        let mut edited = ConfigManager::with_path(path.clone()).load().await.unwrap();
+        edited.pets.get_mut("pet-2").unwrap().x = 640.0;
+        edited.pets.remove("pet-3");
+        std::fs::write(&path, toml::to_string(&edited).unwrap()).unwrap();
+
+        let change = store.reload().await.unwrap().unwrap();
+        assert_eq!(change.sections, vec![ConfigSection::Pets]);
+        let pets = store.get().await.pets;
+        assert_eq!(pets.keys().collect::<Vec<_>>(), vec!["pet-2"]);
+        assert_eq!(pets["pet-2"].x, 640.0);
+
+        // 之后写盘不会把修改覆盖回去
+        store.patch(ConfigSection::Appearance, json!({"pet_size": 200})).await.unwrap();
+        store.flush().await.unwrap();
+        let loaded = ConfigManager::with_path(path.clone()).load().await.unwrap();
+        assert_eq!(loaded.pets, pets);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_reload_picks_up_every_edited_section() {
+        let (store, path) = store("reload_sections", SAVE_DEBOUNCE);
+        store.flush().await.unwrap();
+
+        let mut edited = ConfigManager::with_path(path.clone()).load().await.unwrap();
+        edited.behavior.ai_paused = true;
+        edited.behavior.pets_visible = false;
+        edited.hotkeys.focus_chat = "Alt+Space".to_string();
+        std::fs::write(&path, toml::to_string(&edited).unwrap()).unwrap();
+
+        let change = store.reload().await.unwrap().unwrap();
+        assert_eq!(change.sections, vec![ConfigSection::Behavior, ConfigSection::Hotkeys]);
+        assert_eq!(change.hotkeys.as_ref(), Some(&edited.hotkeys));
+        let config = store.get().await;
+        assert_eq!(config.behavior, edited.behavior);
+        assert_eq!(config.hotkeys.focus_chat, "Alt+Space");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_overrides_are_applied_but_not_saved() {
+        let dir = std::env::temp_dir().join("desktop_pet_test_store_overrides");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("config.toml");
+        let env = vec![("DESKTOP_PET_AI_MODEL".to_string(), "gpt-4o".to_string())];
+        let overrides = Overrides::parse(env, vec!["--set".to_string(), "appearance.pet_size=250".to_string()]).unwrap();
+        let store = ConfigStore::open(ConfigManager::with_path(path.clone()), overrides).await.unwrap();
+        let mut changes = store.subscribe();
+        assert_eq!(store.get().await.ai.model, "gpt-4o");
+        assert_eq!(store.sources().await["appearance.pet_size"], ConfigLayer::Cli);
+
+        // 前端提交回整个分区，覆盖值不写入文件，其他修改照常保存
+        let mut ai = serde_json::to_value(store.get().await.ai).unwrap();
+        ai["temperature"] = json!(1.0);
+        let config = store.patch(ConfigSection::AI, ai).await.unwrap();
+        assert_eq!((config.ai.model.as_str(), config.ai.temperature), ("gpt-4o", 1.0));
+        store.flush().await.unwrap();
+        let (saved, _) = parse_config(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!((saved.ai.model.as_str(), saved.ai.temperature), ("deepseek-chat", 1.0));
+        assert_eq!(saved.appearance.pet_size, 150);
+        // 事件携带实际使用的取值
+        assert_eq!(changes.try_recv().unwrap().ai.unwrap().model, "gpt-4o");
+
+        // 文件中被覆盖的字段改变时实际配置不变，不产生事件
+        let content = std::fs::read_to_string(&path).unwrap().replace("pet_size = 150", "pet_size = 220");
+        std::fs::write(&path, content).unwrap();
+        assert_eq!(store.reload().await.unwrap(), None);
+        assert_eq!(store.get().await.appearance.pet_size, 250);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
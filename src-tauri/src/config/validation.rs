@@ -0,0 +1,283 @@
+//! 配置的语义校验
+//!
+//! 反序列化只保证类型正确，这里检查取值是否有意义（范围、URL 格式等）。
+//! 每个分区各自校验，错误以 `分区.字段` 定位，前端据此在对应的表单项上显示。
+
+use serde::Serialize;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use super::{AIConfig, AppConfig, AppearanceConfig, CharacterConfig, PetConfig, WindowConfig, MAIN_PET};
+use crate::character;
+
+/// 宠物大小（像素）
+pub const PET_SIZE_RANGE: RangeInclusive<i32> = 100..=300;
+/// 宠物不透明度
+pub const PET_OPACITY_RANGE: RangeInclusive<f64> = 0.1..=1.0;
+/// 采样温度，各家接口允许的最大值为 2
+pub const TEMPERATURE_RANGE: RangeInclusive<f64> = 0.0..=2.0;
+/// 单次回复的最大 token 数
+pub const MAX_TOKENS_RANGE: RangeInclusive<i32> = 1..=200_000;
+/// 回放的历史对话轮数
+pub const HISTORY_TURNS_RANGE: RangeInclusive<usize> = 0..=200;
+/// 提示词 token 预算
+pub const CONTEXT_BUDGET_RANGE: RangeInclusive<usize> = 500..=2_000_000;
+/// 每次注入的相关记忆条数
+pub const MEMORY_RECALL_RANGE: RangeInclusive<usize> = 0..=50;
+
+/// 单个字段的校验错误
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    /// `分区.字段`，如 `appearance.pet_size`
+    pub field: String,
+    pub message: String,
+}
+
+/// 一份配置中的全部校验错误
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationErrors(pub Vec<FieldError>);
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let errors: Vec<String> = self.0.iter().map(|error| format!("{}: {}", error.field, error.message)).collect();
+        write!(f, "{}", errors.join("；"))
+    }
+}
+
+/// 收集某个分区的错误
+struct Checker {
+    section: String,
+    errors: Vec<FieldError>,
+}
+
+impl Checker {
+    fn new(section: impl Into<String>) -> Self {
+        Self { section: section.into(), errors: Vec::new() }
+    }
+
+    fn check(&mut self, field: &str, valid: bool, message: impl Into<String>) {
+        if !valid {
+            self.errors.push(FieldError { field: format!("{}.{}", self.section, field), message: message.into() });
+        }
+    }
+
+    fn range<T: PartialOrd + fmt::Display>(&mut self, field: &str, value: T, range: &RangeInclusive<T>) {
+        let message = format!("应在 {} 到 {} 之间", range.start(), range.end());
+        self.check(field, range.contains(&value), message);
+    }
+
+    fn finite(&mut self, field: &str, value: Option<f64>) {
+        self.check(field, value.is_none_or(f64::is_finite), "不是有效的数值");
+    }
+
+    fn positive(&mut self, field: &str, value: Option<f64>) {
+        self.check(field, value.is_none_or(|value| value.is_finite() && value > 0.0), "应大于 0");
+    }
+}
+
+impl AppearanceConfig {
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut checker = Checker::new("appearance");
+        checker.range("pet_size", self.pet_size, &PET_SIZE_RANGE);
+        checker.range("pet_opacity", self.pet_opacity, &PET_OPACITY_RANGE);
+        checker.errors
+    }
+}
+
+impl AIConfig {
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut checker = Checker::new("ai");
+        let base_url = reqwest::Url::parse(self.base_url.trim());
+        let is_http = base_url.is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host());
+        checker.check("base_url", is_http, "不是有效的 http(s) 地址");
+        checker.check("model", !self.model.trim().is_empty(), "不能为空");
+        checker.range("temperature", self.temperature, &TEMPERATURE_RANGE);
+        checker.range("max_tokens", self.max_tokens, &MAX_TOKENS_RANGE);
+        checker.range("history_turns", self.history_turns, &HISTORY_TURNS_RANGE);
+        checker.range("context_budget", self.context_budget, &CONTEXT_BUDGET_RANGE);
+        checker.range("memory_recall_limit", self.memory_recall_limit, &MEMORY_RECALL_RANGE);
+        checker.errors
+    }
+}
+
+impl WindowConfig {
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut checker = Checker::new("window");
+        checker.finite("main_window_x", Some(self.main_window_x));
+        checker.finite("main_window_y", Some(self.main_window_y));
+        checker.positive("main_window_scale", self.main_window_scale);
+        checker.finite("settings_window_x", self.settings_window_x);
+        checker.finite("settings_window_y", self.settings_window_y);
+        checker.positive("settings_window_width", self.settings_window_width);
+        checker.positive("settings_window_height", self.settings_window_height);
+        checker.errors
+    }
+}
+
+impl CharacterConfig {
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut checker = Checker::new("character");
+        checker.check("active", character::is_valid_id(&self.active), "不是有效的角色包 id");
+        checker.errors
+    }
+}
+
+impl PetConfig {
+    /// `id` 用于定位错误，如 `pets.pet-2.size`
+    pub fn validate(&self, id: &str) -> Vec<FieldError> {
+        let mut checker = Checker::new(format!("pets.{}", id));
+        checker.check("character", character::is_valid_id(&self.character), "不是有效的角色包 id");
+        checker.finite("x", Some(self.x));
+        checker.finite("y", Some(self.y));
+        checker.positive("scale", self.scale);
+        checker.range("size", self.size, &PET_SIZE_RANGE);
+        checker.range("opacity", self.opacity, &PET_OPACITY_RANGE);
+        checker.errors
+    }
+}
+
+/// 桌宠 id 同时用作窗口标签，与角色包 id 的规则相同，且不能与主桌宠重名
+pub fn is_valid_pet_id(id: &str) -> bool {
+    id != MAIN_PET && character::is_valid_id(id)
+}
+
+impl AppConfig {
+    /// 校验所有分区，一次性报告所有问题
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = self.appearance.validate();
+        errors.extend(self.ai.validate());
+        errors.extend(self.window.validate());
+        errors.extend(self.character.validate());
+        let mut pets = Checker::new("pets");
+        for (id, pet) in &self.pets {
+            pets.check(id, is_valid_pet_id(id), "桌宠 id 只能包含小写字母、数字、- 和 _，且不能是 main");
+            errors.extend(pet.validate(id));
+        }
+        errors.extend(pets.errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors(errors))
+        }
+    }
+
+    /// 把无效的字段恢复为默认值，返回被恢复的字段；id 无效的桌宠被移除
+    pub fn reset_invalid_fields(&mut self) -> Vec<FieldError> {
+        let Err(ValidationErrors(errors)) = self.validate() else {
+            return Vec::new();
+        };
+        self.pets.retain(|id, _| is_valid_pet_id(id));
+        let fixed = serde_json::to_value(&*self).and_then(|mut value| {
+            let defaults = serde_json::to_value(AppConfig::default())?;
+            let pet_defaults = serde_json::to_value(PetConfig::default())?;
+            for error in &errors {
+                let pointer = format!("/{}", error.field.replace('.', "/"));
+                // 其他桌宠的字段按 PetConfig 的默认值恢复，已移除的桌宠找不到对应字段
+                let default = match error.field.strip_prefix("pets.").map(|rest| rest.split_once('.')) {
+                    Some(Some((_, field))) => pet_defaults.get(field),
+                    Some(None) => None,
+                    None => defaults.pointer(&pointer),
+                };
+                if let (Some(field), Some(default)) = (value.pointer_mut(&pointer), default) {
+                    *field = default.clone();
+                }
+            }
+            serde_json::from_value::<AppConfig>(value)
+        });
+        match fixed {
+            Ok(mut fixed) => {
+                // 序列化时密钥只输出占位符，取回原值
+                fixed.ai.keep_api_key(&self.ai);
+                *self = fixed;
+            }
+            // 无法逐字段恢复时保留原来的配置，不用默认配置整个替换
+            Err(e) => eprintln!("恢复无效字段失败: {}", e),
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(config: &AppConfig) -> Vec<String> {
+        match config.validate() {
+            Ok(()) => Vec::new(),
+            Err(ValidationErrors(errors)) => errors.into_iter().map(|error| error.field).collect(),
+        }
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert_eq!(AppConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_reports_every_invalid_field() {
+        let mut config = AppConfig::default();
+        config.appearance.pet_size = -5;
+        config.appearance.pet_opacity = 7.0;
+        config.ai.temperature = 3.0;
+        config.ai.base_url = "api.deepseek.com".to_string();
+        config.ai.max_tokens = 0;
+        config.window.main_window_x = f64::NAN;
+        config.window.settings_window_width = Some(0.0);
+        config.character.active = "../钦灵".to_string();
+        assert_eq!(
+            fields(&config),
+            vec![
+                "appearance.pet_size",
+                "appearance.pet_opacity",
+                "ai.base_url",
+                "ai.temperature",
+                "ai.max_tokens",
+                "window.main_window_x",
+                "window.settings_window_width",
+                "character.active",
+            ]
+        );
+
+        let mut config = AppConfig::default();
+        config.ai.base_url = "http://localhost:11434".to_string();
+        config.ai.model = " ".to_string();
+        assert_eq!(fields(&config), vec!["ai.model"]);
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "ai.model: 不能为空"
+        );
+    }
+
+    #[test]
+    fn test_reset_invalid_fields_keeps_valid_ones() {
+        let mut config = AppConfig::default();
+        config.appearance.pet_size = 1000;
+        config.appearance.pet_opacity = 0.5;
+        config.ai.temperature = f64::NAN;
+        config.ai.model = "gpt-4o".to_string();
+
+        let reset = config.reset_invalid_fields();
+        assert_eq!(reset.len(), 2);
+        assert_eq!(config.appearance.pet_size, 150);
+        assert_eq!(config.appearance.pet_opacity, 0.5);
+        assert_eq!(config.ai.temperature, 0.7);
+        assert_eq!(config.ai.model, "gpt-4o");
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_pets_are_reset_or_removed() {
+        let mut config = AppConfig::default();
+        let pet = PetConfig { size: 20, opacity: 0.4, ..PetConfig::default() };
+        config.pets.insert("pet-2".to_string(), pet.clone());
+        config.pets.insert("main".to_string(), pet.clone());
+        config.pets.insert("../pet".to_string(), pet);
+        assert_eq!(fields(&config), vec!["pets.../pet.size", "pets.main.size", "pets.pet-2.size", "pets.../pet", "pets.main"]);
+
+        config.reset_invalid_fields();
+        assert_eq!(config.pets.keys().collect::<Vec<_>>(), vec!["pet-2"]);
+        assert_eq!((config.pets["pet-2"].size, config.pets["pet-2"].opacity), (PetConfig::default().size, 0.4));
+        assert_eq!(config.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,339 @@
+/*!
+ * @fileoverview 显示器与主窗口位置模块
+ * @description 根据当前可用的显示器恢复桌宠窗口位置，保证无边框置顶的桌宠始终落在可见的工作区内
+ * @features
+ *   - 枚举显示器的工作区与缩放比例
+ *   - 按保存时的缩放比例把配置中的逻辑坐标换算为物理坐标
+ *   - 原显示器不可用时移到保存时的显示器或最近的工作区，并限制在工作区内
+ *   - 定时检查显示器布局，变化后（拔出显示器、调整缩放）重新放置所有桌宠窗口
+ *   - 把所有桌宠并排移回主显示器中央，找回拖出屏幕的桌宠
+ *   - 放置后把位置、显示器名称与缩放比例写回该桌宠的配置（主桌宠在 window 分区，其他桌宠在 pets 分区）
+ * @structures
+ *   - Rect: 物理像素的矩形
+ *   - Display: 显示器（名称、工作区、缩放比例）
+ *   - Placement: 计算出的桌宠窗口位置
+ *   - SavedPosition: 配置中记录的桌宠位置
+ * @constants
+ *   - DISPLAY_WATCH_INTERVAL: 检查显示器布局的间隔
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, WebviewWindow};
+
+use crate::config::{AppConfig, ConfigSection, ConfigStore, MAIN_PET};
+use crate::error::AppError;
+use crate::window_manager::{self, WindowKind};
+
+/// 检查显示器布局是否变化的间隔
+pub const DISPLAY_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 物理像素的矩形
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    fn contains(&self, (x, y): (f64, f64)) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// 点到矩形的距离的平方，点在矩形内时为 0
+    fn distance_squared(&self, (x, y): (f64, f64)) -> f64 {
+        let dx = (self.x - x).max(0.0).max(x - (self.x + self.width));
+        let dy = (self.y - y).max(0.0).max(y - (self.y + self.height));
+        dx * dx + dy * dy
+    }
+}
+
+/// 显示器
+#[derive(Debug, Clone, PartialEq)]
+pub struct Display {
+    pub name: Option<String>,
+    /// 去掉任务栏、菜单栏后的工作区
+    pub work_area: Rect,
+    pub scale_factor: f64,
+}
+
+impl From<&Monitor> for Display {
+    fn from(monitor: &Monitor) -> Self {
+        let area = monitor.work_area();
+        Self {
+            name: monitor.name().cloned(),
+            work_area: Rect {
+                x: area.position.x as f64,
+                y: area.position.y as f64,
+                width: area.size.width as f64,
+                height: area.size.height as f64,
+            },
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+/// 计算出的桌宠窗口位置
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    /// 窗口左上角的物理坐标
+    pub x: i32,
+    pub y: i32,
+    /// 窗口中心的逻辑坐标，按所在显示器的缩放比例换算，写回配置
+    pub center_x: f64,
+    pub center_y: f64,
+    pub monitor: Option<String>,
+    pub scale_factor: f64,
+}
+
+/// 把 `[start, start + length)` 限制在 `[min, min + available)` 内，放不下时与起点对齐
+fn clamp_span(start: f64, length: f64, min: f64, available: f64) -> f64 {
+    start.min(min + available - length).max(min)
+}
+
+/// 放置桌宠窗口
+///
+/// `center` 是窗口中心的物理坐标，`size` 是窗口的逻辑大小。中心落在某个工作区内时留在该显示器；
+/// 否则优先移到名称为 `saved_monitor` 的显示器，其次是离中心最近的工作区。窗口始终被限制在目标工作区内。
+/// 没有可用的显示器时返回 `None`。
+pub fn place(center: (f64, f64), size: (f64, f64), saved_monitor: Option<&str>, displays: &[Display]) -> Option<Placement> {
+    let target = displays
+        .iter()
+        .find(|display| display.work_area.contains(center))
+        .or_else(|| displays.iter().find(|display| saved_monitor.is_some() && display.name.as_deref() == saved_monitor))
+        .or_else(|| {
+            displays.iter().min_by(|a, b| {
+                a.work_area.distance_squared(center).total_cmp(&b.work_area.distance_squared(center))
+            })
+        })?;
+
+    let area = target.work_area;
+    let (width, height) = (size.0 * target.scale_factor, size.1 * target.scale_factor);
+    let left = clamp_span(center.0 - width / 2.0, width, area.x, area.width);
+    let top = clamp_span(center.1 - height / 2.0, height, area.y, area.height);
+    Some(Placement {
+        x: left.round() as i32,
+        y: top.round() as i32,
+        center_x: (left + width / 2.0) / target.scale_factor,
+        center_y: (top + height / 2.0) / target.scale_factor,
+        monitor: target.name.clone(),
+        scale_factor: target.scale_factor,
+    })
+}
+
+/// 配置中记录的桌宠位置：窗口中心的逻辑坐标、所在显示器与缩放比例
+#[derive(Debug, Clone, PartialEq)]
+struct SavedPosition {
+    x: f64,
+    y: f64,
+    monitor: Option<String>,
+    scale: Option<f64>,
+}
+
+impl SavedPosition {
+    /// 桌宠已被移除时返回 `None`
+    fn of(config: &AppConfig, pet: &str) -> Option<Self> {
+        if pet == MAIN_PET {
+            let window = &config.window;
+            return Some(Self {
+                x: window.main_window_x,
+                y: window.main_window_y,
+                monitor: window.main_window_monitor.clone(),
+                scale: window.main_window_scale,
+            });
+        }
+        let saved = config.pets.get(pet)?;
+        Some(Self { x: saved.x, y: saved.y, monitor: saved.monitor.clone(), scale: saved.scale })
+    }
+
+    /// 写回配置的补丁：主桌宠在 window 分区，其他桌宠在 pets 分区
+    fn patch(&self, pet: &str) -> (ConfigSection, serde_json::Value) {
+        if pet == MAIN_PET {
+            let patch = json!({
+                "main_window_x": self.x,
+                "main_window_y": self.y,
+                "main_window_monitor": self.monitor,
+                "main_window_scale": self.scale,
+            });
+            return (ConfigSection::Window, patch);
+        }
+        let patch = json!({ pet: { "x": self.x, "y": self.y, "monitor": self.monitor, "scale": self.scale } });
+        (ConfigSection::Pets, patch)
+    }
+}
+
+fn displays(app: &AppHandle) -> Result<Vec<Display>, AppError> {
+    Ok(app.available_monitors()?.iter().map(Display::from).collect())
+}
+
+/// 把桌宠窗口放到 `center`（物理坐标）附近的可见位置，位置变化时写回配置
+async fn reposition(window: &WebviewWindow, config: &ConfigStore, pet: &str, center: (f64, f64)) -> Result<(), AppError> {
+    let scale = window.scale_factor()?;
+    let size = window.inner_size()?;
+    let size = (size.width as f64 / scale, size.height as f64 / scale);
+    let Some(saved) = SavedPosition::of(&*config.read().await, pet) else {
+        return Ok(());
+    };
+    let Some(placement) = place(center, size, saved.monitor.as_deref(), &displays(window.app_handle())?) else {
+        return Ok(());
+    };
+
+    if window.inner_position()? != PhysicalPosition::new(placement.x, placement.y) {
+        window.set_position(PhysicalPosition::new(placement.x, placement.y))?;
+    }
+    let placed = SavedPosition {
+        x: placement.center_x,
+        y: placement.center_y,
+        monitor: placement.monitor,
+        scale: Some(placement.scale_factor),
+    };
+    if placed != saved {
+        let (section, patch) = placed.patch(pet);
+        config.patch(section, patch).await?;
+    }
+    Ok(())
+}
+
+/// 按配置恢复桌宠窗口的位置
+pub async fn restore_pet(window: &WebviewWindow, config: &ConfigStore, pet: &str) -> Result<(), AppError> {
+    let Some(saved) = SavedPosition::of(&*config.read().await, pet) else {
+        return Ok(());
+    };
+    let scale = match saved.scale {
+        Some(scale) => scale,
+        None => window.scale_factor()?,
+    };
+    reposition(window, config, pet, (saved.x * scale, saved.y * scale)).await
+}
+
+/// 按窗口当前的位置重新放置桌宠
+async fn replace_pet(window: &WebviewWindow, config: &ConfigStore, pet: &str) -> Result<(), AppError> {
+    let position = window.inner_position()?;
+    let size = window.inner_size()?;
+    let center = (
+        position.x as f64 + size.width as f64 / 2.0,
+        position.y as f64 + size.height as f64 / 2.0,
+    );
+    reposition(window, config, pet, center).await
+}
+
+/// 宽度依次为 `widths` 的窗口在 `area` 中央从左到右并排时各自的中心
+fn row_centers(area: Rect, widths: &[f64]) -> Vec<(f64, f64)> {
+    let mut left = area.x + (area.width - widths.iter().sum::<f64>()) / 2.0;
+    let y = area.y + area.height / 2.0;
+    widths
+        .iter()
+        .map(|width| {
+            let center = (left + width / 2.0, y);
+            left += width;
+            center
+        })
+        .collect()
+}
+
+/// 把所有桌宠窗口并排移到主显示器工作区的中央，主桌宠在最左
+pub async fn center_pets(app: &AppHandle, config: &ConfigStore) -> Result<(), AppError> {
+    let area = match app.primary_monitor()? {
+        Some(monitor) => Display::from(&monitor).work_area,
+        None => match displays(app)?.first() {
+            Some(display) => display.work_area,
+            None => return Ok(()),
+        },
+    };
+    let mut pets: Vec<(String, WebviewWindow)> = app
+        .webview_windows()
+        .into_iter()
+        .filter_map(|(label, window)| match window_manager::classify(&label) {
+            Some((WindowKind::Pet, Some(pet))) => Some((pet, window)),
+            _ => None,
+        })
+        .collect();
+    pets.sort_by(|(a, _), (b, _)| (a != MAIN_PET, a).cmp(&(b != MAIN_PET, b)));
+
+    let widths = pets
+        .iter()
+        .map(|(_, window)| Ok(window.inner_size()?.width as f64))
+        .collect::<Result<Vec<_>, AppError>>()?;
+    for ((pet, window), center) in pets.iter().zip(row_centers(area, &widths)) {
+        reposition(window, config, pet, center).await?;
+    }
+    Ok(())
+}
+
+/// 显示器布局变化后把所有桌宠窗口移回可见的工作区，一直运行到应用退出
+pub async fn run_display_watcher(app: AppHandle, config: Arc<ConfigStore>, interval: Duration) {
+    let mut known = displays(&app).unwrap_or_default();
+    loop {
+        tokio::time::sleep(interval).await;
+        let current = match displays(&app) {
+            Ok(current) => current,
+            Err(e) => {
+                eprintln!("读取显示器列表失败: {}", e);
+                continue;
+            }
+        };
+        // 显示器休眠时可能暂时一个都没有，等它们回来再处理
+        if current == known || current.is_empty() {
+            continue;
+        }
+        known = current;
+
+        for (label, window) in app.webview_windows() {
+            let Some((WindowKind::Pet, Some(pet))) = window_manager::classify(&label) else {
+                continue;
+            };
+            if let Err(e) = replace_pet(&window, &config, &pet).await {
+                eprintln!("显示器变化后放置桌宠 {} 失败: {}", pet, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(name: &str, x: f64, y: f64, width: f64, height: f64, scale_factor: f64) -> Display {
+        Display { name: Some(name.to_string()), work_area: Rect { x, y, width, height }, scale_factor }
+    }
+
+    #[test]
+    fn test_visible_position_stays_on_its_display() {
+        let displays = [display("左", 0.0, 0.0, 1920.0, 1040.0, 1.0), display("右", 1920.0, 0.0, 2560.0, 1400.0, 2.0)];
+        let placement = place((2500.0, 700.0), (100.0, 100.0), Some("左"), &displays).unwrap();
+        // 右侧显示器缩放为 2，窗口物理大小为 200
+        assert_eq!((placement.x, placement.y), (2400, 600));
+        assert_eq!((placement.center_x, placement.center_y), (1250.0, 350.0));
+        assert_eq!((placement.monitor.as_deref(), placement.scale_factor), (Some("右"), 2.0));
+
+        // 超出工作区的部分被推回来
+        let placement = place((1900.0, 1030.0), (100.0, 100.0), None, &displays).unwrap();
+        assert_eq!((placement.x, placement.y), (1820, 940));
+    }
+
+    #[test]
+    fn test_unplugged_display_moves_to_saved_or_nearest() {
+        let displays = [display("主屏", 0.0, 0.0, 1920.0, 1040.0, 1.0), display("副屏", 0.0, 1080.0, 1280.0, 680.0, 1.0)];
+        // 原来在右侧的显示器上，现在已经拔掉
+        let placement = place((3000.0, 500.0), (100.0, 100.0), None, &displays).unwrap();
+        assert_eq!((placement.x, placement.y, placement.monitor.as_deref()), (1820, 450, Some("主屏")));
+
+        let placement = place((3000.0, 500.0), (100.0, 100.0), Some("副屏"), &displays).unwrap();
+        assert_eq!((placement.x, placement.y, placement.monitor.as_deref()), (1180, 1080, Some("副屏")));
+
+        assert_eq!(place((3000.0, 500.0), (100.0, 100.0), None, &[]), None);
+    }
+
+    #[test]
+    fn test_row_centers_line_up_around_area_center() {
+        let area = Rect { x: 1920.0, y: 0.0, width: 1000.0, height: 800.0 };
+        assert_eq!(row_centers(area, &[200.0, 100.0, 300.0]), vec![(2220.0, 400.0), (2370.0, 400.0), (2570.0, 400.0)]);
+        assert_eq!(row_centers(area, &[]), vec![]);
+    }
+}
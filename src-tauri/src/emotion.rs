@@ -0,0 +1,167 @@
+/*!
+ * @fileoverview 表情注册表模块
+ * @description 由当前角色包的清单生成可用表情，供回复格式提示词、回复校验和前端使用
+ * @structures
+ *   - Emotion: 单个表情（名称、英文标识、别名、显示颜色）
+ *   - EmotionRegistry: 表情注册表
+ * @constants
+ *   - DEFAULT_EMOTION: 角色包无法加载时使用的默认表情 ("正常")
+ * @author dada
+ * @version 2.0.0
+ * @since 2025-07-13
+ */
+
+use serde::Serialize;
+
+/// 角色包无法加载时使用的默认表情
+pub const DEFAULT_EMOTION: &str = "正常";
+
+/// 单个表情
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Emotion {
+    /// 表情名，模型输出与立绘都以它为准
+    pub name: String,
+    /// 可选的英文标识，例如 "happy"
+    pub key: Option<String>,
+    /// 模型可能输出的其他说法
+    pub aliases: Vec<String>,
+    /// 显示颜色，例如 "#92400e"
+    pub color: Option<String>,
+}
+
+/// 表情注册表
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EmotionRegistry {
+    emotions: Vec<Emotion>,
+    default_emotion: String,
+}
+
+impl Default for EmotionRegistry {
+    /// 只有默认表情的注册表
+    fn default() -> Self {
+        Self {
+            emotions: vec![Emotion {
+                name: DEFAULT_EMOTION.to_string(),
+                key: None,
+                aliases: Vec::new(),
+                color: None,
+            }],
+            default_emotion: DEFAULT_EMOTION.to_string(),
+        }
+    }
+}
+
+impl EmotionRegistry {
+    /// `default_emotion` 不在列表中时使用第一个表情
+    pub fn new(emotions: Vec<Emotion>, default_emotion: &str) -> Self {
+        if emotions.is_empty() {
+            return Self::default();
+        }
+        let default_emotion = if emotions.iter().any(|emotion| emotion.name == default_emotion) {
+            default_emotion.to_string()
+        } else {
+            emotions[0].name.clone()
+        };
+        Self { emotions, default_emotion }
+    }
+
+    pub fn emotions(&self) -> &[Emotion] {
+        &self.emotions
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.emotions.iter().map(|emotion| emotion.name.as_str()).collect()
+    }
+
+    pub fn default_emotion(&self) -> &str {
+        &self.default_emotion
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.emotions.iter().any(|emotion| emotion.name == name)
+    }
+
+    /// 英文标识与别名，均转为小写
+    fn synonyms(&self) -> Vec<(String, &str)> {
+        self.emotions
+            .iter()
+            .flat_map(|emotion| {
+                emotion
+                    .key
+                    .iter()
+                    .chain(&emotion.aliases)
+                    .map(move |synonym| (synonym.to_lowercase(), emotion.name.as_str()))
+            })
+            .collect()
+    }
+
+    /// 按表情名、英文标识或别名精确查找表情，不区分大小写
+    pub fn resolve(&self, raw: &str) -> Option<&str> {
+        let raw = raw.trim().to_lowercase();
+        if let Some(name) = self.names().into_iter().find(|name| *name == raw) {
+            return Some(name);
+        }
+        self.synonyms().into_iter().find(|(synonym, _)| *synonym == raw).map(|(_, name)| name)
+    }
+
+    /// 把模型输出的表情映射为最接近的合法表情
+    ///
+    /// 依次尝试：完全匹配、英文标识或别名完全匹配、包含表情名（如“有点高兴”）、包含别名；
+    /// 都不命中时退回默认表情。
+    pub fn normalize(&self, raw: &str) -> &str {
+        if let Some(name) = self.resolve(raw) {
+            return name;
+        }
+        let raw = raw.trim().to_lowercase();
+        let mut synonyms = self.synonyms();
+        if let Some(name) = self.names().into_iter().find(|name| raw.contains(name)) {
+            return name;
+        }
+        // 较长的别名优先，避免“笑”抢先匹配“坏笑”
+        synonyms.sort_by_key(|(synonym, _)| std::cmp::Reverse(synonym.chars().count()));
+        if let Some((_, name)) = synonyms.into_iter().find(|(synonym, _)| raw.contains(synonym.as_str())) {
+            return name;
+        }
+        &self.default_emotion
+    }
+}
+
+/// 内置角色的表情注册表，供各模块的测试使用
+#[cfg(test)]
+pub fn builtin_registry() -> EmotionRegistry {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("characters")
+        .join(crate::character::BUILTIN_CHARACTER);
+    crate::character::CharacterPack::load(&dir, true).unwrap().emotion_registry()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_emotion() {
+        let registry = builtin_registry();
+        assert_eq!(registry.normalize("高兴"), "高兴");
+        assert_eq!(registry.normalize("无语"), "无奈");
+        assert_eq!(registry.normalize(" Happy "), "高兴");
+        assert_eq!(registry.normalize("有点害羞"), "羞耻");
+        assert_eq!(registry.normalize("坏笑着"), "调皮");
+        assert_eq!(registry.normalize("???"), "正常");
+
+        assert_eq!(registry.resolve("SAD"), Some("伤心"));
+        assert_eq!(registry.resolve("有点害羞"), None);
+    }
+
+    #[test]
+    fn test_registry_without_emotions_falls_back_to_default() {
+        let registry = EmotionRegistry::new(Vec::new(), "开心");
+        assert_eq!(registry.names(), vec![DEFAULT_EMOTION]);
+
+        let emotion = |name: &str| Emotion { name: name.to_string(), key: None, aliases: Vec::new(), color: None };
+        let registry = EmotionRegistry::new(vec![emotion("微笑"), emotion("哭泣")], "不存在");
+        assert_eq!(registry.default_emotion(), "微笑");
+        assert_eq!(registry.normalize("在哭泣"), "哭泣");
+        assert_eq!(registry.normalize("生气"), "微笑");
+    }
+}
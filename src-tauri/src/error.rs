@@ -0,0 +1,328 @@
+/*!
+ * @fileoverview 统一的命令错误
+ * @description 各模块的错误在命令边界转换为 AppError，序列化为 `{kind, message, details}` 返回给前端
+ * @features
+ *   - kind: 稳定的机器可读类型，前端据此给出对应的处理建议
+ *   - message: 面向用户的中文说明，与原错误的 Display 相同
+ *   - details: 结构化的补充信息（出错的文件与行列、无效的字段、HTTP 状态码等），没有时为 null
+ *   - 覆盖配置、AI、窗口、角色包、数据库与文件读写错误
+ * @structures
+ *   - AppError: 命令错误
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use crate::ai::AiError;
+use crate::bundle::BundleError;
+use crate::character::CharacterError;
+use crate::config::{ConfigError, FieldError};
+use crate::history::HistoryError;
+use crate::hotkeys::{HotkeyError, HotkeyErrors};
+use crate::lingchat::LingChatError;
+use crate::memory::MemoryError;
+
+/// 返回给前端的错误；新增类型只能追加，已有的 kind 不能改名
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// 文件读写失败
+    Io { message: String, path: Option<String> },
+    /// 配置文件（或配置包）无法解析，行列从 1 开始
+    ConfigParse { message: String, path: Option<String>, line: Option<usize>, column: Option<usize> },
+    /// 配置取值无效
+    ConfigInvalid { message: String, fields: Vec<FieldError> },
+    /// 配置由更新版本的应用写入
+    ConfigVersion { message: String, version: i64 },
+    /// 参数无效，如补丁、名称、启动参数
+    InvalidInput(String),
+    /// 要操作的对象不存在
+    NotFound(String),
+    /// 与当前状态冲突，如名称已存在、正在使用
+    Conflict(String),
+    /// 密钥无法加密或解密
+    Secrets(String),
+    /// AI 配置不完整
+    AiConfig(String),
+    /// 无法连接 AI 服务
+    AiNetwork(String),
+    /// AI 服务返回了错误状态
+    AiApi { message: String, status: u16 },
+    /// AI 回复为空或无法解析
+    AiResponse(String),
+    /// 窗口操作失败
+    Window(String),
+    /// 角色包无效
+    Character(String),
+    /// 数据库错误
+    Database(String),
+    /// 不应出现的内部错误
+    Internal(String),
+    /// 全局快捷键无效、互相冲突或无法注册
+    Hotkey { message: String, errors: Vec<HotkeyError> },
+    /// AI 对话已在托盘菜单中暂停
+    AiPaused(String),
+}
+
+impl AppError {
+    /// 机器可读的错误类型
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Io { .. } => "io",
+            AppError::ConfigParse { .. } => "config_parse",
+            AppError::ConfigInvalid { .. } => "config_invalid",
+            AppError::ConfigVersion { .. } => "config_version",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Secrets(_) => "secrets",
+            AppError::AiConfig(_) => "ai_config",
+            AppError::AiNetwork(_) => "ai_network",
+            AppError::AiApi { .. } => "ai_api",
+            AppError::AiResponse(_) => "ai_response",
+            AppError::Window(_) => "window",
+            AppError::Character(_) => "character",
+            AppError::Database(_) => "database",
+            AppError::Internal(_) => "internal",
+            AppError::Hotkey { .. } => "hotkey",
+            AppError::AiPaused(_) => "ai_paused",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::Io { message, .. }
+            | AppError::ConfigParse { message, .. }
+            | AppError::ConfigInvalid { message, .. }
+            | AppError::ConfigVersion { message, .. }
+            | AppError::AiApi { message, .. }
+            | AppError::Hotkey { message, .. } => message,
+            AppError::InvalidInput(message)
+            | AppError::NotFound(message)
+            | AppError::Conflict(message)
+            | AppError::Secrets(message)
+            | AppError::AiConfig(message)
+            | AppError::AiNetwork(message)
+            | AppError::AiResponse(message)
+            | AppError::Window(message)
+            | AppError::Character(message)
+            | AppError::Database(message)
+            | AppError::Internal(message)
+            | AppError::AiPaused(message) => message,
+        }
+    }
+
+    /// 结构化的补充信息
+    pub fn details(&self) -> Value {
+        match self {
+            AppError::Io { path, .. } => json!({ "path": path }),
+            AppError::ConfigParse { path, line, column, .. } => json!({ "path": path, "line": line, "column": column }),
+            AppError::ConfigInvalid { fields, .. } => json!({ "fields": fields }),
+            AppError::ConfigVersion { version, .. } => json!({ "version": version }),
+            AppError::AiApi { status, .. } => json!({ "status": status }),
+            AppError::Hotkey { errors, .. } => json!({ "errors": errors }),
+            _ => Value::Null,
+        }
+    }
+
+    /// 记录出错的文件，只对文件读写与解析错误有效
+    pub fn with_path(mut self, file: &Path) -> Self {
+        if let AppError::Io { path, .. } | AppError::ConfigParse { path, .. } = &mut self {
+            *path = Some(file.display().to_string());
+        }
+        self
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", self.message())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+/// 从 TOML 解析错误的说明中取出行列（`TOML parse error at line 3, column 7`）
+fn toml_location(e: &toml::de::Error) -> (Option<usize>, Option<usize>) {
+    let text = e.to_string();
+    let number_after = |label: &str| {
+        let start = text.find(label)? + label.len();
+        let digits: String = text[start..].chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    };
+    (number_after("line "), number_after("column "))
+}
+
+fn parse_error(message: String, e: &toml::de::Error) -> AppError {
+    let (line, column) = toml_location(e);
+    AppError::ConfigParse { message, path: None, line, column }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Io { message: format!("IO错误: {}", e), path: None }
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        AppError::Window(format!("窗口操作失败: {}", e))
+    }
+}
+
+impl From<ConfigError> for AppError {
+    fn from(e: ConfigError) -> Self {
+        let message = e.to_string();
+        match e {
+            ConfigError::IoError(_) | ConfigError::DirectoryError(_) => AppError::Io { message, path: None },
+            ConfigError::DeserializationError(e) => parse_error(message, &e),
+            ConfigError::SerializationError(_) => AppError::Internal(message),
+            ConfigError::UnsupportedSchemaVersion(version) => AppError::ConfigVersion { message, version },
+            ConfigError::Invalid(errors) => AppError::ConfigInvalid { message, fields: errors.0 },
+            ConfigError::InvalidPatch(_) | ConfigError::InvalidProfileName(_) | ConfigError::InvalidOverride(_) => {
+                AppError::InvalidInput(message)
+            }
+            ConfigError::ProfileNotFound(_) => AppError::NotFound(message),
+            ConfigError::ProfileExists(_) | ConfigError::ProfileActive(_) | ConfigError::ProfilesPinned => {
+                AppError::Conflict(message)
+            }
+            ConfigError::SecretsError(_) => AppError::Secrets(message),
+        }
+    }
+}
+
+impl From<AiError> for AppError {
+    fn from(e: AiError) -> Self {
+        let message = e.to_string();
+        match e {
+            AiError::ConfigIncomplete => AppError::AiConfig(message),
+            AiError::Http(_) => AppError::AiNetwork(message),
+            AiError::Api { status, .. } => AppError::AiApi { message, status },
+            AiError::EmptyResponse | AiError::InvalidFormat(_) => AppError::AiResponse(message),
+        }
+    }
+}
+
+impl From<HistoryError> for AppError {
+    fn from(e: HistoryError) -> Self {
+        let message = e.to_string();
+        match e {
+            HistoryError::IoError(_) => AppError::Io { message, path: None },
+            HistoryError::SqliteError(_) => AppError::Database(message),
+            HistoryError::SessionNotFound(_) => AppError::NotFound(message),
+            HistoryError::InvalidPage { .. } => AppError::InvalidInput(message),
+        }
+    }
+}
+
+impl From<MemoryError> for AppError {
+    fn from(e: MemoryError) -> Self {
+        let message = e.to_string();
+        match e {
+            MemoryError::IoError(_) => AppError::Io { message, path: None },
+            MemoryError::SqliteError(_) => AppError::Database(message),
+            MemoryError::MemoryNotFound(_) => AppError::NotFound(message),
+            MemoryError::EmptyContent => AppError::InvalidInput(message),
+        }
+    }
+}
+
+impl From<CharacterError> for AppError {
+    fn from(e: CharacterError) -> Self {
+        let message = e.to_string();
+        match e {
+            CharacterError::IoError(_) => AppError::Io { message, path: None },
+            CharacterError::CharacterNotFound(_) | CharacterError::AssetNotFound(_) => AppError::NotFound(message),
+            CharacterError::BuiltinCharacter(_) => AppError::Conflict(message),
+            CharacterError::UnsupportedSource(_) => AppError::InvalidInput(message),
+            CharacterError::ZipError(_)
+            | CharacterError::ManifestError(_)
+            | CharacterError::ManifestNotFound
+            | CharacterError::InvalidPack(_) => AppError::Character(message),
+        }
+    }
+}
+
+impl From<LingChatError> for AppError {
+    fn from(e: LingChatError) -> Self {
+        match e {
+            LingChatError::IoError(e) => e.into(),
+            LingChatError::CharacterError(e) => e.into(),
+            LingChatError::SettingsNotFound(_) | LingChatError::PromptNotFound | LingChatError::NoSprites => {
+                AppError::Character(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<HotkeyErrors> for AppError {
+    fn from(e: HotkeyErrors) -> Self {
+        AppError::Hotkey { message: e.to_string(), errors: e.0 }
+    }
+}
+
+impl From<BundleError> for AppError {
+    fn from(e: BundleError) -> Self {
+        let message = e.to_string();
+        match e {
+            BundleError::IoError(_) => AppError::Io { message, path: None },
+            BundleError::DeserializationError(e) => parse_error(message, &e),
+            BundleError::SerializationError(_) => AppError::Internal(message),
+            BundleError::UnsupportedBundle(_) => AppError::InvalidInput(message),
+            BundleError::ConfigError(e) => e.into(),
+            BundleError::MemoryError(e) => e.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_config, AppConfig};
+
+    #[test]
+    fn test_parse_error_reports_location() {
+        let Err(e) = parse_config("[appearance]\npet_size = 150\npet_opacity = \n") else {
+            panic!("应当解析失败");
+        };
+        let error = AppError::from(e).with_path(Path::new("config.toml"));
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["kind"], "config_parse");
+        assert_eq!(json["details"], json!({ "path": "config.toml", "line": 3, "column": 15 }));
+        assert!(json["message"].as_str().unwrap().starts_with("反序列化错误"));
+    }
+
+    #[test]
+    fn test_kinds_and_details() {
+        let mut config = AppConfig::default();
+        config.appearance.pet_size = 5;
+        let error = AppError::from(ConfigError::Invalid(config.validate().unwrap_err()));
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["kind"], "config_invalid");
+        assert_eq!(json["details"]["fields"][0]["field"], "appearance.pet_size");
+
+        let error = AppError::from(AiError::Api { status: 401, body: "unauthorized".to_string() });
+        assert_eq!(serde_json::to_value(&error).unwrap()["details"], json!({ "status": 401 }));
+
+        let error = AppError::from(ConfigError::ProfileNotFound("工作".to_string()));
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!((json["kind"].as_str(), json["details"].is_null()), (Some("not_found"), true));
+        assert_eq!(error.to_string(), "配置档案不存在: 工作");
+    }
+}
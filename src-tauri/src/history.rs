@@ -0,0 +1,497 @@
+/*!
+ * @fileoverview 对话历史模块
+ * @description 使用本地 SQLite 持久化每一轮对话，供多轮上下文回放与历史浏览
+ * @features
+ *   - 会话 (session) 管理：新建、列出、清空、删除；每个会话属于一只桌宠
+ *   - 记录用户消息与桌宠每一句回复（含表情、日语、时间戳）
+ *   - 读取尚未折叠进摘要的对话用于上下文回放
+ *   - 保存会话的滚动摘要
+ *   - 分页浏览会话消息
+ * @structures
+ *   - HistoryStore: 历史存储
+ *   - SessionSummary: 会话摘要
+ *   - HistoryMessage: 单条历史消息
+ *   - HistoryTurn: 一轮对话（用户消息 + 桌宠回复）
+ *   - SessionContext: 会话摘要与未折叠的对话
+ * @storage
+ *   - 数据库路径: 配置目录下的 history.db
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ai::types::PetResponseItem;
+
+// 历史记录错误类型
+#[derive(Debug)]
+pub enum HistoryError {
+    SqliteError(rusqlite::Error),
+    IoError(std::io::Error),
+    SessionNotFound(i64),
+    InvalidPage { page: usize, page_size: usize },
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HistoryError::SqliteError(e) => write!(f, "数据库错误: {}", e),
+            HistoryError::IoError(e) => write!(f, "IO错误: {}", e),
+            HistoryError::SessionNotFound(id) => write!(f, "会话不存在: {}", id),
+            HistoryError::InvalidPage { page, page_size } => {
+                write!(f, "分页超出范围: 第 {} 页，每页 {} 条", page, page_size)
+            }
+        }
+    }
+}
+
+impl Error for HistoryError {}
+
+impl From<rusqlite::Error> for HistoryError {
+    fn from(e: rusqlite::Error) -> Self {
+        HistoryError::SqliteError(e)
+    }
+}
+
+/// 按版本顺序执行的建表与升级语句，已执行的版本记录在 `PRAGMA user_version`
+const MIGRATIONS: &[&str] = &[
+    r#"
+CREATE TABLE IF NOT EXISTS sessions (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    title       TEXT NOT NULL,
+    created_at  INTEGER NOT NULL,
+    updated_at  INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS messages (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id  INTEGER NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    turn        INTEGER NOT NULL,
+    role        TEXT NOT NULL,
+    content     TEXT NOT NULL,
+    emotion     TEXT,
+    japanese    TEXT,
+    created_at  INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_messages_session_turn ON messages(session_id, turn);
+"#,
+    // 滚动摘要：summary_turn 及之前的对话已折叠进 summary
+    r#"
+ALTER TABLE sessions ADD COLUMN summary TEXT;
+ALTER TABLE sessions ADD COLUMN summary_turn INTEGER NOT NULL DEFAULT 0;
+"#,
+    // 多只桌宠：已有会话都属于主桌宠
+    r#"
+ALTER TABLE sessions ADD COLUMN pet TEXT NOT NULL DEFAULT 'main';
+CREATE INDEX IF NOT EXISTS idx_sessions_pet ON sessions(pet, updated_at);
+"#,
+];
+
+/// 会话摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub id: i64,
+    /// 会话所属的桌宠 id
+    pub pet: String,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub message_count: i64,
+}
+
+/// 单条历史消息；用户消息没有表情与日语
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryMessage {
+    pub id: i64,
+    pub turn: i64,
+    pub role: String,
+    pub content: String,
+    pub emotion: Option<String>,
+    pub japanese: Option<String>,
+    pub created_at: i64,
+}
+
+/// 一页消息
+#[derive(Debug, Clone, Serialize)]
+pub struct MessagePage {
+    pub messages: Vec<HistoryMessage>,
+    pub page: usize,
+    pub page_size: usize,
+    pub total: i64,
+}
+
+/// 一轮对话：用户消息及桌宠的全部回复
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryTurn {
+    pub turn: i64,
+    pub user: String,
+    pub items: Vec<PetResponseItem>,
+}
+
+/// 回放上下文所需的会话数据
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionContext {
+    /// 较早对话的摘要
+    pub summary: Option<String>,
+    /// 尚未折叠进摘要的对话，按时间先后排列
+    pub turns: Vec<HistoryTurn>,
+}
+
+/// 当前时间（毫秒时间戳）
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+// 历史存储
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    /// 打开（或创建）数据库文件
+    pub fn open(path: &Path) -> Result<Self, HistoryError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(HistoryError::IoError)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, HistoryError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self, HistoryError> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.execute_batch(&format!("PRAGMA user_version = {}", index + 1))?;
+            tx.commit()?;
+        }
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        // 持锁线程 panic 不影响数据库本身，继续使用
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 为桌宠新建会话
+    pub fn create_session(&self, pet: &str, title: &str) -> Result<i64, HistoryError> {
+        let conn = self.conn();
+        let now = now_millis();
+        conn.execute(
+            "INSERT INTO sessions (pet, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+            params![pet, title, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 桌宠最近更新的会话，没有则新建一个
+    pub fn latest_or_create_session(&self, pet: &str) -> Result<i64, HistoryError> {
+        let latest: Option<i64> = self
+            .conn()
+            .query_row(
+                "SELECT id FROM sessions WHERE pet = ?1 ORDER BY updated_at DESC, id DESC LIMIT 1",
+                [pet],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match latest {
+            Some(id) => Ok(id),
+            None => self.create_session(pet, "新的对话"),
+        }
+    }
+
+    fn ensure_session(conn: &Connection, session_id: i64) -> Result<(), HistoryError> {
+        let exists: Option<i64> = conn
+            .query_row("SELECT id FROM sessions WHERE id = ?1", [session_id], |row| row.get(0))
+            .optional()?;
+        exists.map(|_| ()).ok_or(HistoryError::SessionNotFound(session_id))
+    }
+
+    /// 记录一轮完整对话并返回轮次；会话标题为空时用第一条用户消息命名
+    pub fn record_turn(
+        &self,
+        session_id: i64,
+        user_message: &str,
+        items: &[PetResponseItem],
+    ) -> Result<i64, HistoryError> {
+        let mut conn = self.conn();
+        Self::ensure_session(&conn, session_id)?;
+        let tx = conn.transaction()?;
+        let now = now_millis();
+
+        let turn: i64 = tx.query_row(
+            "SELECT COALESCE(MAX(turn), 0) + 1 FROM messages WHERE session_id = ?1",
+            [session_id],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "INSERT INTO messages (session_id, turn, role, content, created_at) VALUES (?1, ?2, 'user', ?3, ?4)",
+            params![session_id, turn, user_message, now],
+        )?;
+        for item in items {
+            tx.execute(
+                "INSERT INTO messages (session_id, turn, role, content, emotion, japanese, created_at)
+                 VALUES (?1, ?2, 'assistant', ?3, ?4, ?5, ?6)",
+                params![session_id, turn, item.message, item.emotion, item.japanese, now],
+            )?;
+        }
+
+        let title: String = user_message.chars().take(20).collect();
+        tx.execute(
+            "UPDATE sessions SET updated_at = ?2,
+                 title = CASE WHEN title = '新的对话' THEN ?3 ELSE title END
+             WHERE id = ?1",
+            params![session_id, now, title],
+        )?;
+        tx.commit()?;
+        Ok(turn)
+    }
+
+    /// 读取会话摘要及尚未折叠进摘要的全部对话
+    pub fn load_context(&self, session_id: i64) -> Result<SessionContext, HistoryError> {
+        let conn = self.conn();
+        let (summary, summary_turn): (Option<String>, i64) = conn
+            .query_row("SELECT summary, summary_turn FROM sessions WHERE id = ?1", [session_id], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .optional()?
+            .ok_or(HistoryError::SessionNotFound(session_id))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT turn, role, content, emotion, japanese FROM messages
+             WHERE session_id = ?1 AND turn > ?2
+             ORDER BY turn, id",
+        )?;
+        let rows = stmt.query_map(params![session_id, summary_turn], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+
+        let mut turns: Vec<HistoryTurn> = Vec::new();
+        for row in rows {
+            let (turn, role, content, emotion, japanese) = row?;
+            if role == "user" {
+                turns.push(HistoryTurn { turn, user: content, items: Vec::new() });
+            } else if let Some(last) = turns.last_mut() {
+                last.items.push(PetResponseItem {
+                    message: content,
+                    emotion: emotion.unwrap_or_default(),
+                    japanese: japanese.unwrap_or_default(),
+                });
+            }
+        }
+        Ok(SessionContext { summary, turns })
+    }
+
+    /// 保存滚动摘要，`through_turn` 及之前的对话不再回放
+    pub fn save_summary(&self, session_id: i64, summary: &str, through_turn: i64) -> Result<(), HistoryError> {
+        let conn = self.conn();
+        Self::ensure_session(&conn, session_id)?;
+        conn.execute(
+            "UPDATE sessions SET summary = ?2, summary_turn = ?3 WHERE id = ?1",
+            params![session_id, summary, through_turn],
+        )?;
+        Ok(())
+    }
+
+    /// 按最近更新时间列出所有会话
+    pub fn list_sessions(&self) -> Result<Vec<SessionSummary>, HistoryError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.pet, s.title, s.created_at, s.updated_at, COUNT(m.id)
+             FROM sessions s LEFT JOIN messages m ON m.session_id = s.id
+             GROUP BY s.id ORDER BY s.updated_at DESC, s.id DESC",
+        )?;
+        let sessions = stmt
+            .query_map([], |row| {
+                Ok(SessionSummary {
+                    id: row.get(0)?,
+                    pet: row.get(1)?,
+                    title: row.get(2)?,
+                    created_at: row.get(3)?,
+                    updated_at: row.get(4)?,
+                    message_count: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(sessions)
+    }
+
+    /// 分页读取会话消息，`page` 从 0 开始，按时间先后排列
+    pub fn session_messages(
+        &self,
+        session_id: i64,
+        page: usize,
+        page_size: usize,
+    ) -> Result<MessagePage, HistoryError> {
+        let invalid = || HistoryError::InvalidPage { page, page_size };
+        let limit = i64::try_from(page_size).map_err(|_| invalid())?;
+        let offset = page.checked_mul(page_size).and_then(|offset| i64::try_from(offset).ok()).ok_or_else(invalid)?;
+        let conn = self.conn();
+        Self::ensure_session(&conn, session_id)?;
+        let total: i64 =
+            conn.query_row("SELECT COUNT(*) FROM messages WHERE session_id = ?1", [session_id], |row| row.get(0))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, turn, role, content, emotion, japanese, created_at FROM messages
+             WHERE session_id = ?1 ORDER BY id LIMIT ?2 OFFSET ?3",
+        )?;
+        let messages = stmt
+            .query_map(params![session_id, limit, offset], |row| {
+                Ok(HistoryMessage {
+                    id: row.get(0)?,
+                    turn: row.get(1)?,
+                    role: row.get(2)?,
+                    content: row.get(3)?,
+                    emotion: row.get(4)?,
+                    japanese: row.get(5)?,
+                    created_at: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(MessagePage { messages, page, page_size, total })
+    }
+
+    /// 清空会话中的消息，保留会话本身
+    pub fn clear_session(&self, session_id: i64) -> Result<(), HistoryError> {
+        let conn = self.conn();
+        Self::ensure_session(&conn, session_id)?;
+        conn.execute("DELETE FROM messages WHERE session_id = ?1", [session_id])?;
+        conn.execute(
+            "UPDATE sessions SET updated_at = ?2, summary = NULL, summary_turn = 0 WHERE id = ?1",
+            params![session_id, now_millis()],
+        )?;
+        Ok(())
+    }
+
+    /// 删除会话及其消息
+    pub fn delete_session(&self, session_id: i64) -> Result<(), HistoryError> {
+        let conn = self.conn();
+        Self::ensure_session(&conn, session_id)?;
+        conn.execute("DELETE FROM sessions WHERE id = ?1", [session_id])?;
+        Ok(())
+    }
+
+    /// 删除全部会话
+    pub fn clear_all(&self) -> Result<(), HistoryError> {
+        let conn = self.conn();
+        conn.execute_batch("DELETE FROM messages; DELETE FROM sessions;")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(message: &str, emotion: &str) -> PetResponseItem {
+        PetResponseItem { message: message.to_string(), emotion: emotion.to_string(), japanese: String::new() }
+    }
+
+    #[test]
+    fn test_record_and_replay_turns_after_summary() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let session = store.create_session("main", "新的对话").unwrap();
+
+        store.record_turn(session, "第一轮", &[item("一", "高兴")]).unwrap();
+        store.record_turn(session, "第二轮", &[item("二a", "正常"), item("二b", "认真")]).unwrap();
+        assert_eq!(store.record_turn(session, "第三轮", &[item("三", "无奈")]).unwrap(), 3);
+
+        let context = store.load_context(session).unwrap();
+        assert_eq!(context.summary, None);
+        assert_eq!(context.turns.len(), 3);
+        assert_eq!(context.turns[1].turn, 2);
+        assert_eq!(context.turns[1].items, vec![item("二a", "正常"), item("二b", "认真")]);
+
+        store.save_summary(session, "聊了两轮", 2).unwrap();
+        let context = store.load_context(session).unwrap();
+        assert_eq!(context.summary.as_deref(), Some("聊了两轮"));
+        assert_eq!(context.turns.len(), 1);
+        assert_eq!(context.turns[0].user, "第三轮");
+
+        store.clear_session(session).unwrap();
+        assert_eq!(store.load_context(session).unwrap(), SessionContext::default());
+    }
+
+    #[test]
+    fn test_sessions_are_isolated_and_titled() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let first = store.create_session("main", "新的对话").unwrap();
+        let second = store.create_session("main", "新的对话").unwrap();
+
+        // 时间戳精度为毫秒，确保两次写入的更新时间不同
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        store.record_turn(first, "今天天气怎么样", &[item("晴天", "高兴")]).unwrap();
+        assert!(store.load_context(second).unwrap().turns.is_empty());
+
+        let sessions = store.list_sessions().unwrap();
+        assert_eq!(sessions.len(), 2);
+        // 最近更新的排在前面
+        assert_eq!(sessions[0].id, first);
+        assert_eq!(sessions[0].title, "今天天气怎么样");
+        assert_eq!(sessions[0].message_count, 2);
+        assert_eq!(store.latest_or_create_session("main").unwrap(), first);
+    }
+
+    #[test]
+    fn test_each_pet_has_its_own_sessions() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let main = store.latest_or_create_session("main").unwrap();
+        let other = store.latest_or_create_session("pet-2").unwrap();
+        assert_ne!(main, other);
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        store.record_turn(other, "你好", &[item("你好呀", "高兴")]).unwrap();
+        // 另一只桌宠的会话更新了，主桌宠仍然回到自己的会话
+        assert_eq!(store.latest_or_create_session("main").unwrap(), main);
+        assert_eq!(store.latest_or_create_session("pet-2").unwrap(), other);
+        assert!(store.load_context(main).unwrap().turns.is_empty());
+
+        let sessions = store.list_sessions().unwrap();
+        assert_eq!(sessions[0].pet, "pet-2");
+        assert_eq!(sessions[1].pet, "main");
+    }
+
+    #[test]
+    fn test_paging_and_clearing() {
+        let store = HistoryStore::open_in_memory().unwrap();
+        let session = store.latest_or_create_session("main").unwrap();
+        for i in 0..5 {
+            store.record_turn(session, &format!("消息{}", i), &[item("好", "正常")]).unwrap();
+        }
+
+        let page = store.session_messages(session, 1, 4).unwrap();
+        assert_eq!(page.total, 10);
+        assert_eq!(page.messages.len(), 4);
+        assert_eq!(page.messages[0].content, "消息2");
+        assert_eq!(page.messages[1].emotion.as_deref(), Some("正常"));
+        assert!(matches!(store.session_messages(session, usize::MAX, 2), Err(HistoryError::InvalidPage { .. })));
+        assert!(matches!(store.session_messages(session, 0, usize::MAX), Err(HistoryError::InvalidPage { .. })));
+
+        store.clear_session(session).unwrap();
+        assert_eq!(store.session_messages(session, 0, 10).unwrap().total, 0);
+
+        store.delete_session(session).unwrap();
+        assert!(matches!(store.clear_session(session), Err(HistoryError::SessionNotFound(_))));
+    }
+}
@@ -0,0 +1,306 @@
+/*!
+ * @fileoverview 全局快捷键模块
+ * @description 按配置中的 hotkeys 分区注册系统级快捷键，应用不在前台时也能唤出桌宠
+ * @features
+ *   - 显示主桌宠并聚焦聊天输入框
+ *   - 播放当前对话的下一句
+ *   - 显示或隐藏所有桌宠
+ *   - 切换桌宠窗口的鼠标穿透
+ *   - 写法无效、互相冲突或被其他程序占用的快捷键以结构化错误返回，并通过 hotkey-errors 事件通知设置窗口
+ *   - 配置被外部修改或切换档案后重新注册
+ * @structures
+ *   - HotkeyAction: 快捷键对应的操作
+ *   - HotkeyError: 单个快捷键的错误
+ *   - HotkeyManager: 已注册的快捷键
+ * @constants
+ *   - HOTKEY_EVENT: 需要桌宠窗口处理的操作
+ *   - HOTKEY_ERRORS_EVENT: 快捷键错误变化
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::config::{ConfigStore, HotkeyConfig, MAIN_PET};
+use crate::error::AppError;
+use crate::tray;
+use crate::window_manager::pet_label;
+use crate::AppState;
+
+/// 发给桌宠窗口的事件名，携带 `HotkeyAction`，由窗口聚焦输入框或播放下一句
+pub const HOTKEY_EVENT: &str = "hotkey";
+/// 快捷键错误变化时发出的事件名，携带全部 `HotkeyError`
+pub const HOTKEY_ERRORS_EVENT: &str = "hotkey-errors";
+
+/// 快捷键对应的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    FocusChat,
+    PlayNext,
+    ToggleVisible,
+    ToggleClickThrough,
+}
+
+impl HotkeyAction {
+    const ALL: [HotkeyAction; 4] =
+        [HotkeyAction::FocusChat, HotkeyAction::PlayNext, HotkeyAction::ToggleVisible, HotkeyAction::ToggleClickThrough];
+
+    /// 配置中的字段名
+    fn field(self) -> &'static str {
+        match self {
+            HotkeyAction::FocusChat => "focus_chat",
+            HotkeyAction::PlayNext => "play_next",
+            HotkeyAction::ToggleVisible => "toggle_visible",
+            HotkeyAction::ToggleClickThrough => "toggle_click_through",
+        }
+    }
+
+    fn binding(self, config: &HotkeyConfig) -> &str {
+        match self {
+            HotkeyAction::FocusChat => &config.focus_chat,
+            HotkeyAction::PlayNext => &config.play_next,
+            HotkeyAction::ToggleVisible => &config.toggle_visible,
+            HotkeyAction::ToggleClickThrough => &config.toggle_click_through,
+        }
+    }
+}
+
+/// 快捷键无法使用的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyFailure {
+    /// 写法无法识别
+    Invalid,
+    /// 与另一个操作的快捷键相同
+    Conflict,
+    /// 系统拒绝注册，通常已被其他程序占用
+    Unavailable,
+}
+
+/// 单个快捷键的错误
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HotkeyError {
+    /// `hotkeys.字段`，与配置校验错误的定位方式相同
+    pub field: String,
+    pub binding: String,
+    pub reason: HotkeyFailure,
+    pub message: String,
+}
+
+impl HotkeyError {
+    fn new(action: HotkeyAction, binding: &str, reason: HotkeyFailure, message: String) -> Self {
+        Self { field: format!("hotkeys.{}", action.field()), binding: binding.to_string(), reason, message }
+    }
+}
+
+/// 一份快捷键配置中的全部错误
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotkeyErrors(pub Vec<HotkeyError>);
+
+impl fmt::Display for HotkeyErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let errors: Vec<String> = self.0.iter().map(|error| format!("{}: {}", error.field, error.message)).collect();
+        write!(f, "快捷键不可用: {}", errors.join("；"))
+    }
+}
+
+/// 解析每个操作的快捷键；留空的操作跳过，无法解析或与前面的操作重复的记为错误
+fn resolve<K: PartialEq + Copy>(
+    config: &HotkeyConfig,
+    parse: impl Fn(&str) -> Result<K, String>,
+) -> (Vec<(HotkeyAction, K)>, Vec<HotkeyError>) {
+    let mut bindings: Vec<(HotkeyAction, K)> = Vec::new();
+    let mut errors = Vec::new();
+    for action in HotkeyAction::ALL {
+        let binding = action.binding(config).trim();
+        if binding.is_empty() {
+            continue;
+        }
+        let key = match parse(binding) {
+            Ok(key) => key,
+            Err(e) => {
+                let message = format!("无法识别的快捷键: {}", e);
+                errors.push(HotkeyError::new(action, binding, HotkeyFailure::Invalid, message));
+                continue;
+            }
+        };
+        if let Some((other, _)) = bindings.iter().find(|(_, existing)| *existing == key) {
+            let message = format!("与 hotkeys.{} 的快捷键相同", other.field());
+            errors.push(HotkeyError::new(action, binding, HotkeyFailure::Conflict, message));
+            continue;
+        }
+        bindings.push((action, key));
+    }
+    (bindings, errors)
+}
+
+fn parse_shortcut(binding: &str) -> Result<Shortcut, String> {
+    binding.parse::<Shortcut>().map_err(|e| e.to_string())
+}
+
+/// 已注册的快捷键与最近一次注册的错误
+#[derive(Default)]
+pub struct HotkeyManager {
+    registered: Mutex<Vec<(HotkeyAction, Shortcut)>>,
+    errors: Mutex<Vec<HotkeyError>>,
+    /// 串行化重新注册；注册要等主线程执行，期间不能持有 `registered`，否则会与按键回调互相等待
+    updating: Mutex<()>,
+}
+
+impl HotkeyManager {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// 最近一次注册时无法使用的快捷键
+    pub fn errors(&self) -> Vec<HotkeyError> {
+        self.errors.lock().unwrap().clone()
+    }
+
+    fn action(&self, shortcut: &Shortcut) -> Option<HotkeyAction> {
+        let registered = self.registered.lock().unwrap();
+        registered.iter().find(|(_, registered)| registered == shortcut).map(|(action, _)| *action)
+    }
+
+    /// 按配置重新注册全部快捷键，返回无法使用的快捷键；其余快捷键照常生效
+    pub fn apply(&self, app: &AppHandle, config: &HotkeyConfig) -> Vec<HotkeyError> {
+        let _updating = self.updating.lock().unwrap();
+        let (bindings, mut errors) = resolve(config, parse_shortcut);
+        let previous = self.registered.lock().unwrap().clone();
+        if previous == bindings && errors.is_empty() && self.errors().is_empty() {
+            return Vec::new();
+        }
+
+        let shortcuts = app.global_shortcut();
+        self.registered.lock().unwrap().clear();
+        for (action, shortcut) in previous {
+            if let Err(e) = shortcuts.unregister(shortcut) {
+                eprintln!("注销快捷键 {} 失败: {}", action.field(), e);
+            }
+        }
+        let mut registered = Vec::new();
+        for (action, shortcut) in bindings {
+            match shortcuts.register(shortcut) {
+                Ok(()) => registered.push((action, shortcut)),
+                Err(e) => {
+                    let binding = action.binding(config).trim();
+                    let message = format!("无法注册，可能已被其他程序占用: {}", e);
+                    errors.push(HotkeyError::new(action, binding, HotkeyFailure::Unavailable, message));
+                }
+            }
+        }
+        *self.registered.lock().unwrap() = registered;
+        *self.errors.lock().unwrap() = errors.clone();
+        errors
+    }
+}
+
+/// 全局快捷键插件，按下已注册的快捷键时执行对应的操作
+pub fn plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            let Some(action) = app.state::<AppState>().hotkeys.action(shortcut) else {
+                return;
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = perform(&app, action).await {
+                    eprintln!("执行快捷键操作失败: {}", e);
+                }
+            });
+        })
+        .build()
+}
+
+async fn perform(app: &AppHandle, action: HotkeyAction) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    match action {
+        HotkeyAction::FocusChat => {
+            state.windows.focus_pet(MAIN_PET).await?;
+            app.emit_to(pet_label(MAIN_PET), HOTKEY_EVENT, action)?;
+        }
+        // 最近显示气泡的桌宠播放下一句，没有气泡时交给主桌宠
+        HotkeyAction::PlayNext => {
+            let pet = state.windows.latest_bubble_pet().unwrap_or_else(|| MAIN_PET.to_string());
+            app.emit_to(pet_label(&pet), HOTKEY_EVENT, action)?;
+        }
+        HotkeyAction::ToggleVisible => {
+            let visible = state.config.read().await.behavior.pets_visible;
+            state.windows.set_pets_visible(!visible).await?;
+            tray::refresh_menu(app).await;
+        }
+        HotkeyAction::ToggleClickThrough => {
+            state.windows.toggle_click_through()?;
+        }
+    }
+    Ok(())
+}
+
+/// 按当前配置注册快捷键，并把错误通知设置窗口
+pub async fn register_from_config(app: &AppHandle, config: &ConfigStore) {
+    let hotkeys = config.read().await.hotkeys.clone();
+    let errors = app.state::<AppState>().hotkeys.apply(app, &hotkeys);
+    if let Err(e) = app.emit(HOTKEY_ERRORS_EVENT, &errors) {
+        eprintln!("发送快捷键错误失败: {}", e);
+    }
+}
+
+/// 配置被外部修改或切换档案后重新注册快捷键，一直运行到应用退出
+pub async fn run_hotkey_sync(app: AppHandle, config: Arc<ConfigStore>) {
+    let mut changes = config.subscribe();
+    loop {
+        match changes.recv().await {
+            Ok(change) if change.hotkeys.is_none() => continue,
+            Ok(_) | Err(RecvError::Lagged(_)) => register_from_config(&app, &config).await,
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试用的解析：忽略大小写，`?` 表示无法识别
+    fn parse(binding: &str) -> Result<u64, String> {
+        if binding.contains('?') {
+            return Err(binding.to_string());
+        }
+        Ok(binding.to_lowercase().bytes().map(u64::from).sum())
+    }
+
+    #[test]
+    fn test_resolve_skips_empty_and_reports_invalid_or_conflicting_bindings() {
+        let config = HotkeyConfig {
+            focus_chat: "Ctrl+Space".to_string(),
+            play_next: " ".to_string(),
+            toggle_visible: "Ctrl+?".to_string(),
+            toggle_click_through: "ctrl+space".to_string(),
+        };
+        let (bindings, errors) = resolve(&config, parse);
+        assert_eq!(bindings.iter().map(|(action, _)| *action).collect::<Vec<_>>(), vec![HotkeyAction::FocusChat]);
+        let reasons: Vec<(&str, HotkeyFailure)> = errors.iter().map(|error| (error.field.as_str(), error.reason)).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("hotkeys.toggle_visible", HotkeyFailure::Invalid),
+                ("hotkeys.toggle_click_through", HotkeyFailure::Conflict),
+            ]
+        );
+        assert!(errors[1].message.contains("hotkeys.focus_chat"));
+
+        let (bindings, errors) = resolve(&HotkeyConfig::default(), parse);
+        assert_eq!((bindings.len(), errors.len()), (4, 0));
+    }
+}
@@ -4,15 +4,35 @@
  * @features
  *   - 应用程序构建和配置
  *   - 状态管理和配置持久化
+ *   - 配置文件热重载与 config-changed 事件
+ *   - 配置档案切换
+ *   - 配置包导出与导入（配置、记忆、角色包引用）
+ *   - 环境变量与命令行覆盖配置 (--config-dir、--config、--profile、--set)
  *   - 平台特定设置 (macOS)
- *   - 窗口位置和大小管理
+ *   - 窗口位置和大小管理（多显示器下把主窗口放回可见的工作区）
+ *   - 后端创建与登记窗口，聊天气泡跟随桌宠
+ *   - 全局快捷键（聚焦聊天输入框、播放下一句、显示或隐藏桌宠、鼠标穿透）
+ *   - 托盘菜单（显示或隐藏桌宠、打开设置、找回桌宠、切换角色与档案、暂停 AI、退出）
  *   - 命令处理器注册
+ *   - 角色包立绘的自定义 URI 协议 (character://)
  *   - 插件集成
  * @modules
+ *   - ai: AI对话
+ *   - bundle: 配置导出与导入
+ *   - character: 角色包
+ *   - emotion: 表情注册表
+ *   - error: 统一的命令错误
+ *   - history: 对话历史
+ *   - hotkeys: 全局快捷键
+ *   - lingchat: LingChat 角色导入
+ *   - memory: 长期记忆
  *   - macos: macOS平台特定功能
  *   - config: 配置管理
+ *   - display: 显示器与桌宠窗口位置
  *   - commands: Tauri命令处理
  *   - state: 应用状态管理
+ *   - tray: 托盘图标与菜单
+ *   - window_manager: 窗口管理与聊天气泡位置
  * @dependencies
  *   - tauri: Tauri框架核心
  *   - tokio: 异步运行时
@@ -22,57 +42,132 @@
  */
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::Manager;
 
 // 模块导入
+mod ai;
+mod bundle;
+mod character;
 mod commands;
 mod config;
+mod display;
+mod emotion;
+mod error;
+mod history;
+mod hotkeys;
+mod lingchat;
 mod macos;
+mod memory;
 mod state;
+mod tray;
+mod window_manager;
 mod windows;
 
+use character::CharacterStore;
 use commands::*;
-use config::ConfigManager;
+use config::{ConfigStore, Overrides, ProfileStore};
+use history::HistoryStore;
+use hotkeys::HotkeyManager;
+use memory::MemoryStore;
 use state::AppState;
+use window_manager::WindowManager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .setup(|app| {
-            // 创建配置管理器
-            let config_manager = ConfigManager::new("desktop_pet")
-                .map_err(|e| format!("初始化配置管理器失败: {}", e))?;
+            // 环境变量与命令行的覆盖，可以指定配置目录、配置文件、档案和具体字段
+            let overrides = Overrides::from_env_and_args()
+                .map_err(|e| format!("解析启动参数失败: {}", e))?;
+
+            // 配置目录
+            let config_dir = overrides
+                .config_dir("desktop_pet")
+                .map_err(|e| format!("获取配置目录失败: {}", e))?;
+
+            // 打开对话历史数据库
+            let history = HistoryStore::open(&config_dir.join("history.db"))
+                .map_err(|e| format!("打开对话历史失败: {}", e))?;
+
+            // 打开长期记忆数据库
+            let memory = MemoryStore::open(&config_dir.join("memory.db"))
+                .map_err(|e| format!("打开长期记忆失败: {}", e))?;
+
+            // 内置角色包随应用资源发布，用户安装的角色包放在配置目录
+            let builtin_characters = app
+                .path()
+                .resource_dir()
+                .map_err(|e| format!("获取资源目录失败: {}", e))?
+                .join("characters");
+            let characters = CharacterStore::new(builtin_characters, config_dir.join("characters"));
+
+            // 读取当前配置档案，之后的读写都在内存中进行，由后台任务写盘
+            let mut profiles = ProfileStore::new(config_dir);
+            if let Some(file) = overrides.config_file() {
+                profiles = profiles.with_config_file(file.to_path_buf());
+            }
+            let manager = tauri::async_runtime::block_on(profiles.init(overrides.profile()))
+                .map_err(|e| format!("读取配置档案失败: {}", e))?;
+            let config = tauri::async_runtime::block_on(ConfigStore::open(manager, overrides))
+                .map_err(|e| format!("读取配置失败: {}", e))?;
+            let config = Arc::new(config);
+            tauri::async_runtime::spawn(config.clone().run_writer());
+            // 配置文件被外部修改时重新读取，变更通过 config-changed 事件通知所有窗口，无法读取时发出 config-error 事件
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(config.clone().run_watcher(config::WATCH_INTERVAL, move |error, path| {
+                emit_config_error(&app_handle, error, path)
+            }));
+            tauri::async_runtime::spawn(forward_config_changes(app.handle().clone(), config.clone()));
+
+            // 主桌宠窗口由配置文件创建，其他桌宠、气泡与设置窗口由窗口管理器创建
+            let main_window = app.get_webview_window(&window_manager::pet_label(config::MAIN_PET)).unwrap();
+            let windows = WindowManager::new(app.handle().clone(), config.clone());
+            windows.attach_pet(config::MAIN_PET, &main_window);
 
             // 设置应用状态
             let app_state = AppState {
-                config_manager: Arc::new(tokio::sync::Mutex::new(config_manager)),
+                config,
+                profiles: Arc::new(profiles),
+                chat_streams: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+                history: Arc::new(history),
+                memory: Arc::new(memory),
+                characters: Arc::new(characters),
+                windows,
+                hotkeys: HotkeyManager::new(),
             };
 
-            // 异步加载窗口配置并设置主窗口位置
-            let config_manager_clone = app_state.config_manager.clone();
-            let main_window = app.get_webview_window("main").unwrap();
+            // 恢复主窗口位置并打开配置中的其他桌宠，保存时的显示器不可用时移到可见的工作区；之后显示器布局变化时重新放置
+            let config_clone = app_state.config.clone();
             let main_window_clone = main_window.clone();
+            let windows_clone = app_state.windows.clone();
+            let app_handle = app.handle().clone();
 
             tauri::async_runtime::spawn(async move {
-                let manager = config_manager_clone.lock().await;
-                if let Ok(window_config) = manager.get_window().await {
-                    if let Ok(size) = main_window_clone.inner_size() {
-                        let width = size.width as f64;
-                        let height = size.height as f64;
-
-                        // 中心点 => 左上角
-                        let left = window_config.main_window_x - width / 2.0;
-                        let top = window_config.main_window_y - height / 2.0;
-
-                        let _ =
-                            main_window_clone.set_position(tauri::LogicalPosition::new(left, top));
-                    }
+                if let Err(e) = display::restore_pet(&main_window_clone, &config_clone, config::MAIN_PET).await {
+                    eprintln!("恢复主窗口位置失败: {}", e);
                 }
+                windows_clone.sync_pets().await;
+                windows_clone.apply_visibility().await;
+                tauri::async_runtime::spawn(windows_clone.run_pet_sync());
+                display::run_display_watcher(app_handle, config_clone, display::DISPLAY_WATCH_INTERVAL).await;
             });
 
+            let config_clone = app_state.config.clone();
             app.manage(app_state);
 
+            // 托盘菜单的勾选状态随配置更新
+            tray::create(app.handle()).map_err(|e| format!("创建托盘图标失败: {}", e))?;
+            tauri::async_runtime::spawn(tray::run_menu_sync(app.handle().clone(), config_clone.clone()));
+
+            // 注册配置中的全局快捷键，之后随配置变化重新注册
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                hotkeys::register_from_config(&app_handle, &config_clone).await;
+                hotkeys::run_hotkey_sync(app_handle, config_clone).await;
+            });
+
             // 设置平台特定配置
             if macos::is_macos() {
                 macos::setup_app();
@@ -93,15 +188,107 @@ pub fn run() {
 
             Ok(())
         })
+        .register_uri_scheme_protocol("character", |ctx, request| {
+            let state = ctx.app_handle().state::<AppState>();
+            character_asset_response(&state.characters, request.uri().path())
+        })
         .plugin(tauri_plugin_opener::init())
+        .plugin(hotkeys::plugin())
         .invoke_handler(tauri::generate_handler![
             load_config,
             save_config,
+            validate_config,
+            patch_appearance,
+            patch_ai,
+            patch_window,
+            patch_behavior,
+            patch_hotkeys,
+            get_hotkey_errors,
+            take_config_recovery,
+            get_config_sources,
+            export_config,
+            import_config,
+            list_profiles,
+            create_profile,
+            clone_profile,
+            rename_profile,
+            delete_profile,
+            activate_profile,
             get_ai_config,
             get_appearance_config,
             get_window_config,
+            chat_with_pet,
+            chat_with_pet_stream,
+            cancel_chat_stream,
+            test_ai_connection,
+            list_chat_sessions,
+            new_chat_session,
+            get_chat_messages,
+            clear_chat_session,
+            delete_chat_session,
+            clear_chat_history,
+            list_memories,
+            add_memory,
+            update_memory,
+            delete_memory,
+            list_characters,
+            get_active_character,
+            install_character,
+            import_lingchat_character,
+            uninstall_character,
+            switch_character,
+            list_emotions,
+            list_windows,
+            open_settings,
+            close_settings,
+            show_bubble,
+            append_bubble_messages,
+            set_bubble_index,
+            close_bubble,
+            get_bubble_state,
+            list_pets,
+            add_pet,
+            remove_pet,
+            patch_pet,
             quit_app
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // 退出前把去抖中尚未落盘的配置写入磁盘
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<AppState>();
+                if let Err(e) = tauri::async_runtime::block_on(state.config.flush()) {
+                    eprintln!("保存配置失败: {}", e);
+                }
+            }
+        });
+}
+
+/// 响应 `character://localhost/<id>/sprites/<表情>` 与 `character://localhost/<id>/avatar`
+fn character_asset_response(characters: &CharacterStore, path: &str) -> tauri::http::Response<Vec<u8>> {
+    let path = urlencoding::decode(path).map(|path| path.into_owned()).unwrap_or_default();
+    let file = characters
+        .resolve_asset(&path)
+        .and_then(|file| std::fs::read(&file).map(|bytes| (file, bytes)).map_err(Into::into));
+    match file {
+        Ok((file, bytes)) => {
+            let mime = match file.extension().and_then(|ext| ext.to_str()) {
+                Some("png") => "image/png",
+                Some("jpg") | Some("jpeg") => "image/jpeg",
+                Some("gif") => "image/gif",
+                Some("webp") => "image/webp",
+                _ => "application/octet-stream",
+            };
+            tauri::http::Response::builder()
+                .header("Content-Type", mime)
+                .header("Access-Control-Allow-Origin", "*")
+                .body(bytes)
+                .unwrap_or_default()
+        }
+        Err(e) => tauri::http::Response::builder()
+            .status(404)
+            .body(e.to_string().into_bytes())
+            .unwrap_or_default(),
+    }
 }
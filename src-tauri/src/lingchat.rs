@@ -0,0 +1,313 @@
+/*!
+ * @fileoverview LingChat 角色导入模块
+ * @description 把 LingChat 的角色目录（settings.txt 中的人设与 avatar/ 下的表情立绘）转换为本应用的角色包并安装
+ * @features
+ *   - 宽松解析 settings.txt：`key = "值"`，支持单引号与三引号多行字符串，忽略注释和无法识别的行
+ *   - 按内置角色的表情名、英文标识和别名匹配立绘文件名
+ *   - 报告无法对应的图片与缺少立绘的表情，而不是静默丢弃
+ * @structures
+ *   - LingChatImport: 导入结果（安装的角色、未识别的图片、缺少立绘的表情）
+ *   - LingChatError: 导入错误
+ * @source
+ *   <角色目录>/settings.txt     system_prompt、ai_name 等设置
+ *   <角色目录>/avatar/         以表情命名的立绘（如 高兴.png），头像.png 为角色头像
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::character::{CharacterError, CharacterManifest, CharacterStore, CharacterSummary, EmotionEntry, MANIFEST_FILE};
+use crate::emotion::EmotionRegistry;
+use crate::history::now_millis;
+
+/// LingChat 的角色设置文件
+pub const SETTINGS_FILE: &str = "settings.txt";
+
+/// LingChat 中作为角色头像的图片
+const AVATAR_STEM: &str = "头像";
+
+/// 可以作为立绘的图片格式
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+
+// LingChat 导入错误类型
+#[derive(Debug)]
+pub enum LingChatError {
+    IoError(std::io::Error),
+    SettingsNotFound(String),
+    PromptNotFound,
+    NoSprites,
+    CharacterError(CharacterError),
+}
+
+impl fmt::Display for LingChatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LingChatError::IoError(e) => write!(f, "IO错误: {}", e),
+            LingChatError::SettingsNotFound(path) => write!(f, "不是 LingChat 角色目录，找不到 {}: {}", SETTINGS_FILE, path),
+            LingChatError::PromptNotFound => write!(f, "{} 中没有 system_prompt", SETTINGS_FILE),
+            LingChatError::NoSprites => write!(f, "没有任何图片能对应到已知表情"),
+            LingChatError::CharacterError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for LingChatError {}
+
+impl From<std::io::Error> for LingChatError {
+    fn from(e: std::io::Error) -> Self {
+        LingChatError::IoError(e)
+    }
+}
+
+impl From<CharacterError> for LingChatError {
+    fn from(e: CharacterError) -> Self {
+        LingChatError::CharacterError(e)
+    }
+}
+
+/// 导入结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LingChatImport {
+    /// 安装后的角色
+    pub character: CharacterSummary,
+    /// 无法对应到已知表情的图片文件名，未被导入
+    pub unmapped_images: Vec<String>,
+    /// 没有对应立绘的已知表情
+    pub missing_emotions: Vec<String>,
+}
+
+/// 宽松解析 settings.txt，返回其中的字符串设置
+pub fn parse_settings(content: &str) -> HashMap<String, String> {
+    let mut settings = HashMap::new();
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let value = value.trim();
+
+        let quote = ["\"\"\"", "'''", "\"", "'"].into_iter().find(|quote| value.starts_with(quote));
+        let Some(quote) = quote else {
+            continue;
+        };
+        let rest = &value[quote.len()..];
+        if let Some(end) = rest.find(quote) {
+            settings.insert(key, rest[..end].to_string());
+        } else if quote.len() == 3 {
+            // 多行字符串，一直读到结束的三引号
+            let mut text: Vec<&str> = if rest.is_empty() { Vec::new() } else { vec![rest] };
+            for line in lines.by_ref() {
+                if let Some(end) = line.find(quote) {
+                    text.push(&line[..end]);
+                    break;
+                }
+                text.push(line);
+            }
+            settings.insert(key, text.join("\n"));
+        }
+    }
+    settings
+}
+
+/// 立绘所在目录：avatar/，没有时为角色目录本身
+fn sprite_dir(source: &Path) -> PathBuf {
+    let avatar = source.join("avatar");
+    if avatar.is_dir() { avatar } else { source.to_path_buf() }
+}
+
+/// 由目录名生成稳定的角色 id，重复导入同一角色时会替换之前的导入
+fn character_id(source: &Path) -> String {
+    let name = source.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    // FNV-1a
+    let hash = name.bytes().fold(0x811c9dc5u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193));
+    format!("lingchat-{:08x}", hash)
+}
+
+/// 把 LingChat 角色目录转换为角色包，写入 `target`
+///
+/// `vocabulary` 为已知表情（内置角色的表情注册表），立绘文件名按表情名、英文标识或别名匹配。
+/// 返回 (未识别的图片, 缺少立绘的表情)。
+pub fn convert(
+    source: &Path,
+    vocabulary: &EmotionRegistry,
+    target: &Path,
+) -> Result<(Vec<String>, Vec<String>), LingChatError> {
+    let settings_path = source.join(SETTINGS_FILE);
+    if !settings_path.is_file() {
+        return Err(LingChatError::SettingsNotFound(source.display().to_string()));
+    }
+    let settings = parse_settings(&fs::read_to_string(&settings_path)?);
+    let prompt = settings
+        .get("system_prompt")
+        .map(|prompt| prompt.trim())
+        .filter(|prompt| !prompt.is_empty())
+        .ok_or(LingChatError::PromptNotFound)?;
+
+    let mut images: Vec<PathBuf> = fs::read_dir(sprite_dir(source))?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+        })
+        .collect();
+    images.sort();
+
+    let sprites = target.join("sprites");
+    fs::create_dir_all(&sprites)?;
+    let mut avatar = None;
+    let mut mapped: HashMap<&str, String> = HashMap::new();
+    let mut unmapped_images = Vec::new();
+    for image in &images {
+        let file_name = image.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let stem = image.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        if stem == AVATAR_STEM {
+            fs::copy(image, sprites.join(&file_name))?;
+            avatar = Some(format!("sprites/{}", file_name));
+            continue;
+        }
+        match vocabulary.resolve(&stem) {
+            // 同一表情有多张图片时只保留第一张
+            Some(name) if !mapped.contains_key(name) => {
+                fs::copy(image, sprites.join(&file_name))?;
+                mapped.insert(name, format!("sprites/{}", file_name));
+            }
+            _ => unmapped_images.push(file_name),
+        }
+    }
+    if mapped.is_empty() {
+        return Err(LingChatError::NoSprites);
+    }
+
+    let mut emotions = Vec::new();
+    let mut missing_emotions = Vec::new();
+    for emotion in vocabulary.emotions() {
+        match mapped.get(emotion.name.as_str()) {
+            Some(sprite) => emotions.push(EmotionEntry {
+                name: emotion.name.clone(),
+                sprite: sprite.clone(),
+                key: emotion.key.clone(),
+                aliases: emotion.aliases.clone(),
+                color: emotion.color.clone(),
+            }),
+            None => missing_emotions.push(emotion.name.clone()),
+        }
+    }
+    let default_emotion = if mapped.contains_key(vocabulary.default_emotion()) {
+        vocabulary.default_emotion().to_string()
+    } else {
+        emotions[0].name.clone()
+    };
+
+    let name = ["ai_name", "title"]
+        .iter()
+        .filter_map(|key| settings.get(*key))
+        .map(|name| name.trim())
+        .find(|name| !name.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| source.file_name().unwrap_or_default().to_string_lossy().into_owned());
+    let manifest = CharacterManifest {
+        id: character_id(source),
+        name,
+        version: String::new(),
+        author: String::new(),
+        description: settings.get("info").map(|info| info.trim().to_string()).unwrap_or_default(),
+        prompt: prompt.to_string(),
+        user_persona: None,
+        default_emotion,
+        avatar,
+        emotions,
+    };
+    let content = toml::to_string(&manifest).map_err(|e| CharacterError::InvalidPack(vec![e.to_string()]))?;
+    fs::write(target.join(MANIFEST_FILE), content)?;
+
+    Ok((unmapped_images, missing_emotions))
+}
+
+/// 把 LingChat 角色目录转换为角色包并安装
+pub fn import(
+    source: &Path,
+    vocabulary: &EmotionRegistry,
+    store: &CharacterStore,
+) -> Result<LingChatImport, LingChatError> {
+    let staging = std::env::temp_dir().join(format!("desktop_pet_lingchat_{}", now_millis()));
+    let result = convert(source, vocabulary, &staging).and_then(|(unmapped_images, missing_emotions)| {
+        let pack = store.install(&staging)?;
+        Ok(LingChatImport { character: pack.summary(), unmapped_images, missing_emotions })
+    });
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emotion::builtin_registry;
+
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/lingchat/钦灵")
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        let settings = parse_settings(
+            "# 角色设定\nai_name = \"钦灵\"\ntitle='诺一钦灵'\nscale = 1.0\nsystem_prompt = \"\"\"\n第一行\n第二行\n\"\"\"\ninfo = \"白狼娘\"",
+        );
+        assert_eq!(settings["ai_name"], "钦灵");
+        assert_eq!(settings["title"], "诺一钦灵");
+        assert_eq!(settings["system_prompt"], "第一行\n第二行\n");
+        assert_eq!(settings["info"], "白狼娘");
+        assert!(!settings.contains_key("scale"));
+    }
+
+    #[test]
+    fn test_import_fixture_reports_unmapped_and_missing() {
+        let root = std::env::temp_dir().join(format!("desktop_pet_lingchat_test_{}", now_millis()));
+        let store = CharacterStore::new(root.join("builtin"), root.join("user"));
+
+        let report = import(&fixture(), &builtin_registry(), &store).unwrap();
+        assert_eq!(report.character.name, "钦灵");
+        assert_eq!(report.character.emotions, vec!["正常", "高兴", "无奈"]);
+        assert_eq!(report.character.default_emotion, "正常");
+        assert!(report.character.has_avatar);
+        assert_eq!(report.unmapped_images, vec!["打哈欠.png"]);
+        assert_eq!(report.missing_emotions.len(), builtin_registry().emotions().len() - 3);
+        assert!(report.missing_emotions.contains(&"伤心".to_string()));
+
+        let pack = store.get(&report.character.id).unwrap();
+        assert!(pack.system_prompt().contains("你叫钦灵"));
+        assert!(pack.sprite_path("无奈").ends_with("sprites/无语.png"));
+        assert_eq!(pack.emotion_registry().normalize("sad"), "正常");
+
+        // 同一目录再次导入时替换之前的角色
+        let again = import(&fixture(), &builtin_registry(), &store).unwrap();
+        assert_eq!(again.character.id, report.character.id);
+        assert_eq!(store.list().len(), 1);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_import_rejects_non_lingchat_directory() {
+        let root = std::env::temp_dir().join(format!("desktop_pet_lingchat_empty_{}", now_millis()));
+        fs::create_dir_all(&root).unwrap();
+        let store = CharacterStore::new(root.join("builtin"), root.join("user"));
+
+        let result = import(&root, &builtin_registry(), &store);
+        assert!(matches!(result, Err(LingChatError::SettingsNotFound(_))));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
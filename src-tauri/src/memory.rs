@@ -0,0 +1,411 @@
+/*!
+ * @fileoverview 长期记忆模块
+ * @description 保存桌宠跨会话记住的关于用户的事实，并在对话时按关键词检索相关记忆
+ * @features
+ *   - 记忆的增删改查，记录时间戳与来源（自动提取 / 手动添加）
+ *   - 自动提取的记忆记录来源会话与轮次
+ *   - 导入配置包中的记忆时保留原来的时间戳，跳过已有的记忆
+ *   - 本地 BM25 检索，中文按二元组切分，无需向量服务
+ * @structures
+ *   - MemoryStore: 记忆存储
+ *   - Memory: 单条记忆
+ *   - MemorySource: 记忆来源
+ * @storage
+ *   - 数据库路径: 配置目录下的 memory.db
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::history::now_millis;
+
+// 记忆错误类型
+#[derive(Debug)]
+pub enum MemoryError {
+    SqliteError(rusqlite::Error),
+    IoError(std::io::Error),
+    MemoryNotFound(i64),
+    EmptyContent,
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MemoryError::SqliteError(e) => write!(f, "数据库错误: {}", e),
+            MemoryError::IoError(e) => write!(f, "IO错误: {}", e),
+            MemoryError::MemoryNotFound(id) => write!(f, "记忆不存在: {}", id),
+            MemoryError::EmptyContent => write!(f, "记忆内容不能为空"),
+        }
+    }
+}
+
+impl Error for MemoryError {}
+
+impl From<rusqlite::Error> for MemoryError {
+    fn from(e: rusqlite::Error) -> Self {
+        MemoryError::SqliteError(e)
+    }
+}
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS memories (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    content     TEXT NOT NULL,
+    source      TEXT NOT NULL,
+    session_id  INTEGER,
+    turn        INTEGER,
+    created_at  INTEGER NOT NULL,
+    updated_at  INTEGER NOT NULL
+);
+"#;
+
+/// 记忆来源
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemorySource {
+    /// 对话结束后由模型提取
+    Auto,
+    /// 在设置窗口中手动添加
+    Manual,
+}
+
+impl MemorySource {
+    fn as_str(self) -> &'static str {
+        match self {
+            MemorySource::Auto => "auto",
+            MemorySource::Manual => "manual",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "auto" => MemorySource::Auto,
+            _ => MemorySource::Manual,
+        }
+    }
+}
+
+/// 单条记忆
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Memory {
+    pub id: i64,
+    pub content: String,
+    pub source: MemorySource,
+    /// 自动提取时所在的会话与轮次
+    pub session_id: Option<i64>,
+    pub turn: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// BM25 参数
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+// 记忆存储
+pub struct MemoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl MemoryStore {
+    /// 打开（或创建）数据库文件
+    pub fn open(path: &Path) -> Result<Self, MemoryError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(MemoryError::IoError)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self, MemoryError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, MemoryError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        // 持锁线程 panic 不影响数据库本身，继续使用
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 全部记忆，最近更新的在前
+    pub fn list(&self) -> Result<Vec<Memory>, MemoryError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, content, source, session_id, turn, created_at, updated_at
+             FROM memories ORDER BY updated_at DESC, id DESC",
+        )?;
+        let memories = stmt
+            .query_map([], |row| {
+                Ok(Memory {
+                    id: row.get(0)?,
+                    content: row.get(1)?,
+                    source: MemorySource::parse(&row.get::<_, String>(2)?),
+                    session_id: row.get(3)?,
+                    turn: row.get(4)?,
+                    created_at: row.get(5)?,
+                    updated_at: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(memories)
+    }
+
+    /// 添加一条记忆；与已有记忆内容相同时返回已有记忆的 id
+    pub fn add(
+        &self,
+        content: &str,
+        source: MemorySource,
+        session_id: Option<i64>,
+        turn: Option<i64>,
+    ) -> Result<i64, MemoryError> {
+        let content = content.trim();
+        if content.is_empty() {
+            return Err(MemoryError::EmptyContent);
+        }
+        let conn = self.conn();
+        let existing: Option<i64> = conn
+            .query_row("SELECT id FROM memories WHERE content = ?1", [content], |row| row.get(0))
+            .optional()?;
+        if let Some(id) = existing {
+            return Ok(id);
+        }
+
+        let now = now_millis();
+        conn.execute(
+            "INSERT INTO memories (content, source, session_id, turn, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            params![content, source.as_str(), session_id, turn, now],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 导入其他机器上的记忆（内容、来源、创建与更新时间），保留原来的时间戳；与已有记忆内容相同时跳过，返回导入的条数
+    ///
+    /// `replace` 时先删除全部记忆。整个导入在一个事务中完成，出错时不修改任何记忆。
+    pub fn import<'a>(
+        &self,
+        entries: impl IntoIterator<Item = (&'a str, MemorySource, i64, i64)>,
+        replace: bool,
+    ) -> Result<usize, MemoryError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        if replace {
+            tx.execute("DELETE FROM memories", [])?;
+        }
+        let mut imported = 0;
+        for (content, source, created_at, updated_at) in entries {
+            let content = content.trim();
+            if content.is_empty() {
+                return Err(MemoryError::EmptyContent);
+            }
+            imported += tx.execute(
+                "INSERT INTO memories (content, source, session_id, turn, created_at, updated_at)
+                 SELECT ?1, ?2, NULL, NULL, ?3, ?4
+                 WHERE NOT EXISTS (SELECT 1 FROM memories WHERE content = ?1)",
+                params![content, source.as_str(), created_at, updated_at],
+            )?;
+        }
+        tx.commit()?;
+        Ok(imported)
+    }
+
+    /// 修改记忆内容
+    pub fn update(&self, id: i64, content: &str) -> Result<(), MemoryError> {
+        let content = content.trim();
+        if content.is_empty() {
+            return Err(MemoryError::EmptyContent);
+        }
+        let changed = self.conn().execute(
+            "UPDATE memories SET content = ?2, updated_at = ?3 WHERE id = ?1",
+            params![id, content, now_millis()],
+        )?;
+        if changed == 0 {
+            return Err(MemoryError::MemoryNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// 删除记忆
+    pub fn delete(&self, id: i64) -> Result<(), MemoryError> {
+        let changed = self.conn().execute("DELETE FROM memories WHERE id = ?1", [id])?;
+        if changed == 0 {
+            return Err(MemoryError::MemoryNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// 按 BM25 检索与 `query` 最相关的至多 `limit` 条记忆，不相关的记忆不会返回
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<Memory>, MemoryError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let memories = self.list()?;
+        let documents: Vec<Vec<String>> = memories.iter().map(|memory| tokenize(&memory.content)).collect();
+        let scores = bm25_scores(&tokenize(query), &documents);
+
+        let mut ranked: Vec<(f64, Memory)> = scores
+            .into_iter()
+            .zip(memories)
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+        // 分数相同时保持最近更新的在前
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(ranked.into_iter().take(limit).map(|(_, memory)| memory).collect())
+    }
+}
+
+/// CJK 统一表意文字与日文假名
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF)
+}
+
+/// 分词：英文与数字按连续片段切分并转小写，中日文按相邻二元组切分（单字片段保留单字）
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut run: Vec<char> = Vec::new();
+
+    let flush_run = |run: &mut Vec<char>, tokens: &mut Vec<String>| {
+        match run.len() {
+            0 => {}
+            1 => tokens.push(run[0].to_string()),
+            _ => tokens.extend(run.windows(2).map(|pair| pair.iter().collect())),
+        }
+        run.clear();
+    };
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            run.push(c);
+        } else {
+            flush_run(&mut run, &mut tokens);
+            if c.is_alphanumeric() {
+                word.extend(c.to_lowercase());
+            } else if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+        }
+    }
+    flush_run(&mut run, &mut tokens);
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens
+}
+
+/// 计算每篇文档对查询的 BM25 分数
+fn bm25_scores(query: &[String], documents: &[Vec<String>]) -> Vec<f64> {
+    if documents.is_empty() {
+        return Vec::new();
+    }
+    let total = documents.len() as f64;
+    let average_length = documents.iter().map(Vec::len).sum::<usize>() as f64 / total;
+    let query_terms: HashSet<&String> = query.iter().collect();
+
+    let mut document_frequency: HashMap<&String, usize> = HashMap::new();
+    for document in documents {
+        let terms: HashSet<&String> = document.iter().collect();
+        for term in terms.intersection(&query_terms) {
+            *document_frequency.entry(term).or_default() += 1;
+        }
+    }
+
+    documents
+        .iter()
+        .map(|document| {
+            let length = document.len() as f64;
+            query_terms
+                .iter()
+                .filter_map(|term| {
+                    let frequency = document.iter().filter(|token| token == term).count() as f64;
+                    if frequency == 0.0 {
+                        return None;
+                    }
+                    let df = document_frequency[term] as f64;
+                    let idf = ((total - df + 0.5) / (df + 0.5) + 1.0).ln();
+                    let norm = BM25_K1 * (1.0 - BM25_B + BM25_B * length / average_length.max(1.0));
+                    Some(idf * frequency * (BM25_K1 + 1.0) / (frequency + norm))
+                })
+                .sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_mixed_text() {
+        assert_eq!(tokenize("我的生日"), vec!["我的", "的生", "生日"]);
+        assert_eq!(tokenize("喜欢 Vue3 和 Rust!"), vec!["喜欢", "vue3", "和", "rust"]);
+    }
+
+    #[test]
+    fn test_crud_and_deduplication() {
+        let store = MemoryStore::open_in_memory().unwrap();
+        let id = store.add("我的名字是莱姆", MemorySource::Auto, Some(1), Some(3)).unwrap();
+        assert_eq!(store.add(" 我的名字是莱姆 ", MemorySource::Manual, None, None).unwrap(), id);
+        assert!(matches!(store.add("  ", MemorySource::Manual, None, None), Err(MemoryError::EmptyContent)));
+
+        store.update(id, "我的名字是莱姆，喜欢被叫作小莱").unwrap();
+        let memories = store.list().unwrap();
+        assert_eq!(memories.len(), 1);
+        assert_eq!(memories[0].source, MemorySource::Auto);
+        assert_eq!(memories[0].session_id, Some(1));
+        assert!(memories[0].content.contains("小莱"));
+
+        store.delete(id).unwrap();
+        assert!(matches!(store.delete(id), Err(MemoryError::MemoryNotFound(_))));
+    }
+
+    #[test]
+    fn test_import_is_all_or_nothing() {
+        let store = MemoryStore::open_in_memory().unwrap();
+        store.add("我在学习 Rust 编程", MemorySource::Manual, None, None).unwrap();
+        let entries = [("我的生日是5月3日", MemorySource::Auto, 1, 2), ("我在学习 Rust 编程", MemorySource::Auto, 1, 2)];
+        assert_eq!(store.import(entries, false).unwrap(), 1);
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        // 中途出错时已清空与已导入的记忆都被撤销
+        let invalid = [("我喜欢猫", MemorySource::Auto, 1, 2), (" ", MemorySource::Auto, 1, 2)];
+        assert!(matches!(store.import(invalid, true), Err(MemoryError::EmptyContent)));
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        assert_eq!(store.import([("我喜欢猫", MemorySource::Auto, 1, 2)], true).unwrap(), 1);
+        let memories = store.list().unwrap();
+        assert_eq!((memories.len(), memories[0].created_at), (1, 1));
+    }
+
+    #[test]
+    fn test_search_ranks_relevant_memories() {
+        let store = MemoryStore::open_in_memory().unwrap();
+        store.add("我的生日是5月3日", MemorySource::Auto, None, None).unwrap();
+        store.add("我最喜欢的食物是草莓蛋糕", MemorySource::Auto, None, None).unwrap();
+        store.add("我在学习 Rust 编程", MemorySource::Manual, None, None).unwrap();
+
+        let results = store.search("你还记得我生日是哪天吗", 2).unwrap();
+        assert_eq!(results[0].content, "我的生日是5月3日");
+
+        let results = store.search("rust 好难", 5).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].content.contains("Rust"));
+
+        assert!(store.search("天气", 5).unwrap().is_empty());
+    }
+}
@@ -3,24 +3,56 @@
  * @description 定义和管理应用的全局状态，提供线程安全的状态访问
  * @features
  *   - 全局应用状态定义
- *   - 配置管理器的线程安全封装
+ *   - 内存中的配置（唯一可信来源，后台写盘）
+ *   - 配置档案
+ *   - 进行中的流式对话任务登记（用于取消）
+ *   - 对话历史存储
+ *   - 长期记忆存储
+ *   - 角色包存储
+ *   - 窗口管理（桌宠、聊天气泡、设置窗口）
+ *   - 已注册的全局快捷键
  *   - Arc + Mutex模式确保并发安全
  * @structures
  *   - AppState: 应用全局状态结构
+ *   - ChatStream: 进行中的流式对话
  * @concurrency
  *   - 使用Arc<Mutex<T>>模式实现线程安全
  *   - 支持多线程并发访问配置
  * @note
- *   聊天气泡相关状态已移除，功能完全由前端管理
+ *   聊天气泡的内容与位置由 WindowManager 维护，通过 bubble-state 事件推送给气泡窗口
  * @author dada
  * @version 2.0.0
  * @since 2025-07-13
  */
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use crate::config::ConfigManager;
+use crate::character::CharacterStore;
+use crate::config::{ConfigStore, ProfileStore};
+use crate::history::HistoryStore;
+use crate::hotkeys::HotkeyManager;
+use crate::memory::MemoryStore;
+use crate::window_manager::WindowManager;
+
+/// 进行中的流式对话；准备上下文期间还没有任务，取消只记下标记，开始生成前检查
+#[derive(Default)]
+pub struct ChatStream {
+    pub cancelled: bool,
+    pub task: Option<tokio::task::AbortHandle>,
+}
 
 // 全局状态管理
 pub struct AppState {
-    pub config_manager: Arc<tokio::sync::Mutex<ConfigManager>>,
+    pub config: Arc<ConfigStore>,
+    /// 配置档案，切换时由 `config` 改为读写另一个文件
+    pub profiles: Arc<ProfileStore>,
+    /// 进行中的流式对话，键为前端生成的 stream_id
+    pub chat_streams: Arc<tokio::sync::Mutex<HashMap<String, ChatStream>>>,
+    pub history: Arc<HistoryStore>,
+    pub memory: Arc<MemoryStore>,
+    pub characters: Arc<CharacterStore>,
+    /// 按标签创建与登记窗口，聊天气泡的内容与位置由它维护
+    pub windows: Arc<WindowManager>,
+    /// 已注册的全局快捷键及无法注册的错误
+    pub hotkeys: Arc<HotkeyManager>,
 }
@@ -0,0 +1,227 @@
+/*!
+ * @fileoverview 托盘图标模块
+ * @description 在系统托盘提供桌宠的常用操作，隐藏或拖出屏幕的桌宠可以从这里找回
+ * @features
+ *   - 显示或隐藏所有桌宠
+ *   - 打开设置窗口
+ *   - 把桌宠移回主显示器中央
+ *   - 切换主桌宠的角色与配置档案
+ *   - 暂停或恢复 AI 对话
+ *   - 退出应用（退出前写盘）
+ *   - 配置、角色包或档案列表变化后重建菜单，勾选状态与配置保持一致
+ * @structures
+ *   - TrayAction: 菜单项对应的操作
+ * @constants
+ *   - TRAY_ID: 托盘图标的 id
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use std::sync::Arc;
+use tauri::menu::{CheckMenuItem, Menu, MenuBuilder, MenuEvent, MenuItem, SubmenuBuilder};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::commands::activate_character;
+use crate::config::{ChangeSource, ConfigSection, ConfigStore};
+use crate::display;
+use crate::error::AppError;
+use crate::AppState;
+
+/// 托盘图标的 id
+pub const TRAY_ID: &str = "desktop-pet";
+
+const CHARACTER_PREFIX: &str = "character:";
+const PROFILE_PREFIX: &str = "profile:";
+
+/// 菜单项对应的操作，菜单项的 id 由操作生成
+#[derive(Debug, Clone, PartialEq)]
+enum TrayAction {
+    ToggleVisible,
+    OpenSettings,
+    ResetPosition,
+    SwitchCharacter(String),
+    SwitchProfile(String),
+    TogglePause,
+    Quit,
+}
+
+impl TrayAction {
+    fn id(&self) -> String {
+        match self {
+            TrayAction::ToggleVisible => "toggle-visible".to_string(),
+            TrayAction::OpenSettings => "open-settings".to_string(),
+            TrayAction::ResetPosition => "reset-position".to_string(),
+            TrayAction::SwitchCharacter(id) => format!("{}{}", CHARACTER_PREFIX, id),
+            TrayAction::SwitchProfile(name) => format!("{}{}", PROFILE_PREFIX, name),
+            TrayAction::TogglePause => "toggle-pause".to_string(),
+            TrayAction::Quit => "quit".to_string(),
+        }
+    }
+
+    fn parse(id: &str) -> Option<Self> {
+        if let Some(character) = id.strip_prefix(CHARACTER_PREFIX) {
+            return Some(TrayAction::SwitchCharacter(character.to_string()));
+        }
+        if let Some(profile) = id.strip_prefix(PROFILE_PREFIX) {
+            return Some(TrayAction::SwitchProfile(profile.to_string()));
+        }
+        match id {
+            "toggle-visible" => Some(TrayAction::ToggleVisible),
+            "open-settings" => Some(TrayAction::OpenSettings),
+            "reset-position" => Some(TrayAction::ResetPosition),
+            "toggle-pause" => Some(TrayAction::TogglePause),
+            "quit" => Some(TrayAction::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// 按当前配置、角色包与档案列表生成菜单
+async fn build_menu(app: &AppHandle) -> Result<Menu<Wry>, AppError> {
+    let state = app.state::<AppState>();
+    let config = state.config.get().await;
+    let profiles = state.profiles.list().await?;
+    let check = |action: TrayAction, text: &str, checked: bool| {
+        CheckMenuItem::with_id(app, action.id(), text, true, checked, None::<&str>)
+    };
+    let item = |action: TrayAction, text: &str| MenuItem::with_id(app, action.id(), text, true, None::<&str>);
+
+    let mut characters = SubmenuBuilder::new(app, "切换角色");
+    for pack in state.characters.list() {
+        let id = pack.manifest.id;
+        let active = id == config.character.active;
+        characters = characters.item(&check(TrayAction::SwitchCharacter(id), &pack.manifest.name, active)?);
+    }
+    let mut profile_menu = SubmenuBuilder::new(app, "配置档案");
+    for profile in profiles {
+        profile_menu = profile_menu.item(&check(TrayAction::SwitchProfile(profile.name.clone()), &profile.name, profile.active)?);
+    }
+
+    let menu = MenuBuilder::new(app)
+        .item(&check(TrayAction::ToggleVisible, "显示桌宠", config.behavior.pets_visible)?)
+        .item(&item(TrayAction::OpenSettings, "打开设置")?)
+        .item(&item(TrayAction::ResetPosition, "桌宠回到屏幕中央")?)
+        .separator()
+        .item(&characters.build()?)
+        .item(&profile_menu.build()?)
+        .separator()
+        .item(&check(TrayAction::TogglePause, "暂停 AI 对话", config.behavior.ai_paused)?)
+        .separator()
+        .item(&item(TrayAction::Quit, "退出")?)
+        .build()?;
+    Ok(menu)
+}
+
+/// 重建托盘菜单；角色包或档案列表变化后由对应命令调用
+pub async fn refresh_menu(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let result = match build_menu(app).await {
+        Ok(menu) => tray.set_menu(Some(menu)).map_err(AppError::from),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        eprintln!("更新托盘菜单失败: {}", e);
+    }
+}
+
+/// 创建托盘图标，菜单在后台生成
+pub fn create(app: &AppHandle) -> Result<(), AppError> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("桌面宠物")
+        .on_menu_event(|app, event: MenuEvent| {
+            let Some(action) = TrayAction::parse(event.id().as_ref()) else {
+                return;
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = perform(&app, action).await {
+                    eprintln!("执行托盘菜单操作失败: {}", e);
+                }
+                refresh_menu(&app).await;
+            });
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move { refresh_menu(&app).await });
+    Ok(())
+}
+
+async fn perform(app: &AppHandle, action: TrayAction) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let behavior = state.config.read().await.behavior.clone();
+    match action {
+        TrayAction::ToggleVisible => state.windows.set_pets_visible(!behavior.pets_visible).await?,
+        TrayAction::OpenSettings => state.windows.open_settings().await?,
+        TrayAction::ResetPosition => {
+            if !behavior.pets_visible {
+                state.windows.set_pets_visible(true).await?;
+            }
+            display::center_pets(app, &state.config).await?;
+        }
+        TrayAction::SwitchCharacter(id) => {
+            state.characters.get(&id)?;
+            activate_character(&state, id).await;
+        }
+        TrayAction::SwitchProfile(name) => state.profiles.activate(&name, &state.config).await?,
+        TrayAction::TogglePause => {
+            state
+                .config
+                .patch(ConfigSection::Behavior, serde_json::json!({ "ai_paused": !behavior.ai_paused }))
+                .await?;
+        }
+        // 退出时 RunEvent::Exit 会把尚未落盘的配置写入磁盘
+        TrayAction::Quit => app.exit(0),
+    }
+    Ok(())
+}
+
+/// 配置被外部修改或切换档案后重建菜单，一直运行到应用退出
+pub async fn run_menu_sync(app: AppHandle, config: Arc<ConfigStore>) {
+    let mut changes = config.subscribe();
+    loop {
+        match changes.recv().await {
+            // 桌宠位置的变化很频繁，只在菜单显示的状态变化时重建
+            Ok(change)
+                if change.source == ChangeSource::Profile
+                    || change.behavior.is_some()
+                    || change.character.is_some() =>
+            {
+                refresh_menu(&app).await
+            }
+            Ok(_) => continue,
+            Err(RecvError::Lagged(_)) => refresh_menu(&app).await,
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_ids_round_trip() {
+        let actions = [
+            TrayAction::ToggleVisible,
+            TrayAction::OpenSettings,
+            TrayAction::ResetPosition,
+            TrayAction::SwitchCharacter("hiyori".to_string()),
+            TrayAction::SwitchProfile("工作".to_string()),
+            TrayAction::TogglePause,
+            TrayAction::Quit,
+        ];
+        for action in actions {
+            assert_eq!(TrayAction::parse(&action.id()), Some(action));
+        }
+        assert_eq!(TrayAction::parse("unknown"), None);
+    }
+}
@@ -0,0 +1,657 @@
+/*!
+ * @fileoverview 窗口管理模块
+ * @description 由后端按标签创建并登记桌宠、聊天气泡与设置窗口，计算气泡相对桌宠的位置，并通过事件把气泡内容推送给气泡窗口
+ * @features
+ *   - 按需创建其他桌宠、气泡与设置窗口，已存在时复用
+ *   - 每只桌宠有自己的窗口与气泡：主桌宠是 main / chat-bubble，其他桌宠是 pet:<id> / chat-bubble:<id>
+ *   - 气泡显示在桌宠上方，靠近屏幕顶部时翻到下方，并限制在工作区内
+ *   - 配置中的桌宠列表变化时打开或关闭对应的桌宠窗口，按配置显示或隐藏所有桌宠
+ *   - 切换桌宠窗口的鼠标穿透（由快捷键触发，不写入配置）
+ *   - 桌宠移动或缩放时气泡跟随
+ *   - 每只桌宠的气泡内容（多句话、当前句、自动隐藏）保存在后端，变化时向该气泡窗口发出 bubble-state 事件
+ *   - 设置窗口关闭时隐藏，位置与大小写回配置
+ * @structures
+ *   - WindowKind: 窗口类型
+ *   - WindowManager: 窗口管理器
+ *   - BubbleState: 气泡内容
+ *   - BubbleSide: 气泡在桌宠的哪一侧
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+    WindowEvent,
+};
+
+use crate::ai::types::PetResponseItem;
+use crate::config::{ConfigSection, ConfigStore, MAIN_PET};
+use crate::display::{self, Display, Rect};
+use crate::error::AppError;
+
+/// 气泡内容变化时发给气泡窗口的事件名，携带 `BubbleState`
+pub const BUBBLE_STATE_EVENT: &str = "bubble-state";
+
+/// 气泡的宽度（逻辑像素）
+const BUBBLE_WIDTH: f64 = 320.0;
+/// 气泡与桌宠之间的间距（逻辑像素）
+const BUBBLE_GAP: f64 = 10.0;
+
+/// 设置窗口的标签
+const SETTINGS_LABEL: &str = "settings";
+/// 主桌宠气泡窗口的标签，其他桌宠的气泡在后面加上 `:<桌宠 id>`
+const BUBBLE_LABEL: &str = "chat-bubble";
+/// 其他桌宠窗口标签的前缀，主桌宠的窗口标签是 `main`
+const PET_LABEL_PREFIX: &str = "pet:";
+
+/// 窗口类型；设置窗口只有一个，桌宠与气泡每只桌宠各一个
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowKind {
+    Pet,
+    Bubble,
+    Settings,
+}
+
+/// 桌宠窗口的标签
+pub fn pet_label(pet: &str) -> String {
+    if pet == MAIN_PET {
+        MAIN_PET.to_string()
+    } else {
+        format!("{}{}", PET_LABEL_PREFIX, pet)
+    }
+}
+
+/// 桌宠的气泡窗口的标签
+pub fn bubble_label(pet: &str) -> String {
+    if pet == MAIN_PET {
+        BUBBLE_LABEL.to_string()
+    } else {
+        format!("{}:{}", BUBBLE_LABEL, pet)
+    }
+}
+
+/// 按标签判断窗口类型及所属的桌宠，不是由窗口管理器创建的窗口返回 `None`
+pub fn classify(label: &str) -> Option<(WindowKind, Option<String>)> {
+    if label == SETTINGS_LABEL {
+        return Some((WindowKind::Settings, None));
+    }
+    if label == MAIN_PET {
+        return Some((WindowKind::Pet, Some(MAIN_PET.to_string())));
+    }
+    if label == BUBBLE_LABEL {
+        return Some((WindowKind::Bubble, Some(MAIN_PET.to_string())));
+    }
+    if let Some(pet) = label.strip_prefix(PET_LABEL_PREFIX) {
+        return Some((WindowKind::Pet, Some(pet.to_string())));
+    }
+    let pet = label.strip_prefix(BUBBLE_LABEL)?.strip_prefix(':')?;
+    Some((WindowKind::Bubble, Some(pet.to_string())))
+}
+
+/// 已登记的窗口
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowInfo {
+    pub label: String,
+    pub kind: WindowKind,
+    /// 桌宠与气泡窗口所属的桌宠
+    pub pet: Option<String>,
+    pub visible: bool,
+}
+
+/// 气泡在桌宠的哪一侧
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BubbleSide {
+    Above,
+    Below,
+}
+
+/// 显示气泡的选项
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BubbleOptions {
+    /// 只有一句话时，打字完成后自动隐藏
+    pub auto_hide: bool,
+    /// 自动隐藏前停留的毫秒数
+    pub auto_hide_delay: u64,
+}
+
+impl Default for BubbleOptions {
+    fn default() -> Self {
+        Self { auto_hide: true, auto_hide_delay: 3000 }
+    }
+}
+
+/// 气泡内容，是气泡窗口显示的唯一来源
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BubbleState {
+    /// 每次显示新的一组句子时递增，气泡窗口据此重新开始打字
+    pub id: u64,
+    /// 气泡所属的桌宠
+    pub pet: String,
+    pub messages: Vec<PetResponseItem>,
+    /// 当前显示的句子
+    pub index: usize,
+    pub auto_hide: bool,
+    pub auto_hide_delay: u64,
+    pub side: BubbleSide,
+}
+
+impl BubbleState {
+    fn current_message(&self) -> &str {
+        self.messages.get(self.index).map_or("", |item| item.message.as_str())
+    }
+}
+
+/// 气泡的逻辑大小，高度按当前句子的长度估算
+pub fn bubble_size(message: &str) -> (f64, f64) {
+    let lines = message.chars().count().div_ceil(15) as f64;
+    (BUBBLE_WIDTH, (lines * 24.0 + 60.0).clamp(80.0, 250.0))
+}
+
+/// 计算气泡左上角的物理坐标
+///
+/// 气泡默认水平居中显示在桌宠上方；上方放不下时翻到下方，两边都放不下时贴着工作区顶部。
+/// 水平方向限制在工作区内。找不到桌宠所在的显示器时 `work_area` 为 `None`，不做限制。
+pub fn place_bubble(pet: Rect, size: (f64, f64), gap: f64, work_area: Option<Rect>) -> (f64, f64, BubbleSide) {
+    let (width, height) = size;
+    let x = pet.x + pet.width / 2.0 - width / 2.0;
+    let above = pet.y - height - gap;
+    let below = pet.y + pet.height + gap;
+    let Some(area) = work_area else {
+        return (x, above, BubbleSide::Above);
+    };
+
+    let x = x.min(area.x + area.width - width).max(area.x);
+    if above >= area.y {
+        (x, above, BubbleSide::Above)
+    } else if below + height <= area.y + area.height {
+        (x, below, BubbleSide::Below)
+    } else {
+        (x, area.y, BubbleSide::Above)
+    }
+}
+
+/// 窗口管理器
+pub struct WindowManager {
+    app: AppHandle,
+    config: Arc<ConfigStore>,
+    /// 各桌宠的气泡内容，键为桌宠 id
+    bubbles: Mutex<HashMap<String, BubbleState>>,
+    next_bubble_id: AtomicU64,
+    /// 桌宠窗口是否让鼠标穿透，只在本次运行中有效
+    click_through: AtomicBool,
+}
+
+impl WindowManager {
+    pub fn new(app: AppHandle, config: Arc<ConfigStore>) -> Arc<Self> {
+        Arc::new(Self {
+            app,
+            config,
+            bubbles: Mutex::new(HashMap::new()),
+            next_bubble_id: AtomicU64::new(1),
+            click_through: AtomicBool::new(false),
+        })
+    }
+
+    /// 登记桌宠窗口，桌宠移动或缩放时它的气泡跟随
+    pub fn attach_pet(self: &Arc<Self>, pet: &str, window: &WebviewWindow) {
+        let manager = Arc::clone(self);
+        let pet = pet.to_string();
+        window.on_window_event(move |event| {
+            if let WindowEvent::Moved(_) | WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } = event {
+                if let Err(e) = manager.follow_pet(&pet) {
+                    eprintln!("气泡跟随桌宠失败: {}", e);
+                }
+            }
+        });
+    }
+
+    /// 按标签排序的全部窗口
+    pub fn list(&self) -> Vec<WindowInfo> {
+        let mut windows: Vec<WindowInfo> = self
+            .app
+            .webview_windows()
+            .into_iter()
+            .filter_map(|(label, window)| {
+                let (kind, pet) = classify(&label)?;
+                Some(WindowInfo { label, kind, pet, visible: window.is_visible().unwrap_or(false) })
+            })
+            .collect();
+        windows.sort_by(|a, b| a.label.cmp(&b.label));
+        windows
+    }
+
+    /// 创建其他桌宠的窗口并恢复上次的位置，窗口已存在时直接返回
+    pub async fn spawn_pet(self: &Arc<Self>, pet: &str) -> Result<WebviewWindow, AppError> {
+        let label = pet_label(pet);
+        if let Some(window) = self.app.get_webview_window(&label) {
+            return Ok(window);
+        }
+        let size = match self.config.read().await.pets.get(pet) {
+            Some(config) => config.size as f64 + 80.0,
+            None => return Err(AppError::NotFound(format!("桌宠不存在: {}", pet))),
+        };
+        let window = WebviewWindowBuilder::new(&self.app, &label, WebviewUrl::App("/".into()))
+            .title("桌面宠物")
+            .inner_size(size, size)
+            .min_inner_size(50.0, 50.0)
+            .resizable(false)
+            .transparent(true)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .visible(false)
+            .build()?;
+        if let Err(e) = crate::macos::setup_window(&window) {
+            eprintln!("设置macOS窗口配置时出错: {}", e);
+        }
+        if let Err(e) = crate::windows::setup_window(&window) {
+            eprintln!("设置Windows窗口配置时出错: {}", e);
+        }
+        self.attach_pet(pet, &window);
+        window.set_ignore_cursor_events(self.click_through.load(Ordering::SeqCst))?;
+        display::restore_pet(&window, &self.config, pet).await?;
+        if self.config.read().await.behavior.pets_visible {
+            window.show()?;
+        }
+        Ok(window)
+    }
+
+    /// 打开配置中还没有窗口的桌宠，关闭已从配置中移除的桌宠
+    pub async fn sync_pets(self: &Arc<Self>) {
+        let configured: Vec<String> = self.config.read().await.pets.keys().cloned().collect();
+        for label in self.app.webview_windows().into_keys() {
+            let Some((WindowKind::Pet, Some(pet))) = classify(&label) else {
+                continue;
+            };
+            if pet != MAIN_PET && !configured.contains(&pet) {
+                if let Err(e) = self.close_pet(&pet) {
+                    eprintln!("关闭桌宠 {} 失败: {}", pet, e);
+                }
+            }
+        }
+        for pet in configured {
+            if let Err(e) = self.spawn_pet(&pet).await {
+                eprintln!("打开桌宠 {} 失败: {}", pet, e);
+            }
+        }
+    }
+
+    /// 按配置显示或隐藏所有桌宠，隐藏时气泡一并隐藏
+    pub async fn apply_visibility(&self) {
+        let visible = self.config.read().await.behavior.pets_visible;
+        for (label, window) in self.app.webview_windows() {
+            let result = match classify(&label) {
+                Some((WindowKind::Pet, _)) if visible => window.show(),
+                Some((WindowKind::Pet | WindowKind::Bubble, _)) if !visible => window.hide(),
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                eprintln!("切换窗口 {} 的显示状态失败: {}", label, e);
+            }
+        }
+    }
+
+    /// 显示或隐藏所有桌宠并写回配置
+    pub async fn set_pets_visible(&self, visible: bool) -> Result<(), AppError> {
+        self.config.patch(ConfigSection::Behavior, json!({ "pets_visible": visible })).await?;
+        self.apply_visibility().await;
+        Ok(())
+    }
+
+    /// 显示桌宠（所有桌宠隐藏时一并显示）并让它的窗口获得焦点
+    pub async fn focus_pet(&self, pet: &str) -> Result<(), AppError> {
+        if !self.config.read().await.behavior.pets_visible {
+            self.set_pets_visible(true).await?;
+        }
+        let window = self
+            .app
+            .get_webview_window(&pet_label(pet))
+            .ok_or_else(|| AppError::NotFound(format!("找不到桌宠窗口: {}", pet)))?;
+        window.show()?;
+        window.set_focus()?;
+        Ok(())
+    }
+
+    /// 切换所有桌宠窗口的鼠标穿透，返回切换后的状态
+    pub fn toggle_click_through(&self) -> Result<bool, AppError> {
+        let enabled = !self.click_through.fetch_xor(true, Ordering::SeqCst);
+        for (label, window) in self.app.webview_windows() {
+            if let Some((WindowKind::Pet, _)) = classify(&label) {
+                window.set_ignore_cursor_events(enabled)?;
+            }
+        }
+        Ok(enabled)
+    }
+
+    /// 导入配置、切换档案或外部编辑改变了桌宠列表或显示状态时同步窗口，一直运行到应用退出
+    pub async fn run_pet_sync(self: Arc<Self>) {
+        let mut changes = self.config.subscribe();
+        loop {
+            match changes.recv().await {
+                Ok(change) => {
+                    if change.pets.is_some() {
+                        self.sync_pets().await;
+                    }
+                    if change.behavior.is_some() {
+                        self.apply_visibility().await;
+                    }
+                }
+                // 积压时不知道错过了哪些变更，直接按当前配置同步
+                Err(RecvError::Lagged(_)) => {
+                    self.sync_pets().await;
+                    self.apply_visibility().await;
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+
+    /// 关闭其他桌宠的窗口及其气泡
+    pub fn close_pet(&self, pet: &str) -> Result<(), AppError> {
+        self.bubbles.lock().unwrap().remove(pet);
+        for label in [pet_label(pet), bubble_label(pet)] {
+            if let Some(window) = self.app.get_webview_window(&label) {
+                window.destroy()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 取得桌宠的气泡窗口，不存在时创建（创建后处于隐藏状态）
+    fn ensure_bubble(self: &Arc<Self>, pet: &str) -> Result<WebviewWindow, AppError> {
+        let label = bubble_label(pet);
+        if let Some(window) = self.app.get_webview_window(&label) {
+            return Ok(window);
+        }
+        let (width, height) = bubble_size("");
+        let window = WebviewWindowBuilder::new(&self.app, &label, WebviewUrl::App("/#/chat-bubble".into()))
+            .title("")
+            .inner_size(width, height)
+            .resizable(false)
+            .transparent(true)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .shadow(false)
+            .focused(false)
+            .visible(false)
+            .build()?;
+        let manager = Arc::clone(self);
+        let pet = pet.to_string();
+        window.on_window_event(move |event| {
+            if let WindowEvent::Destroyed = event {
+                manager.bubbles.lock().unwrap().remove(&pet);
+            }
+        });
+        Ok(window)
+    }
+
+    /// 取得设置窗口，不存在时创建（创建后处于隐藏状态）
+    fn ensure_settings(self: &Arc<Self>) -> Result<WebviewWindow, AppError> {
+        if let Some(window) = self.app.get_webview_window(SETTINGS_LABEL) {
+            return Ok(window);
+        }
+        let window = WebviewWindowBuilder::new(&self.app, SETTINGS_LABEL, WebviewUrl::App("/#/settings".into()))
+            .title("宠物设置")
+            .inner_size(800.0, 600.0)
+            .min_inner_size(700.0, 500.0)
+            .center()
+            .visible(false)
+            .build()?;
+        let manager = Arc::clone(self);
+        let handle = window.clone();
+        window.on_window_event(move |event| match event {
+            // 设置窗口关闭时只隐藏，保留正在编辑的内容
+            WindowEvent::CloseRequested { api, .. } => {
+                api.prevent_close();
+                let _ = handle.hide();
+            }
+            WindowEvent::Moved(_) | WindowEvent::Resized(_) => manager.save_settings_geometry(&handle),
+            _ => {}
+        });
+        Ok(window)
+    }
+
+    /// 打开设置窗口，恢复上次的位置与大小
+    pub async fn open_settings(self: &Arc<Self>) -> Result<(), AppError> {
+        let created = self.app.get_webview_window(SETTINGS_LABEL).is_none();
+        let window = self.ensure_settings()?;
+        if created {
+            let saved = self.config.read().await.window.clone();
+            if let (Some(width), Some(height)) = (saved.settings_window_width, saved.settings_window_height) {
+                window.set_size(tauri::LogicalSize::new(width, height))?;
+            }
+            if let (Some(x), Some(y)) = (saved.settings_window_x, saved.settings_window_y) {
+                window.set_position(tauri::LogicalPosition::new(x, y))?;
+            }
+        }
+        window.show()?;
+        window.unminimize()?;
+        window.set_focus()?;
+        Ok(())
+    }
+
+    pub fn hide_settings(&self) -> Result<(), AppError> {
+        self.hide(SETTINGS_LABEL)
+    }
+
+    fn hide(&self, label: &str) -> Result<(), AppError> {
+        if let Some(window) = self.app.get_webview_window(label) {
+            window.hide()?;
+        }
+        Ok(())
+    }
+
+    /// 把设置窗口的逻辑位置与大小写回配置
+    fn save_settings_geometry(&self, window: &WebviewWindow) {
+        let geometry = (|| -> Result<_, tauri::Error> {
+            let scale = window.scale_factor()?;
+            Ok((window.inner_position()?, window.inner_size()?, scale))
+        })();
+        let Ok((position, size, scale)) = geometry else {
+            return;
+        };
+        let patch = json!({
+            "settings_window_x": position.x as f64 / scale,
+            "settings_window_y": position.y as f64 / scale,
+            "settings_window_width": size.width as f64 / scale,
+            "settings_window_height": size.height as f64 / scale,
+        });
+        let config = Arc::clone(&self.config);
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = config.patch(ConfigSection::Window, patch).await {
+                eprintln!("保存设置窗口位置失败: {}", e);
+            }
+        });
+    }
+
+    pub fn bubble_state(&self, pet: &str) -> Option<BubbleState> {
+        self.bubbles.lock().unwrap().get(pet).cloned()
+    }
+
+    /// 最近显示气泡且还未关闭的桌宠
+    pub fn latest_bubble_pet(&self) -> Option<String> {
+        let bubbles = self.bubbles.lock().unwrap();
+        bubbles.values().max_by_key(|state| state.id).map(|state| state.pet.clone())
+    }
+
+    /// 在桌宠旁显示新的一组句子，从第一句开始
+    pub fn show_bubble(
+        self: &Arc<Self>,
+        pet: &str,
+        messages: Vec<PetResponseItem>,
+        options: BubbleOptions,
+    ) -> Result<BubbleState, AppError> {
+        if self.app.get_webview_window(&pet_label(pet)).is_none() {
+            return Err(AppError::NotFound(format!("找不到桌宠窗口: {}", pet)));
+        }
+        let state = BubbleState {
+            id: self.next_bubble_id.fetch_add(1, Ordering::Relaxed),
+            pet: pet.to_string(),
+            messages,
+            index: 0,
+            auto_hide: options.auto_hide,
+            auto_hide_delay: options.auto_hide_delay,
+            side: BubbleSide::Above,
+        };
+        self.bubbles.lock().unwrap().insert(pet.to_string(), state);
+        self.ensure_bubble(pet)?;
+        self.refresh_bubble(pet, true)
+    }
+
+    /// 追加流式生成的后续句子，气泡已关闭时忽略
+    pub fn append_bubble(&self, pet: &str, messages: Vec<PetResponseItem>) -> Result<Option<BubbleState>, AppError> {
+        let Some(state) = self.update_state(pet, |state| state.messages.extend(messages)) else {
+            return Ok(None);
+        };
+        self.emit_state(&state)?;
+        Ok(Some(state))
+    }
+
+    /// 切换到第 `index` 句
+    pub fn set_bubble_index(&self, pet: &str, index: usize) -> Result<Option<BubbleState>, AppError> {
+        let mut out_of_range = false;
+        let updated = self.update_state(pet, |state| {
+            out_of_range = index >= state.messages.len();
+            if !out_of_range {
+                state.index = index;
+            }
+        });
+        if out_of_range {
+            return Err(AppError::InvalidInput(format!("没有第 {} 句", index + 1)));
+        }
+        match updated {
+            Some(_) => self.refresh_bubble(pet, false).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 隐藏桌宠的气泡并清空内容
+    pub fn close_bubble(&self, pet: &str) -> Result<(), AppError> {
+        self.bubbles.lock().unwrap().remove(pet);
+        self.hide(&bubble_label(pet))
+    }
+
+    /// 桌宠移动后把它可见的气泡移到新位置
+    fn follow_pet(&self, pet: &str) -> Result<(), AppError> {
+        let visible = match self.app.get_webview_window(&bubble_label(pet)) {
+            Some(bubble) => bubble.is_visible()?,
+            None => false,
+        };
+        if visible && self.bubble_state(pet).is_some() {
+            self.refresh_bubble(pet, false)?;
+        }
+        Ok(())
+    }
+
+    fn update_state(&self, pet: &str, f: impl FnOnce(&mut BubbleState)) -> Option<BubbleState> {
+        let mut bubbles = self.bubbles.lock().unwrap();
+        let state = bubbles.get_mut(pet)?;
+        f(state);
+        Some(state.clone())
+    }
+
+    /// 按当前句子调整气泡大小与位置，并把内容推送给气泡窗口
+    fn refresh_bubble(&self, pet: &str, show: bool) -> Result<BubbleState, AppError> {
+        let (Some(pet_window), Some(bubble)) = (
+            self.app.get_webview_window(&pet_label(pet)),
+            self.app.get_webview_window(&bubble_label(pet)),
+        ) else {
+            return Err(AppError::NotFound("找不到桌宠或气泡窗口".to_string()));
+        };
+        let Some(message) = self.bubble_state(pet).map(|state| state.current_message().to_string()) else {
+            return Err(AppError::NotFound("气泡已关闭".to_string()));
+        };
+
+        let scale = pet_window.scale_factor()?;
+        let position = pet_window.inner_position()?;
+        let size = pet_window.inner_size()?;
+        let pet_rect = Rect {
+            x: position.x as f64,
+            y: position.y as f64,
+            width: size.width as f64,
+            height: size.height as f64,
+        };
+        let monitor = match pet_window.current_monitor()? {
+            Some(monitor) => Some(monitor),
+            None => pet_window.primary_monitor()?,
+        };
+        let work_area = monitor.as_ref().map(|monitor| Display::from(monitor).work_area);
+        let (width, height) = bubble_size(&message);
+        let (width, height) = (width * scale, height * scale);
+        let (x, y, side) = place_bubble(pet_rect, (width, height), BUBBLE_GAP * scale, work_area);
+
+        bubble.set_size(PhysicalSize::new(width.round() as u32, height.round() as u32))?;
+        bubble.set_position(PhysicalPosition::new(x.round() as i32, y.round() as i32))?;
+        let state = self
+            .update_state(pet, |state| state.side = side)
+            .ok_or_else(|| AppError::NotFound("气泡已关闭".to_string()))?;
+        self.emit_state(&state)?;
+        if show {
+            bubble.show()?;
+            bubble.set_always_on_top(true)?;
+        }
+        Ok(state)
+    }
+
+    fn emit_state(&self, state: &BubbleState) -> Result<(), AppError> {
+        self.app.emit_to(bubble_label(&state.pet), BUBBLE_STATE_EVENT, state)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PET: Rect = Rect { x: 800.0, y: 500.0, width: 200.0, height: 200.0 };
+    const SCREEN: Rect = Rect { x: 0.0, y: 0.0, width: 1920.0, height: 1040.0 };
+
+    #[test]
+    fn test_labels_identify_pet_windows() {
+        assert_eq!(pet_label(MAIN_PET), "main");
+        assert_eq!(bubble_label("pet-2"), "chat-bubble:pet-2");
+        for pet in [MAIN_PET, "pet-2"] {
+            assert_eq!(classify(&pet_label(pet)), Some((WindowKind::Pet, Some(pet.to_string()))));
+            assert_eq!(classify(&bubble_label(pet)), Some((WindowKind::Bubble, Some(pet.to_string()))));
+        }
+        assert_eq!(classify("settings"), Some((WindowKind::Settings, None)));
+        assert_eq!(classify("chat-bubble-old"), None);
+    }
+
+    #[test]
+    fn test_bubble_size_grows_with_message() {
+        assert_eq!(bubble_size(""), (320.0, 80.0));
+        assert_eq!(bubble_size(&"好".repeat(45)), (320.0, 132.0));
+        assert_eq!(bubble_size(&"好".repeat(1000)), (320.0, 250.0));
+    }
+
+    #[test]
+    fn test_bubble_flips_near_screen_edges() {
+        assert_eq!(place_bubble(PET, (320.0, 100.0), 10.0, Some(SCREEN)), (740.0, 390.0, BubbleSide::Above));
+
+        // 桌宠贴近屏幕顶部，气泡翻到下方
+        let pet = Rect { y: 50.0, ..PET };
+        assert_eq!(place_bubble(pet, (320.0, 100.0), 10.0, Some(SCREEN)), (740.0, 260.0, BubbleSide::Below));
+
+        // 桌宠贴近屏幕左右边缘，气泡被推回工作区
+        let pet = Rect { x: -50.0, ..PET };
+        assert_eq!(place_bubble(pet, (320.0, 100.0), 10.0, Some(SCREEN)).0, 0.0);
+        let pet = Rect { x: 1800.0, ..PET };
+        assert_eq!(place_bubble(pet, (320.0, 100.0), 10.0, Some(SCREEN)).0, 1600.0);
+
+        // 找不到显示器时不限制
+        let pet = Rect { y: 0.0, ..PET };
+        assert_eq!(place_bubble(pet, (320.0, 100.0), 10.0, None), (740.0, -110.0, BubbleSide::Above));
+    }
+}
@@ -5,7 +5,8 @@
  *   - 对话状态管理
  *   - 对话进度控制
  *   - 表情同步切换
- *   - 跨窗口通信
+ *   - 气泡内容由后端维护并推送给气泡窗口
+ *   - 流式追加后续句子
  * @author dada
  * @version 1.0.0
  * @since 2025-07-13
@@ -13,7 +14,8 @@
 
 import { ref } from 'vue';
 import type { EmotionName } from '../../types/emotion';
-import { createNotificationWindow } from '../../services/windowFactory';
+import type { BubbleOptions } from '../../types/window';
+import { appendBubbleMessages, closeBubble, setBubbleIndex, showBubble } from '../../services/windowService';
 import { DEFAULT_EMOTION } from '../../constants/emotions';
 
 export interface ConversationMessage {
@@ -27,12 +29,15 @@ export function useConversation() {
     const isInConversation = ref(false);
     const conversationMessages = ref<ConversationMessage[]>([]);
     const conversationIndex = ref(0);
+    // 后续句子是否仍在流式生成中
+    const isStreaming = ref(false);
 
-    // 开始对话
+    // 开始对话；流式对话传入 { auto_hide: false }，否则后续句子到达前气泡可能已自动关闭
     async function startConversation(
         messages: ConversationMessage[],
         onEmotionChange: (emotion: EmotionName) => void,
-        onShakeEffect: () => void
+        onShakeEffect: () => void,
+        options?: BubbleOptions
     ) {
         isInConversation.value = true;
         conversationMessages.value = messages;
@@ -44,14 +49,20 @@ export function useConversation() {
             onEmotionChange(messages[0].emotion as EmotionName);
         }
 
-        // 存储数据供气泡窗口使用
-        localStorage.setItem('bubbleMessages', JSON.stringify(messages));
-        localStorage.setItem('currentMessageIndex', '0');
-        localStorage.setItem('bubbleMessage', messages[0]?.message || '');
-        localStorage.setItem('currentEmotion', messages[0]?.emotion || DEFAULT_EMOTION);
+        // 由后端显示气泡并推送内容
+        await showBubble(messages, options);
+    }
+
+    // 流式追加一句话
+    function appendMessage(message: ConversationMessage) {
+        if (!isInConversation.value) return;
+        conversationMessages.value.push(message);
+        appendBubbleMessages([message]).catch((error) => console.error('追加气泡句子失败:', error));
+    }
 
-        // 创建气泡窗口
-        await createNotificationWindow(messages[0]?.message || '');
+    // 标记流式生成状态
+    function setStreaming(streaming: boolean) {
+        isStreaming.value = streaming;
     }
 
     // 播放下一句话
@@ -71,11 +82,12 @@ export function useConversation() {
                 onEmotionChange(nextMessage.emotion as EmotionName);
 
                 // 更新气泡内容
-                localStorage.setItem('currentMessageIndex', conversationIndex.value.toString());
-                localStorage.setItem('bubbleMessage', nextMessage.message);
-                localStorage.setItem('currentEmotion', nextMessage.emotion);
+                setBubbleIndex(conversationIndex.value).catch((error) => console.error('切换气泡句子失败:', error));
             }
             return true;
+        } else if (isStreaming.value) {
+            // 后续句子还在生成，停留在当前句
+            return true;
         } else {
             // 对话结束
             endConversation();
@@ -89,12 +101,10 @@ export function useConversation() {
         isInConversation.value = false;
         conversationMessages.value = [];
         conversationIndex.value = 0;
+        isStreaming.value = false;
 
-        // 清理数据并触发关闭事件
-        localStorage.removeItem('bubbleMessages');
-        localStorage.removeItem('currentMessageIndex');
-        localStorage.removeItem('bubbleMessage');
-        localStorage.setItem('closeBubble', Date.now().toString());
+        // 关闭气泡
+        closeBubble().catch((error) => console.error('关闭气泡失败:', error));
     }
 
     return {
@@ -102,9 +112,12 @@ export function useConversation() {
         isInConversation,
         conversationMessages,
         conversationIndex,
+        isStreaming,
 
         // 方法
         startConversation,
+        appendMessage,
+        setStreaming,
         playNext,
         endConversation,
     };
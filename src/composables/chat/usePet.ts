@@ -3,13 +3,15 @@
  * @description 管理桌面宠物的表情、状态和行为，提供表情切换、互动响应等核心功能
  * @features
  *   - 表情状态管理和随机切换
+ *   - 从后端加载当前窗口所属桌宠的角色及其表情注册表
  *   - 宠物行为状态跟踪
  *   - 用户交互响应处理
  *   - 表情图片预加载优化
  *   - 心情和活跃度管理
  * @exports
  *   - currentEmotion: 当前表情状态
- *   - emotions: 所有可用表情列表
+ *   - currentSprite: 当前角色包中当前表情的立绘地址
+ *   - emotions: 当前角色的可用表情列表
  *   - switchEmotion: 切换表情函数
  *   - setEmotion: 设置指定表情
  *   - getRandomEmotion: 获取随机表情
@@ -20,11 +22,20 @@
  * @since 2025-07-13
  */
 
-import { ref } from "vue";
+import { ref, computed, watch } from "vue";
 import type { EmotionName } from "../../types/emotion";
-import { EMOTIONS, DEFAULT_EMOTION } from "../../constants/emotions";
+import { DEFAULT_EMOTION } from "../../constants/emotions";
+import { useConfigStore } from "../../stores/config";
+import { listEmotions, spriteUrl } from "../../services/characterService";
+import { currentPetId } from "../../services/windowService";
 
 export function usePet() {
+  const configStore = useConfigStore();
+  const pet = currentPetId();
+
+  // 当前窗口所属桌宠使用的角色包
+  const character = computed(() => configStore.petCharacter(pet));
+
   // ===================
   // 表情管理
   // ===================
@@ -32,10 +43,16 @@ export function usePet() {
   // 当前表情
   const currentEmotion = ref<EmotionName>(DEFAULT_EMOTION);
 
+  // 当前角色的可用表情，加载完成前只有默认表情
+  const emotions = ref<EmotionName[]>([DEFAULT_EMOTION]);
+
+  // 当前立绘，由后端从当前角色包中提供
+  const currentSprite = computed(() => spriteUrl(character.value, currentEmotion.value));
+
   // 获取随机表情
   function getRandomEmotion(): EmotionName {
-    const randomIndex = Math.floor(Math.random() * EMOTIONS.length);
-    return EMOTIONS[randomIndex];
+    const randomIndex = Math.floor(Math.random() * emotions.value.length);
+    return emotions.value[randomIndex];
   }
 
   // 点击切换表情
@@ -45,23 +62,36 @@ export function usePet() {
     let newEmotion: EmotionName;
     do {
       newEmotion = getRandomEmotion();
-    } while (newEmotion === currentEmotion.value && EMOTIONS.length > 1);
+    } while (newEmotion === currentEmotion.value && emotions.value.length > 1);
     
     currentEmotion.value = newEmotion;
   }
 
   // 设置指定表情
   function setEmotion(emotion: EmotionName) {
-    if (EMOTIONS.includes(emotion)) {
+    if (emotions.value.includes(emotion)) {
       currentEmotion.value = emotion;
     }
   }
 
+  // 加载当前角色的表情注册表
+  async function loadEmotions() {
+    try {
+      const registry = await listEmotions(pet);
+      emotions.value = registry.emotions.map(emotion => emotion.name);
+      if (!emotions.value.includes(currentEmotion.value)) {
+        currentEmotion.value = registry.default_emotion;
+      }
+    } catch (error) {
+      console.error('读取表情列表失败:', error);
+    }
+  }
+
   // 防止表情图片加载期间的闪烁
   function preloadImages() {
-    EMOTIONS.forEach((emotion: EmotionName) => {
+    emotions.value.forEach((emotion: EmotionName) => {
       const img = new Image();
-      img.src = `/avatar/${emotion}.png`;
+      img.src = spriteUrl(character.value, emotion);
     });
   }
 
@@ -109,13 +139,19 @@ export function usePet() {
   // 初始化
   // ===================
   
-  // 预加载所有表情图片
-  preloadImages();
+  // 加载表情并预加载所有表情图片，切换角色后重新加载
+  async function loadCharacter() {
+    await loadEmotions();
+    preloadImages();
+  }
+  loadCharacter();
+  watch(character, loadCharacter);
 
   return {
     // 表情相关
     currentEmotion,
-    emotions: EMOTIONS,
+    currentSprite,
+    emotions,
     switchEmotion,
     setEmotion,
     getRandomEmotion,
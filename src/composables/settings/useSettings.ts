@@ -2,7 +2,7 @@
  * @fileoverview 设置管理组合式函数
  * @description 统一管理各种设置状态，提供设置窗口的打开/关闭功能，处理设置标签页的切换
  * @features
- *   - 设置窗口生命周期管理 (使用窗口工厂)
+ *   - 设置窗口生命周期管理 (由后端窗口管理器创建与隐藏)
  *   - 多标签页状态管理
  *   - 各设置模块的集成管理
  *   - 响应式状态更新
@@ -18,7 +18,7 @@
  *   - closeSettings: 关闭设置窗口
  *   - appearanceSettings: 外观设置功能
  * @dependencies
- *   - windowFactory: 统一窗口管理
+ *   - windowService: 后端窗口管理命令
  * @author dada
  * @version 2.0.0
  * @since 2025-07-13
@@ -27,7 +27,7 @@
 import { ref, computed, onMounted, onUnmounted } from "vue";
 import { SETTINGS_TABS, DEFAULT_ACTIVE_TAB } from "../../constants/settings-ui";
 import type { SettingsState } from '../../types/settings-ui';
-import { WindowFactory } from '../../services/windowFactory';
+import { closeSettings as hideSettingsWindow } from '../../services/windowService';
 
 export function useSettings() {
   // ===================
@@ -66,8 +66,8 @@ export function useSettings() {
   // 关闭设置窗口
   async function closeSettings() {
     try {
-      await WindowFactory.closeWindow('settings');
-      console.log('设置窗口已关闭');
+      await hideSettingsWindow();
+      console.log('设置窗口已隐藏');
     } catch (error) {
       console.error('关闭设置窗口失败:', error);
     }
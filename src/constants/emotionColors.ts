@@ -1,6 +1,7 @@
 /**
  * @fileoverview 表情颜色主题常量
- * @description 定义每种表情对应的气泡颜色主题，用于营造不同的情感氛围
+ * @description 定义内置表情对应的气泡颜色主题，用于营造不同的情感氛围；
+ *   角色包中的其他表情按清单里的 color 生成主题
  * @author dada
  * @version 1.0.0
  * @since 2025-07-13
@@ -147,7 +148,23 @@ export const EMOTION_COLOR_THEMES: Record<EmotionName, ColorTheme> = {
   },
 };
 
-// 获取表情对应的颜色主题
-export function getEmotionColorTheme(emotion: EmotionName): ColorTheme {
-  return EMOTION_COLOR_THEMES[emotion] || EMOTION_COLOR_THEMES["正常"];
+// 由 #rrggbb 颜色生成主题：文字使用该颜色，背景取与白色混合后的浅色
+export function colorThemeFromHex(color: string): ColorTheme | null {
+  const match = /^#([0-9a-f]{2})([0-9a-f]{2})([0-9a-f]{2})$/i.exec(color.trim());
+  if (!match) return null;
+  const [r, g, b] = match.slice(1).map(hex => parseInt(hex, 16));
+  const tint = (channel: number) => Math.round(255 - (255 - channel) * 0.12);
+  return {
+    background: `rgba(${tint(r)}, ${tint(g)}, ${tint(b)}, 0.98)`,
+    border: `rgba(${r}, ${g}, ${b}, 0.4)`,
+    text: color,
+    shadow: `rgba(${r}, ${g}, ${b}, 0.2)`
+  };
+}
+
+// 获取表情对应的颜色主题；没有内置主题时使用角色包清单中的颜色
+export function getEmotionColorTheme(emotion: EmotionName, color?: string | null): ColorTheme {
+  return EMOTION_COLOR_THEMES[emotion]
+    || (color ? colorThemeFromHex(color) : null)
+    || EMOTION_COLOR_THEMES["正常"];
 }
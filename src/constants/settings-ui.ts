@@ -7,6 +7,7 @@
  * @tabs
  *   - appearance: 外观设置 (宠物大小、透明度、边框)
  *   - ai: AI设置 (API配置、模型选择、对话参数)
+ *   - hotkeys: 快捷键设置 (全局快捷键)
  *   - about: 关于页面 (应用信息、版本、帮助)
  * @usage
  *   import { SETTINGS_TABS, DEFAULT_ACTIVE_TAB } from '@/constants/settings-ui'
@@ -29,6 +30,11 @@ export const SETTINGS_TABS: SettingsTab[] = [
     name: 'AI设置',
     icon: 'ai',
   },
+  {
+    id: 'hotkeys',
+    name: '快捷键',
+    icon: 'hotkeys',
+  },
   {
     id: 'about',
     name: '关于',
@@ -17,8 +17,8 @@
 
 // 设置默认值和限制常量
 import type { AppSetting, SettingsConstraints } from '../types/settings';
-import { DEFAULT_CHARACTER_PROMPT } from './ai';
 
+// 与后端 config/validation.rs 中的取值范围保持一致
 export const SETTINGS_CONSTRAINTS: SettingsConstraints = {
   minSize: 100,
   maxSize: 300,
@@ -27,25 +27,48 @@ export const SETTINGS_CONSTRAINTS: SettingsConstraints = {
 };
 
 export const DEFAULT_SETTINGS: AppSetting = {
+  schema_version: 2,
   appearance: {
     pet_size: 150,
     pet_opacity: 1.0,
     pet_show_border: true,
   },
   ai: {
+    provider: 'openai',
     api_key: '',
     base_url: 'https://api.deepseek.com/v1',
     model: 'deepseek-chat',
     temperature: 0.7,
     max_tokens: 1024,
-    system_prompt: DEFAULT_CHARACTER_PROMPT,
+    system_prompt: null,
+    repair_invalid_reply: true,
+    history_turns: 10,
+    context_budget: 6000,
+    auto_memory: true,
+    memory_recall_limit: 5,
   },
   window: {
     main_window_x: 100,
     main_window_y: 100,
+    main_window_monitor: null,
+    main_window_scale: null,
     settings_window_x: 200,
     settings_window_y: 200,
     settings_window_width: 800,
     settings_window_height: 600,
   },
+  character: {
+    active: 'qinling',
+  },
+  pets: {},
+  behavior: {
+    pets_visible: true,
+    ai_paused: false,
+  },
+  hotkeys: {
+    focus_chat: 'CommandOrControl+Shift+Space',
+    play_next: 'CommandOrControl+Shift+N',
+    toggle_visible: 'CommandOrControl+Shift+H',
+    toggle_click_through: 'CommandOrControl+Shift+T',
+  },
 };
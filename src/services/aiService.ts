@@ -1,9 +1,12 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useConfigStore } from "../stores/config";
-import type { AIResponse, ChatRequest, AIMessage, PetResponse, PetResponseItem } from '../types/ai';
-import { DEFAULT_CHARACTER_PROMPT, RESPONSE_FORMAT_PROMPT, USER_PROMPT_WRAPPER } from '../constants/ai';
-import { EMOTIONS } from '../constants/emotions';
-import { EmotionName } from '../types/emotion';
+import { toAppError } from './errors';
+import { currentPetId } from './windowService';
+import type { PetResponse, PetResponseItem, PetStreamItem, PetStreamResult } from '../types/ai';
+import type { AppError } from '../types/error';
 
+// AI 请求由 Rust 后端完成，API Key 不会在 webview 中发出
 export function useAIService() {
     const configStore = useConfigStore();
 
@@ -16,132 +19,59 @@ export function useAIService() {
         );
     }
 
-    async function callAI(messages: AIMessage[]): Promise<AIResponse> {
-        const aiConfig = configStore.ai;
-
-        const requestBody: ChatRequest = {
-            messages,
-            temperature: aiConfig.temperature,
-            max_tokens: aiConfig.max_tokens,
-            stream: false,
-        };
-
-        const response = await fetch(`${aiConfig.base_url}/chat/completions`, {
-            method: 'POST',
-            headers: {
-                'Content-Type': 'application/json',
-                'Authorization': `Bearer ${aiConfig.api_key}`,
-            },
-            body: JSON.stringify({
-                model: aiConfig.model,
-                ...requestBody,
-            }),
-        });
-
-        if (!response.ok) {
-            const errorText = await response.text();
-            throw new Error(`AI API 调用失败: ${response.status} ${response.statusText}\n${errorText}`);
-        }
-
-        return await response.json() as AIResponse;
-    }
-
     async function chatWithPet(userMessage: string): Promise<PetResponse> {
-        const aiConfig = configStore.ai;
-        // 检查配置是否完整
-        if (!validateAIConfig()) {
-            return {
-                success: false,
-                error: '请正确配置AI服务'
-            };
-        }
-
         try {
-            const messages: AIMessage[] = [];
-            // 添加系统提示词和响应格式要求
-            if (aiConfig.system_prompt) {
-                messages.push({
-                    role: 'system',
-                    content: aiConfig.system_prompt + '\n\n' + RESPONSE_FORMAT_PROMPT
-                });
-            }
-            messages.push({
-                role: 'user',
-                content: USER_PROMPT_WRAPPER.replace('{}', userMessage)
-            });
-            console.log('发送的消息:', messages);
-            const response = await callAI(messages);
+            const response = await invoke<PetResponse>('chat_with_pet', { message: userMessage, pet: currentPetId() });
             console.log('AI响应:', response);
-            const aiResponseContent = response.choices[0]?.message?.content;
-
-            if (!aiResponseContent) {
-                console.error('AI服务返回空响应');
-                return { success: false, error: 'AI服务返回空响应' };
-            }
-
-            // 解析JSON并转换为PetResponseItem[]
-            try {
-                const parsedResponse = JSON.parse(aiResponseContent);
-                console.log('parsed AI响应:', parsedResponse);
+            return response;
+        } catch (error) {
+            return { success: false, error: toAppError(error) };
+        }
+    }
 
-                // 验证并转换为PetResponseItem
-                if (Array.isArray(parsedResponse)) {
-                    const validItems: PetResponseItem[] = parsedResponse.filter(item =>
-                        typeof item === 'object' &&
-                        typeof item.message === 'string' &&
-                        typeof item.emotion === 'string' &&
-                        typeof item.japanese === 'string' &&
-                        EMOTIONS.includes(item.emotion as EmotionName)
-                    ).map(item => ({
-                        message: item.message,
-                        emotion: item.emotion as EmotionName,
-                        japanese: item.japanese
-                    }));
+    // 流式对话：每收到一句完整回复就回调 onItem，返回值在整个回复结束后 resolve
+    function chatWithPetStream(userMessage: string, onItem: (item: PetResponseItem, index: number) => void) {
+        const streamId = crypto.randomUUID();
 
-                    return validItems.length > 0
-                        ? { success: true, data: validItems }
-                        : { success: false };
+        const result = (async (): Promise<PetStreamResult> => {
+            // 先注册监听，再发起请求，避免漏掉第一句
+            const unlisten = await listen<PetStreamItem>('pet-stream-item', (event) => {
+                if (event.payload.stream_id === streamId) {
+                    onItem(event.payload.item, event.payload.index);
                 }
-
-                return { success: false };
+            });
+            try {
+                return await invoke<PetStreamResult>('chat_with_pet_stream', {
+                    streamId, message: userMessage, pet: currentPetId(),
+                });
             } catch (error) {
-                console.error('AI响应解析失败:', error);
-                return { success: false };
+                return { success: false, count: 0, cancelled: false, error: toAppError(error) };
+            } finally {
+                unlisten();
             }
+        })();
 
-        } catch (error) {
-            const errorMessage = error instanceof Error ? error.message : '未知错误';
-            return {
-                success: false,
-                error: `对话失败: ${errorMessage}`
-            };
-        }
-    }
+        const cancel = () => invoke<boolean>('cancel_chat_stream', { streamId });
 
-    async function testAIConnection(): Promise<{ success: boolean; message: string }> {
-        if (!validateAIConfig()) {
-            return { success: false, message: '请正确配置AI服务' };
-        }
+        return { streamId, result, cancel };
+    }
 
+    async function testAIConnection(): Promise<{ success: boolean; message: string; error?: AppError }> {
         try {
-            const response = await callAI([
-                { role: 'system', content: DEFAULT_CHARACTER_PROMPT },
-                { role: 'user', content: '你好' }
-            ]);
-            if (!response || !response.choices || response.choices.length === 0) {
-                return { success: false, message: 'AI服务未返回有效响应' };
-            }
-            return { success: true, message: '连接成功，AI响应正常' };
+            // 传入当前表单中的配置，便于在保存前测试
+            return await invoke<{ success: boolean; message: string; error?: AppError }>('test_ai_connection', {
+                config: configStore.ai
+            });
         } catch (error) {
-            const errorMessage = error instanceof Error ? error.message : '未知错误';
-            return { success: false, message: `连接失败: ${errorMessage}` };
+            const appError = toAppError(error);
+            return { success: false, message: `连接失败: ${appError.message}`, error: appError };
         }
     }
 
     return {
         validateAIConfig,
-        callAI,
         chatWithPet,
+        chatWithPetStream,
         testAIConnection
     };
 }
@@ -0,0 +1,22 @@
+/**
+ * @fileoverview 配置包服务
+ * @description 调用后端配置导出与导入命令；导入后变化的配置由后端 config-changed 事件推送
+ * @features
+ *   - 导出当前档案的配置、记忆与角色包引用
+ *   - 以合并或替换的方式导入配置包
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { ExportOptions, ImportMode, ImportSummary } from '../types/settings';
+
+export function exportConfig(path: string, options: ExportOptions): Promise<void> {
+  return invoke<void>('export_config', { path, options });
+}
+
+// merge 合并进本机的配置与记忆，replace 以包中的为准（记忆由所有档案共用，会全部清空）
+export function importConfig(path: string, mode: ImportMode): Promise<ImportSummary> {
+  return invoke<ImportSummary>('import_config', { path, mode });
+}
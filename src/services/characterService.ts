@@ -0,0 +1,57 @@
+/**
+ * @fileoverview 角色包服务
+ * @description 调用后端角色包命令，并生成 character:// 协议下的立绘地址
+ * @features
+ *   - 角色包列表、安装、卸载、切换
+ *   - 导入 LingChat 角色目录
+ *   - 每只桌宠当前角色的表情注册表
+ *   - 立绘与头像地址（兼容 Windows 的 http://character.localhost 形式）
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+import { convertFileSrc, invoke } from '@tauri-apps/api/core';
+import type { CharacterSummary, LingChatImport } from '../types/character';
+import type { EmotionRegistry } from '../types/emotion';
+
+// 表情立绘地址，角色包中没有该表情时后端返回默认表情的立绘
+export function spriteUrl(characterId: string, emotion: string): string {
+  return convertFileSrc(`${characterId}/sprites/${emotion}`, 'character');
+}
+
+export function avatarUrl(characterId: string): string {
+  return convertFileSrc(`${characterId}/avatar`, 'character');
+}
+
+export function listCharacters(): Promise<CharacterSummary[]> {
+  return invoke<CharacterSummary[]>('list_characters');
+}
+
+// 桌宠当前使用的角色，不传 pet 时是主桌宠
+export function getActiveCharacter(pet?: string): Promise<CharacterSummary> {
+  return invoke<CharacterSummary>('get_active_character', { pet });
+}
+
+// path 为角色包目录或 zip 文件
+export function installCharacter(path: string): Promise<CharacterSummary> {
+  return invoke<CharacterSummary>('install_character', { path });
+}
+
+// path 为 LingChat 角色目录（包含 settings.txt 与 avatar/），导入后切换到该角色
+export function importLingChatCharacter(path: string): Promise<LingChatImport> {
+  return invoke<LingChatImport>('import_lingchat_character', { path });
+}
+
+export function uninstallCharacter(id: string): Promise<void> {
+  return invoke('uninstall_character', { id });
+}
+
+export function switchCharacter(id: string): Promise<CharacterSummary> {
+  return invoke<CharacterSummary>('switch_character', { id });
+}
+
+// 桌宠当前角色的表情注册表，不传 pet 时是主桌宠
+export function listEmotions(pet?: string): Promise<EmotionRegistry> {
+  return invoke<EmotionRegistry>('list_emotions', { pet });
+}
@@ -0,0 +1,46 @@
+/**
+ * @fileoverview 命令错误处理
+ * @description 把 invoke 抛出的错误统一为 AppError，并生成带处理建议的说明
+ * @features
+ *   - 识别后端返回的 AppError，其他错误归为 internal
+ *   - 配置文件无法解析时附上行列
+ *   - 取出快捷键的逐项错误
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+import type { AppError, ConfigParseDetails, HotkeyErrorDetails } from '../types/error';
+import type { HotkeyError } from '../types/settings';
+
+export function isAppError(error: unknown): error is AppError {
+  return typeof error === 'object' && error !== null && 'kind' in error && 'message' in error;
+}
+
+export function toAppError(error: unknown): AppError {
+  if (isAppError(error)) {
+    return error;
+  }
+  const message = error instanceof Error ? error.message : String(error);
+  return { kind: 'internal', message, details: null };
+}
+
+// 配置文件无法解析时返回出错的位置，其他错误返回 null
+export function configParseDetails(error: AppError): ConfigParseDetails | null {
+  return error.kind === 'config_parse' && error.details ? (error.details as unknown as ConfigParseDetails) : null;
+}
+
+// 快捷键无效、冲突或无法注册时的逐项错误，其他错误返回空数组
+export function hotkeyErrors(error: AppError): HotkeyError[] {
+  return error.kind === 'hotkey' && error.details ? (error.details as unknown as HotkeyErrorDetails).errors : [];
+}
+
+// 面向用户的说明，配置文件无法解析时附上行列
+export function errorMessage(error: unknown): string {
+  const appError = toAppError(error);
+  const location = configParseDetails(appError);
+  if (location?.line) {
+    return `${appError.message}（第 ${location.line} 行，第 ${location.column ?? 1} 列）`;
+  }
+  return appError.message;
+}
@@ -5,13 +5,15 @@
  *   - 设置预览事件 (实时预览，不保存)
  *   - 设置确定事件 (最终确认并应用)
  *   - 设置保存事件 (后端保存完成通知)
- *   - AI配置变更事件
+ *   - 配置变更事件 (后端发出，只携带变化的分区)
+ *   - 配置错误事件 (配置文件被外部修改后无法读取)
  *   - 事件监听器和发射器封装
  * @events
  *   预览事件: preview-pet-size, preview-pet-opacity, preview-pet-border
  *   确定事件: pet-size-changed, pet-opacity-changed, pet-border-changed
  *   保存事件: pet-size-saved, pet-opacity-saved, pet-border-saved
  *   AI事件: ai-config-saved
+ *   配置事件: config-changed, config-error
  * @dependencies
  *   - @tauri-apps/api/event: Tauri事件API
  * @author dada
@@ -20,24 +22,29 @@
  */
 
 // 事件总线组合式函数，用于组件间通信
-import { listen, emit } from '@tauri-apps/api/event';
-import { AppSetting } from '../types/settings';
+import { listen } from '@tauri-apps/api/event';
+import type { AppError } from '../types/error';
+import type { ConfigChanged } from '../types/settings';
 
 export function eventBusService() {
-  // 设置变更，一般需要保存
-  async function emitConfigChanged(appConfig: AppSetting) {
-    await emit('config-changed', appConfig);
+  // 后端在配置落盘或配置文件被外部修改后发出，是各窗口配置的唯一来源
+  async function onConfigChanged(receiver: string, callback: (change: ConfigChanged) => void) {
+    await listen<ConfigChanged>('config-changed', (event) => {
+      console.log(`${receiver} received a config-changed event:`, event.payload.sections);
+      callback(event.payload);
+    });
   }
 
-  async function onConfigChanged(receiver: string, callback: (appConfig: AppSetting) => void) {
-    await listen('config-changed', (event) => {
-      console.log(`${receiver} received a config-changed event`);
-      callback(event.payload as AppSetting);
+  // 配置文件被外部修改后无法读取时由后端发出，内存中的配置保持不变
+  async function onConfigError(receiver: string, callback: (error: AppError) => void) {
+    await listen<AppError>('config-error', (event) => {
+      console.log(`${receiver} received a config-error event:`, event.payload.kind);
+      callback(event.payload);
     });
   }
 
   return {
     onConfigChanged,
-    emitConfigChanged,
+    onConfigError,
   };
 }
@@ -0,0 +1,35 @@
+/**
+ * @fileoverview 全局快捷键服务
+ * @description 调用后端快捷键命令并监听快捷键事件；快捷键由后端注册，应用不在前台时也能触发
+ * @features
+ *   - 修改快捷键，无效、冲突或无法注册时逐项返回错误且不保存
+ *   - 读取并监听当前无法使用的快捷键
+ *   - 桌宠窗口接收需要自己处理的操作（聚焦输入框、播放下一句）
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import type { AppSetting, HotkeyAction, HotkeyError, HotkeySetting } from '../types/settings';
+
+// 只提交修改的快捷键，返回修改后的完整配置
+export function patchHotkeys(patch: Partial<HotkeySetting>): Promise<AppSetting> {
+  return invoke<AppSetting>('patch_hotkeys', { patch });
+}
+
+export function getHotkeyErrors(): Promise<HotkeyError[]> {
+  return invoke<HotkeyError[]>('get_hotkey_errors');
+}
+
+// 配置被外部修改或切换档案后重新注册快捷键时，后端发出全部无法使用的快捷键
+export function onHotkeyErrors(callback: (errors: HotkeyError[]) => void): Promise<UnlistenFn> {
+  return listen<HotkeyError[]>('hotkey-errors', (event) => callback(event.payload));
+}
+
+// 后端只把聚焦输入框与播放下一句发给对应的桌宠窗口
+export function onHotkey(callback: (action: HotkeyAction) => void): Promise<UnlistenFn> {
+  return getCurrentWebviewWindow().listen<HotkeyAction>('hotkey', (event) => callback(event.payload));
+}
@@ -0,0 +1,33 @@
+/**
+ * @fileoverview 桌宠服务
+ * @description 调用后端多桌宠命令；每只桌宠有自己的窗口、角色与配置记录，主桌宠的配置在外观与窗口分区中
+ * @features
+ *   - 列出、添加、移除桌宠
+ *   - 修改某只桌宠的角色、位置、大小与透明度
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { AppSetting, PetInfo, PetSetting } from '../types/settings';
+
+// 主桌宠在前，其余按 id 排序
+export function listPets(): Promise<PetInfo[]> {
+  return invoke<PetInfo[]>('list_pets');
+}
+
+// 添加一只桌宠并打开它的窗口，不传角色时与主桌宠相同
+export function addPet(character?: string): Promise<PetInfo> {
+  return invoke<PetInfo>('add_pet', { character });
+}
+
+// 关闭桌宠的窗口并从配置中移除，对话历史保留；主桌宠不能移除
+export function removePet(id: string): Promise<void> {
+  return invoke<void>('remove_pet', { id });
+}
+
+// 只提交修改的字段，返回修改后的完整配置
+export function patchPet(id: string, patch: Partial<PetSetting>): Promise<AppSetting> {
+  return invoke<AppSetting>('patch_pet', { id, patch });
+}
@@ -0,0 +1,40 @@
+/**
+ * @fileoverview 配置档案服务
+ * @description 调用后端配置档案命令；切换档案后变化的配置由后端 config-changed 事件推送
+ * @features
+ *   - 档案列表
+ *   - 新建、复制、改名、删除档案
+ *   - 切换当前档案
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { ProfileInfo } from '../types/settings';
+
+export function listProfiles(): Promise<ProfileInfo[]> {
+  return invoke<ProfileInfo[]>('list_profiles');
+}
+
+// 新建使用默认配置的档案
+export function createProfile(name: string): Promise<ProfileInfo[]> {
+  return invoke<ProfileInfo[]>('create_profile', { name });
+}
+
+export function cloneProfile(source: string, name: string): Promise<ProfileInfo[]> {
+  return invoke<ProfileInfo[]>('clone_profile', { source, name });
+}
+
+export function renameProfile(name: string, newName: string): Promise<ProfileInfo[]> {
+  return invoke<ProfileInfo[]>('rename_profile', { name, newName });
+}
+
+// 当前使用的档案不能删除
+export function deleteProfile(name: string): Promise<ProfileInfo[]> {
+  return invoke<ProfileInfo[]>('delete_profile', { name });
+}
+
+export function activateProfile(name: string): Promise<ProfileInfo[]> {
+  return invoke<ProfileInfo[]>('activate_profile', { name });
+}
@@ -3,20 +3,32 @@ import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 import { useWindowSize } from '@vueuse/core';
 import { useConfigStore } from '../stores/config';
 import { throttle } from 'lodash';
+import { currentPetId, MAIN_PET } from './windowService';
 
 /**
- * 窗口大小管理
+ * 窗口大小管理，主窗口与其他桌宠的窗口共用
  */
 export function useMainWindowResize() {
   const configStore = useConfigStore();
   const appWindow = getCurrentWebviewWindow();
   const { width, height } = useWindowSize();
+  const pet = currentPetId();
+
+  // 配置中记录的窗口中心
+  function savedCenter() {
+    const record = configStore.config.pets[pet];
+    if (pet === MAIN_PET || !record) {
+      return { x: configStore.window.main_window_x, y: configStore.window.main_window_y };
+    }
+    return { x: record.x, y: record.y };
+  }
 
   function computeLogicalMainWindowState(petSize: number) {
     const logicalSize = new LogicalSize(petSize + 80, petSize + 80);
+    const center = savedCenter();
     const logicalPosition = new LogicalPosition(
-      configStore.window.main_window_x - logicalSize.width / 2,
-      configStore.window.main_window_y - logicalSize.height / 2
+      center.x - logicalSize.width / 2,
+      center.y - logicalSize.height / 2
     );
     return { logicalSize, logicalPosition };
   }
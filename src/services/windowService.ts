@@ -0,0 +1,80 @@
+/**
+ * @fileoverview 窗口服务
+ * @description 调用后端窗口管理命令；窗口由后端按标签创建与登记，聊天气泡的位置与跟随也由后端计算
+ * @features
+ *   - 按窗口标签得到当前窗口所属的桌宠
+ *   - 打开与隐藏设置窗口
+ *   - 显示聊天气泡、追加句子、切换当前句、关闭气泡（默认是当前窗口所属桌宠的气泡）
+ *   - 监听后端推送给当前气泡窗口的 bubble-state 事件
+ * @author dada
+ * @version 2.0.0
+ * @since 2025-07-13
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { UnlistenFn } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import { DEFAULT_EMOTION } from '../constants/emotions';
+import type { BubbleMessage, BubbleOptions, BubbleState, WindowInfo } from '../types/window';
+
+// 主桌宠的 id，与主窗口的标签相同
+export const MAIN_PET = 'main';
+
+// 窗口标签与后端 window_manager 保持一致：其他桌宠是 pet:<id>，它们的气泡是 chat-bubble:<id>
+const PET_LABEL_PREFIX = 'pet:';
+const BUBBLE_LABEL_PREFIX = 'chat-bubble:';
+
+// 当前窗口所属的桌宠；主窗口、主桌宠的气泡以及设置窗口都是主桌宠
+export function currentPetId(): string {
+  const label = getCurrentWebviewWindow().label;
+  for (const prefix of [PET_LABEL_PREFIX, BUBBLE_LABEL_PREFIX]) {
+    if (label.startsWith(prefix)) {
+      return label.slice(prefix.length);
+    }
+  }
+  return MAIN_PET;
+}
+
+export function listWindows(): Promise<WindowInfo[]> {
+  return invoke<WindowInfo[]>('list_windows');
+}
+
+export function openSettings(): Promise<void> {
+  return invoke<void>('open_settings');
+}
+
+// 只隐藏设置窗口，下次打开时保留正在编辑的内容
+export function closeSettings(): Promise<void> {
+  return invoke<void>('close_settings');
+}
+
+// 在桌宠旁显示一组句子，从第一句开始
+export function showBubble(messages: BubbleMessage[], options?: BubbleOptions, pet = currentPetId()): Promise<BubbleState> {
+  return invoke<BubbleState>('show_bubble', { messages, options, pet });
+}
+
+// 显示一条提示（错误、配置恢复等），打字完成后自动隐藏
+export function showNotification(message: string, options?: BubbleOptions): Promise<BubbleState> {
+  return showBubble([{ message, emotion: DEFAULT_EMOTION, japanese: '' }], options);
+}
+
+export function appendBubbleMessages(messages: BubbleMessage[], pet = currentPetId()): Promise<BubbleState | null> {
+  return invoke<BubbleState | null>('append_bubble_messages', { messages, pet });
+}
+
+export function setBubbleIndex(index: number, pet = currentPetId()): Promise<BubbleState | null> {
+  return invoke<BubbleState | null>('set_bubble_index', { index, pet });
+}
+
+export function closeBubble(pet = currentPetId()): Promise<void> {
+  return invoke<void>('close_bubble', { pet });
+}
+
+export function getBubbleState(pet = currentPetId()): Promise<BubbleState | null> {
+  return invoke<BubbleState | null>('get_bubble_state', { pet });
+}
+
+// 气泡内容变化时由后端发给对应的气泡窗口，只接收发给当前窗口的事件
+export function onBubbleState(callback: (state: BubbleState) => void): Promise<UnlistenFn> {
+  return getCurrentWebviewWindow().listen<BubbleState>('bubble-state', (event) => callback(event.payload));
+}
@@ -1,53 +1,111 @@
 import { defineStore } from 'pinia'
 import { invoke } from '@tauri-apps/api/core'
 import { eventBusService } from '../services/eventBus'
-import type { AppSetting, AppearanceSetting } from '../types/settings';
+import type { AppError } from '../types/error';
+import type {
+  AppSetting, AppearanceSetting, ConfigChanged, ConfigLayer, ConfigRecovery, ConfigSection, FieldError,
+} from '../types/settings';
 import { DEFAULT_SETTINGS } from '../constants/settings_default';
+import { currentPetId, MAIN_PET } from '../services/windowService';
 import { getCurrentWebview } from '@tauri-apps/api/webview';
 import { watch } from 'vue';
 import { throttle } from 'lodash';
 
-const { onConfigChanged, emitConfigChanged } = eventBusService()
+// 可以局部提交的配置分区，对应后端 patch_* 命令；其他桌宠通过 patch_pet 逐只提交
+type PatchableSection = Exclude<ConfigSection, 'character' | 'pets'>;
+
+const { onConfigChanged, onConfigError } = eventBusService()
 
 
 export const useConfigStore = defineStore('config', {
-  state: (): { config: AppSetting } => ({
+  state: (): {
+    config: AppSetting, fieldErrors: Record<string, string>, sources: Record<string, ConfigLayer>,
+    configError: AppError | null,
+  } => ({
     config: DEFAULT_SETTINGS,
+    fieldErrors: {},    // 后端校验出的字段错误，键为 分区.字段
+    sources: {},        // 每个字段的取值来自哪一层，键为 分区.字段
+    configError: null,  // 配置文件被外部修改后无法读取的错误，重新读取成功后清除
   }),
   actions: {
     async initialize(isSettingWindow: boolean = false) {
       // 初始化时加载配置
       await this.loadConfig();
-      // 如果不是设置窗口就需要监听配置变更事件
-      if (!isSettingWindow) {
-        onConfigChanged(getCurrentWebview().label, (appConfig: AppSetting) => {
-          Object.assign(this.config, appConfig);
-          this.throttledSaveConfig();
-        });
-      } else {
-        // 设置窗口需要发射配置变更事件
+      // 设置窗口维护外观与AI配置，主窗口维护窗口位置，其他桌宠的窗口直接提交自己的位置；
+      // 自己维护的分区只接受外部修改，避免落盘前的旧值覆盖正在编辑的内容
+      const ownSections: ConfigSection[] = isSettingWindow
+        ? ['appearance', 'ai']
+        : currentPetId() === MAIN_PET ? ['window'] : [];
+      onConfigChanged(getCurrentWebview().label, (change: ConfigChanged) => {
+        this.configError = null;
+        this.applyChange(change, ownSections);
+      });
+      onConfigError(getCurrentWebview().label, (error: AppError) => {
+        this.configError = error;
+      });
+      if (isSettingWindow) {
+        // 设置窗口的修改提交到后端，落盘后由后端通知其他窗口
         watch(
-          () => this.config,
-          (newConfig) => {
-            console.log('来自store的配置变更提醒:', newConfig);
-            emitConfigChanged(newConfig);
-          },
+          () => [this.config.appearance, this.config.ai],
+          () => this.throttledPatchSettings(),
           { deep: true }
         );
       }
     },
+    // 把后端 config-changed 事件中变化的分区合并进本地配置
+    applyChange(change: ConfigChanged, ownSections: ConfigSection[] = []) {
+      for (const section of change.sections) {
+        if (change.source === 'save' && ownSections.includes(section)) {
+          continue;
+        }
+        if (section === 'pets') {
+          // 整体替换，已移除的桌宠随之消失
+          this.config.pets = change.pets ?? {};
+          continue;
+        }
+        const value = change[section];
+        if (value) {
+          Object.assign(this.config[section], value);
+        }
+      }
+    },
     async loadConfig() {
       const cfg = await invoke<AppSetting>('load_config')
       console.log('从后端加载配置:', cfg);
       Object.assign(this.config, cfg)
+      this.sources = await invoke<Record<string, ConfigLayer>>('get_config_sources')
     },
-    async saveConfig() {
-      console.log(`配置已保存: ${JSON.stringify(this.config)}`);
-      await invoke('save_config', { config: this.config })
+    // 由后端校验当前配置，结果记录在 fieldErrors 中，返回是否有效
+    async validateConfig() {
+      const errors = await invoke<FieldError[]>('validate_config', { config: this.config })
+      this.fieldErrors = Object.fromEntries(errors.map((error) => [error.field, error.message]))
+      return errors.length === 0
     },
-    throttledSaveConfig: throttle(
+    // 启动时配置文件损坏并已从备份恢复的情况，只返回一次
+    async takeConfigRecovery() {
+      return await invoke<ConfigRecovery | null>('take_config_recovery')
+    },
+    // 只提交某个分区，后端合并进内存中的配置，不会覆盖其他窗口修改的分区
+    async patchSection(section: PatchableSection, patch: object) {
+      await invoke(`patch_${section}`, { patch })
+    },
+    // 设置窗口修改的外观与AI配置
+    throttledPatchSettings: throttle(
+      async function (this: any) {
+        // 校验未通过时不提交，错误显示在表单上
+        if (!(await this.validateConfig())) {
+          return;
+        }
+        await this.patchSection('appearance', this.config.appearance);
+        await this.patchSection('ai', this.config.ai);
+      },
+      1000,
+      { leading: true, trailing: true }
+    ),
+    // 主窗口维护的窗口位置
+    throttledPatchWindow: throttle(
       async function (this: any) {
-        await this.saveConfig();
+        await this.patchSection('window', this.config.window);
       },
       1000,
       { leading: true, trailing: true }
@@ -62,5 +120,16 @@ export const useConfigStore = defineStore('config', {
     appearance: (state) => state.config.appearance,
     ai: (state) => state.config.ai,
     window: (state) => state.config.window,
+    // 桌宠使用的角色包，主桌宠是当前角色；已被移除的桌宠沿用当前角色
+    petCharacter: (state) => (pet: string): string =>
+      state.config.pets[pet]?.character ?? state.config.character.active,
+    // 被环境变量或命令行覆盖的字段的提示；在设置中修改这些字段不会生效
+    overrideHint: (state) => (field: string): string | undefined => {
+      switch (state.sources[field]) {
+        case 'env': return '已由环境变量指定，此处的修改不会生效'
+        case 'cli': return '已由命令行参数指定，此处的修改不会生效'
+        default: return undefined
+      }
+    },
   }
 })
\ No newline at end of file
@@ -15,6 +15,8 @@
  * @since 2025-07-13
  */
 
+import type { AppError } from './error';
+
 // AI 相关类型定义
 
 import { EmotionName } from "./emotion";
@@ -67,7 +69,59 @@ export interface PetResponseItem {
 export interface PetResponse {
   success: boolean;
   data?: PetResponseItem[];
-  error?: string;
+  error?: AppError;
+}
+
+// 流式对话事件负载（pet-stream-item）
+export interface PetStreamItem {
+  stream_id: string;
+  index: number;
+  item: PetResponseItem;
+}
+
+export interface PetStreamResult {
+  success: boolean;
+  count: number;
+  cancelled: boolean;
+  error?: AppError;
+}
+
+// 对话历史（SQLite 持久化）
+export interface ChatSession {
+  id: number;
+  pet: string;  // 会话所属的桌宠 id
+  title: string;
+  created_at: number;
+  updated_at: number;
+  message_count: number;
+}
+
+export interface ChatHistoryMessage {
+  id: number;
+  turn: number;
+  role: 'user' | 'assistant';
+  content: string;
+  emotion?: EmotionName;
+  japanese?: string;
+  created_at: number;
+}
+
+export interface ChatMessagePage {
+  messages: ChatHistoryMessage[];
+  page: number;
+  page_size: number;
+  total: number;
+}
+
+// 长期记忆
+export interface PetMemory {
+  id: number;
+  content: string;
+  source: 'auto' | 'manual';
+  session_id?: number | null;
+  turn?: number | null;
+  created_at: number;
+  updated_at: number;
 }
 
 export interface ConversationContext {
@@ -0,0 +1,32 @@
+/**
+ * @fileoverview 角色包相关类型定义
+ * @description 定义后端角色包（character.toml）提供给前端的信息
+ * @interfaces
+ *   - CharacterSummary: 角色包信息 (名称、表情列表、默认表情等)
+ *   - LingChatImport: 导入 LingChat 角色的结果
+ * @usage
+ *   import type { CharacterSummary, LingChatImport } from '@/types/character'
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+export interface CharacterSummary {
+  id: string;
+  name: string;
+  version: string;
+  author: string;
+  description: string;
+  default_emotion: string;
+  emotions: string[];
+  has_avatar: boolean;
+  builtin: boolean;
+}
+
+export interface LingChatImport {
+  character: CharacterSummary;
+  // 无法对应到已知表情、未被导入的图片
+  unmapped_images: string[];
+  // 没有立绘的表情
+  missing_emotions: string[];
+}
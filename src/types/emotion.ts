@@ -1,25 +1,32 @@
 /**
  * @fileoverview 表情相关类型定义
- * @description 定义桌面宠物表情名称和状态的TypeScript类型，提供表情管理的类型安全
+ * @description 定义桌面宠物表情名称、表情注册表和状态的TypeScript类型
  * @types
- *   - EmotionName: 表情文件名联合类型 (19种表情)
+ *   - EmotionName: 表情名，可用表情由当前角色包的表情注册表决定
+ *   - Emotion: 单个表情 (名称、英文标识、别名、显示颜色)
+ *   - EmotionRegistry: 后端 list_emotions 返回的表情注册表
  *   - EmotionState: 表情状态接口 (当前表情、抖动状态)
- * @emotions
- *   正常、高兴、伤心、生气、害怕、惊讶、厌恶、害羞、兴奋、担心、
- *   调皮、慌张、紧张、认真、无奈、心动、羞耻、自信、疑惑
  * @usage
- *   import type { EmotionName, EmotionState } from '@/types/emotion'
+ *   import type { EmotionName, EmotionRegistry, EmotionState } from '@/types/emotion'
  * @author dada
- * @version 1.0.0
+ * @version 2.0.0
  * @since 2025-07-13
  */
 
 // 表情相关类型定义
-export type EmotionName = 
-  | "正常" | "高兴" | "伤心" | "生气" | "害怕"
-  | "惊讶" | "厌恶" | "羞愤" | "兴奋" | "担心"
-  | "调皮" | "慌张" | "紧张" | "认真" | "无奈"
-  | "心动" | "羞耻" | "自信" | "疑惑";
+export type EmotionName = string;
+
+export interface Emotion {
+  name: EmotionName;
+  key: string | null;
+  aliases: string[];
+  color: string | null;
+}
+
+export interface EmotionRegistry {
+  emotions: Emotion[];
+  default_emotion: EmotionName;
+}
 
 export interface EmotionState {
   currentEmotion: EmotionName;
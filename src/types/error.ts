@@ -0,0 +1,53 @@
+/**
+ * @fileoverview 命令错误类型定义
+ * @description 后端命令失败时返回的 AppError，kind 是稳定的机器可读类型，可据此给出对应的处理建议
+ * @interfaces
+ *   - AppError: 命令错误 (kind、message、details)
+ *   - ConfigParseDetails: 配置文件无法解析时的文件与行列
+ *   - HotkeyErrorDetails: 快捷键无法使用时的逐项错误
+ * @usage
+ *   import type { AppError } from '@/types/error'
+ * @author dada
+ * @version 1.0.0
+ * @since 2025-07-13
+ */
+
+import type { HotkeyError } from './settings'
+
+export type AppErrorKind =
+  | 'io'
+  | 'config_parse'
+  | 'config_invalid'
+  | 'config_version'
+  | 'invalid_input'
+  | 'not_found'
+  | 'conflict'
+  | 'secrets'
+  | 'ai_config'
+  | 'ai_network'
+  | 'ai_api'
+  | 'ai_response'
+  | 'window'
+  | 'character'
+  | 'database'
+  | 'internal'
+  | 'hotkey'
+  | 'ai_paused'
+
+export interface AppError {
+  kind: AppErrorKind
+  message: string
+  details: Record<string, unknown> | null  // 结构化的补充信息，没有时为 null
+}
+
+// kind 为 config_parse 时的 details，行列从 1 开始
+export interface ConfigParseDetails {
+  path: string | null
+  line: number | null
+  column: number | null
+}
+
+// kind 为 hotkey 时的 details
+export interface HotkeyErrorDetails {
+  errors: HotkeyError[]
+}
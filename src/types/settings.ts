@@ -3,6 +3,7 @@
  * @description 定义宠物设置、约束条件、预览状态等相关的TypeScript类型接口
  * @interfaces
  *   - PetSettings: 宠物基础设置 (大小、透明度、边框)
+ *   - PetSetting: 其他桌宠的配置 (角色、位置、大小、透明度)
  *   - SettingsConstraints: 设置约束条件 (最小/最大值限制)
  *   - SettingsPreview: 预览状态设置 (实时显示但不保存)
  * @usage
@@ -19,28 +20,152 @@ export interface AppearanceSetting {
   pet_show_border: boolean
 }
 
+export type AIProvider = 'openai' | 'anthropic' | 'ollama'
+
 export interface AISetting {
-  api_key: string
+  provider: AIProvider
+  api_key: string                // 已保存的密钥由后端替换为占位符，原样提交表示不修改
   base_url: string
   model: string
   temperature: number
   max_tokens: number
-  system_prompt?: string | null
+  system_prompt?: string | null  // 为空时使用当前角色包的人设
+  repair_invalid_reply: boolean  // 回复无法解析时让模型重试一次
+  history_turns: number          // 每次请求最多回放的历史轮数
+  context_budget: number         // 提示词 token 预算，超出部分折叠为摘要
+  auto_memory: boolean           // 每轮对话后自动提取长期记忆
+  memory_recall_limit: number    // 每次请求注入的相关记忆条数上限
 }
 
 export interface WindowSetting {
   main_window_x: number
   main_window_y: number
+  main_window_monitor?: string | null  // 保存位置时所在的显示器
+  main_window_scale?: number | null    // 保存位置时的缩放比例
   settings_window_x?: number | null   // 注意，这里指的是窗口的中心位置
   settings_window_y?: number | null
   settings_window_width?: number | null
   settings_window_height?: number | null
 }
 
+export interface CharacterSetting {
+  active: string  // 当前角色包 id
+}
+
+// 主桌宠之外的桌宠，位置与主桌宠一样是窗口中心的逻辑坐标
+export interface PetSetting {
+  character: string      // 角色包 id
+  x: number
+  y: number
+  monitor?: string | null  // 保存位置时所在的显示器
+  scale?: number | null    // 保存位置时的缩放比例
+  size: number
+  opacity: number
+}
+
+// 由托盘菜单切换的运行状态
+export interface BehaviorSetting {
+  pets_visible: boolean  // 是否显示所有桌宠
+  ai_paused: boolean     // 暂停时不再发起 AI 对话
+}
+
+// 全局快捷键，写法如 CommandOrControl+Shift+Space，留空表示不使用
+export interface HotkeySetting {
+  focus_chat: string            // 显示主桌宠并聚焦聊天输入框
+  play_next: string             // 播放当前对话的下一句
+  toggle_visible: string        // 显示或隐藏所有桌宠
+  toggle_click_through: string  // 切换桌宠窗口的鼠标穿透
+}
+
+// 快捷键对应的操作，随 hotkey 事件发给桌宠窗口
+export type HotkeyAction = 'focus_chat' | 'play_next' | 'toggle_visible' | 'toggle_click_through'
+
+// 无法使用的快捷键：写法无效 (invalid)、与其他操作重复 (conflict)、被其他程序占用 (unavailable)
+export interface HotkeyError {
+  field: string    // hotkeys.字段，如 hotkeys.focus_chat
+  binding: string
+  reason: 'invalid' | 'conflict' | 'unavailable'
+  message: string
+}
+
 export interface AppSetting {
+  schema_version: number  // 配置结构版本，由后端维护
   appearance: AppearanceSetting
   ai: AISetting
   window: WindowSetting
+  character: CharacterSetting
+  pets: Record<string, PetSetting>  // 键为桌宠 id
+  behavior: BehaviorSetting
+  hotkeys: HotkeySetting
+}
+
+// 一只桌宠（后端 list_pets），主桌宠的 id 是 main
+export interface PetInfo {
+  id: string
+  character: string
+}
+
+// 后端校验配置得到的字段错误
+export interface FieldError {
+  field: string    // 分区.字段，如 ai.base_url
+  message: string
+}
+
+// 配置文件损坏或含有无效字段时的恢复情况（后端 take_config_recovery）
+export interface ConfigRecovery {
+  message: string
+  restored_backup: string | null  // 为 null 时没有从备份恢复
+  corrupt_file: string | null     // 损坏的配置保留为该文件名；只是含有无效字段时为 null
+  invalid_fields: FieldError[]    // 已恢复为默认值的字段
+}
+
+// 配置分区，对应后端 ConfigSection
+export type ConfigSection = 'appearance' | 'ai' | 'window' | 'character' | 'pets' | 'behavior' | 'hotkeys'
+
+// 后端 config-changed 事件：配置落盘 (save) 或配置文件被外部修改 (external) 后，只携带发生变化的分区
+export interface ConfigChanged {
+  source: 'save' | 'external' | 'profile'
+  sections: ConfigSection[]
+  appearance?: AppearanceSetting
+  ai?: AISetting
+  window?: WindowSetting
+  character?: CharacterSetting
+  pets?: Record<string, PetSetting>  // 变化后的全部其他桌宠
+  behavior?: BehaviorSetting
+  hotkeys?: HotkeySetting
+}
+
+// 配置取值来自哪一层（后端 get_config_sources）：默认值 < 配置文件 < 环境变量 < 命令行
+export type ConfigLayer = 'default' | 'file' | 'env' | 'cli'
+
+// 配置档案（后端 list_profiles）
+export interface ProfileInfo {
+  name: string
+  active: boolean  // 当前使用的档案
+}
+
+// 配置包导出选项，默认都不导出
+export interface ExportOptions {
+  include_secrets: boolean  // 以明文导出 API 密钥
+  include_window: boolean   // 导出窗口位置
+}
+
+// 配置包导入方式：merge 合并进本机的配置与记忆，replace 以包中的为准（记忆由所有档案共用，会全部清空）
+export type ImportMode = 'merge' | 'replace'
+
+// 配置包中引用的角色包
+export interface CharacterRef {
+  id: string
+  name: string
+  version: string
+  builtin: boolean
+}
+
+// 配置包导入结果（后端 import_config）
+export interface ImportSummary {
+  memories_imported: number
+  missing_characters: CharacterRef[]  // 本机没有安装的角色包
+  kept_character: boolean             // 包中的当前角色本机没有，沿用了本机的角色
 }
 
 export interface SettingsConstraints {
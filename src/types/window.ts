@@ -13,3 +13,39 @@ export interface DragState {
   isDragging: boolean;
   startPosition?: WindowPosition;
 }
+
+// 后端登记的窗口
+export type WindowKind = 'pet' | 'bubble' | 'settings';
+
+export interface WindowInfo {
+  label: string;
+  kind: WindowKind;
+  pet: string | null;  // 桌宠与气泡窗口所属的桌宠
+  visible: boolean;
+}
+
+// 聊天气泡中的一句话
+export interface BubbleMessage {
+  message: string;
+  emotion: string;
+  japanese: string;
+}
+
+// 气泡在桌宠的哪一侧，靠近屏幕顶部时翻到下方
+export type BubbleSide = 'above' | 'below';
+
+export interface BubbleOptions {
+  auto_hide?: boolean;       // 只有一句话时，打字完成后自动隐藏
+  auto_hide_delay?: number;  // 自动隐藏前停留的毫秒数
+}
+
+// 气泡内容，由后端维护并通过 bubble-state 事件推送
+export interface BubbleState {
+  id: number;  // 每次显示新的一组句子时递增
+  pet: string; // 气泡所属的桌宠
+  messages: BubbleMessage[];
+  index: number;
+  auto_hide: boolean;
+  auto_hide_delay: number;
+  side: BubbleSide;
+}